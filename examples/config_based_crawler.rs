@@ -77,6 +77,11 @@ async fn main() -> Result<(), Box<dyn Error>> {
             println!("  Prefix: {}", s3_config.prefix);
             UriType::S3(s3_config.bucket.clone(), s3_config.region.clone())
         }
+        CrawlerConfigType::Warc(warc_config) => {
+            println!("WARC replay configuration:");
+            println!("  Path: {}", warc_config.path);
+            UriType::Warc(warc_config.path.clone())
+        }
     };
 
     // Create a Pages builder with the URI and configuration
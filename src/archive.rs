@@ -0,0 +1,518 @@
+use crate::parsers::{ContentTypeDetector, ParserOptionsSelector, ParserRegistry, ParserType};
+use crate::results::PageData;
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::io::{Cursor, Read};
+use zip::ZipArchive;
+
+/// Separator between the URL of an archive and the path of a member inside it
+pub const MEMBER_SEPARATOR: &str = "!/";
+
+/// Error type for archive expansion
+pub type ArchiveError = Box<dyn Error + Send + Sync>;
+
+/// Archive formats whose members can be crawled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    /// ZIP archives, including `.jar` files
+    Zip,
+    /// Uncompressed tarballs
+    Tar,
+    /// Gzip-compressed tarballs (`.tar.gz`, `.tgz`)
+    TarGz,
+}
+
+impl ArchiveFormat {
+    /// Identifies an archive from its leading bytes
+    ///
+    /// Tarballs are recognised by the `ustar` magic of their first header; gzipped ones
+    /// without it are still accepted when their URL names a tarball, as old tools wrote them.
+    ///
+    /// Office documents and e-books are ZIP packages too, but they're parsed as documents.
+    pub fn detect(url: &str, bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(b"PK\x03\x04") || bytes.starts_with(b"PK\x05\x06") {
+            return (ParserType::sniff(bytes) == Some(ParserType::Other)).then_some(Self::Zip);
+        }
+
+        if bytes.starts_with(b"\x1f\x8b") {
+            // Only gzipped tarballs are archives; other gzip files are single documents
+            let mut head = Vec::with_capacity(512);
+            let _ = GzDecoder::new(bytes).take(512).read_to_end(&mut head);
+            let path = url_path(url);
+            return (is_tar_header(&head) || path.ends_with(".tar.gz") || path.ends_with(".tgz"))
+                .then_some(Self::TarGz);
+        }
+
+        // Anything else named `.tar` is parsed as a plain file
+        is_tar_header(bytes).then_some(Self::Tar)
+    }
+}
+
+/// Returns whether a block starts with a POSIX or GNU tar header
+fn is_tar_header(bytes: &[u8]) -> bool {
+    bytes.get(257..262) == Some(b"ustar")
+}
+
+/// Returns the lowercased path of a URL, without query or fragment
+fn url_path(url: &str) -> String {
+    url.split(['?', '#'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+/// Options for crawling the members of archives
+///
+/// The limits guard against archive bombs: small files that expand to huge
+/// amounts of data or nest archives many levels deep.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArchiveOptions {
+    /// Whether to expand archives into their members; disabled archives are skipped
+    pub enabled: bool,
+
+    /// How many levels of archives inside archives are expanded
+    pub max_depth: usize,
+
+    /// Most members read from one archive, counting nested archives
+    pub max_entries: usize,
+
+    /// Largest member read, in bytes; larger members are skipped
+    pub max_entry_size: u64,
+
+    /// Most bytes extracted from one archive, counting nested archives
+    pub max_total_size: u64,
+}
+
+impl Default for ArchiveOptions {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_depth: 3,
+            max_entries: 10_000,
+            max_entry_size: 16 * 1024 * 1024,
+            max_total_size: 128 * 1024 * 1024,
+        }
+    }
+}
+
+/// A file extracted from an archive
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArchiveMember {
+    /// URL of the member: the archive URL, `!/` and the path inside the archive
+    pub url: String,
+    /// URL of the archive directly containing the member
+    pub archive_url: String,
+    /// Path of the member inside its archive
+    pub path: String,
+    /// Contents of the member
+    pub bytes: Vec<u8>,
+}
+
+/// Extracts the files of an archive, expanding nested archives
+///
+/// Members beyond the limits are skipped with a warning rather than failing
+/// the whole archive. Returns an error only if the archive can't be opened.
+pub fn expand(
+    url: &str,
+    bytes: &[u8],
+    format: ArchiveFormat,
+    options: &ArchiveOptions,
+) -> Result<Vec<ArchiveMember>, ArchiveError> {
+    let mut expander = Expander {
+        options,
+        entries_left: options.max_entries,
+        bytes_left: options.max_total_size,
+        members: Vec::new(),
+    };
+    expander.expand(url, bytes, format, 0)?;
+    Ok(expander.members)
+}
+
+/// Parses the members of an archive into pages
+///
+/// Each member is parsed like a fetched resource, by a registered parser or the
+/// built-in one its type is detected as. Binary members no parser handles are skipped.
+pub fn expand_to_pages(
+    url: &str,
+    bytes: &[u8],
+    format: ArchiveFormat,
+    options: &ArchiveOptions,
+    detector: &ContentTypeDetector,
+    registry: &ParserRegistry,
+    parser_options: &ParserOptionsSelector,
+) -> Result<Vec<PageData>, ArchiveError> {
+    let mut pages = Vec::new();
+    for member in expand(url, bytes, format, options)? {
+        let parser_type = detector.detect(&member.url, None, Some(&member.bytes));
+        let parser = match registry.find(&member.url, None, parser_type) {
+            Some(parser) => parser,
+            None if parser_type == ParserType::Other => {
+                ::log::debug!("Skipping binary archive member: {}", member.url);
+                continue;
+            }
+            None => registry.resolve(&member.url, None, parser_type),
+        };
+
+        let options = parser_options.for_url(&member.url);
+        let mut result = parser.parse_bytes(&member.bytes, None, Some(&member.url), options);
+        result.resolve_links(&member.url);
+
+        let mut page = PageData::from_parse_result(&member.url, result);
+        page.metadata.archive_url = Some(member.archive_url);
+        page.source = options.keep_source.then_some(member.bytes);
+        pages.push(page);
+    }
+    Ok(pages)
+}
+
+/// Walks an archive and the archives nested in it, sharing the limits between them
+struct Expander<'a> {
+    options: &'a ArchiveOptions,
+    entries_left: usize,
+    bytes_left: u64,
+    members: Vec<ArchiveMember>,
+}
+
+/// Result of reading one member
+enum Extracted {
+    /// The member's contents
+    Data(Vec<u8>),
+    /// The member is larger than the per-member limit
+    TooLarge,
+    /// The archive's total size limit was reached
+    Exhausted,
+}
+
+impl Expander<'_> {
+    fn expand(
+        &mut self,
+        url: &str,
+        bytes: &[u8],
+        format: ArchiveFormat,
+        depth: usize,
+    ) -> Result<(), ArchiveError> {
+        match format {
+            ArchiveFormat::Zip => self.expand_zip(url, bytes, depth),
+            ArchiveFormat::Tar => self.expand_tar(url, bytes, depth),
+            ArchiveFormat::TarGz => {
+                // The decompressed stream can't outgrow what may still be extracted
+                let stream = GzDecoder::new(bytes).take(self.bytes_left);
+                self.expand_tar(url, stream, depth)
+            }
+        }
+    }
+
+    fn expand_zip(&mut self, url: &str, bytes: &[u8], depth: usize) -> Result<(), ArchiveError> {
+        let mut archive = ZipArchive::new(Cursor::new(bytes))?;
+        for index in 0..archive.len() {
+            let file = match archive.by_index(index) {
+                Ok(file) => file,
+                Err(e) => {
+                    ::log::warn!("Skipping unreadable member {} of {}: {}", index, url, e);
+                    continue;
+                }
+            };
+            if !file.is_file() {
+                continue;
+            }
+            let name = file.name().to_string();
+            if file.size() > self.options.max_entry_size {
+                ::log::warn!("Skipping oversized member {} of {}", name, url);
+                continue;
+            }
+            if !self.add(url, &name, file, depth) {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn expand_tar(
+        &mut self,
+        url: &str,
+        reader: impl Read,
+        depth: usize,
+    ) -> Result<(), ArchiveError> {
+        let mut archive = tar::Archive::new(reader);
+        for entry in archive.entries()? {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    // Tar streams can't be resynchronized after a bad header
+                    ::log::warn!("Stopped reading {} early: {}", url, e);
+                    break;
+                }
+            };
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let name = entry.path_bytes().into_owned();
+            let name = String::from_utf8_lossy(&name).into_owned();
+            if entry.size() > self.options.max_entry_size {
+                ::log::warn!("Skipping oversized member {} of {}", name, url);
+                continue;
+            }
+            if !self.add(url, &name, entry, depth) {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads a member, expanding it if it is an archive itself
+    ///
+    /// Returns `false` once a limit stops the whole expansion.
+    fn add(&mut self, archive_url: &str, name: &str, reader: impl Read, depth: usize) -> bool {
+        let Some(path) = normalize_path(name) else {
+            ::log::warn!(
+                "Skipping member with unsafe path {} in {}",
+                name,
+                archive_url
+            );
+            return true;
+        };
+        if self.entries_left == 0 {
+            ::log::warn!(
+                "Stopped expanding {} after {} members",
+                archive_url,
+                self.options.max_entries
+            );
+            return false;
+        }
+        self.entries_left -= 1;
+
+        let bytes = match self.extract(reader) {
+            Extracted::Data(bytes) => bytes,
+            Extracted::TooLarge => {
+                ::log::warn!("Skipping oversized member {} of {}", path, archive_url);
+                return true;
+            }
+            Extracted::Exhausted => {
+                ::log::warn!(
+                    "Stopped expanding {} after {} bytes",
+                    archive_url,
+                    self.options.max_total_size
+                );
+                return false;
+            }
+        };
+
+        let url = format!("{}{}{}", archive_url, MEMBER_SEPARATOR, path);
+        if let Some(format) = ArchiveFormat::detect(&url, &bytes) {
+            if depth + 1 > self.options.max_depth {
+                ::log::warn!("Skipping archive nested too deeply: {}", url);
+            } else if let Err(e) = self.expand(&url, &bytes, format, depth + 1) {
+                ::log::warn!("Failed to expand nested archive {}: {}", url, e);
+            }
+            return true;
+        }
+
+        self.members.push(ArchiveMember {
+            url,
+            archive_url: archive_url.to_string(),
+            path,
+            bytes,
+        });
+        true
+    }
+
+    /// Reads a member within the per-member and total size limits
+    fn extract(&mut self, reader: impl Read) -> Extracted {
+        let limit = self.options.max_entry_size.min(self.bytes_left);
+        let mut bytes = Vec::new();
+        // A member's declared size can lie, so read one byte past the limit to notice
+        if let Err(e) = reader.take(limit + 1).read_to_end(&mut bytes) {
+            ::log::debug!("Failed to read archive member: {}", e);
+        }
+
+        let read = bytes.len() as u64;
+        self.bytes_left = self.bytes_left.saturating_sub(read);
+        if read <= limit {
+            Extracted::Data(bytes)
+        } else if read > self.options.max_entry_size {
+            Extracted::TooLarge
+        } else {
+            Extracted::Exhausted
+        }
+    }
+}
+
+/// Normalizes a member path to `/`-separated segments
+///
+/// Returns `None` for empty paths and paths escaping the archive with `..`.
+fn normalize_path(name: &str) -> Option<String> {
+    let mut segments = Vec::new();
+    for segment in name.split(['/', '\\']) {
+        match segment {
+            "" | "." => {}
+            ".." => return None,
+            segment => segments.push(segment),
+        }
+    }
+    (!segments.is_empty()).then(|| segments.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::ZipWriter;
+    use zip::write::SimpleFileOptions;
+
+    fn zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in files {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(content).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    fn tar_gz(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        for (name, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, name, *content).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[test]
+    fn test_detect() {
+        let archive = zip(&[("a.txt", b"a")]);
+        assert_eq!(
+            ArchiveFormat::detect("https://example.org/a.bin", &archive),
+            Some(ArchiveFormat::Zip)
+        );
+        let archive = tar_gz(&[("a.txt", b"a")]);
+        assert_eq!(
+            ArchiveFormat::detect("https://example.org/download", &archive),
+            Some(ArchiveFormat::TarGz)
+        );
+        assert_eq!(
+            ArchiveFormat::detect("https://example.org/a.tar.gz", b"<html>"),
+            None
+        );
+
+        let mut builder = tar::Builder::new(Vec::new());
+        let mut header = tar::Header::new_ustar();
+        header.set_size(1);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "a.txt", &b"a"[..])
+            .unwrap();
+        let archive = builder.into_inner().unwrap();
+        assert_eq!(
+            ArchiveFormat::detect("https://example.org/a.bin", &archive),
+            Some(ArchiveFormat::Tar)
+        );
+        // A `.tar` URL without a tar header is an error page or a mislabelled file
+        assert_eq!(
+            ArchiveFormat::detect("https://example.org/a.tar", b"Not found"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_nested_member_urls() {
+        let inner = tar_gz(&[("./docs/intro.md", b"# Intro")]);
+        let outer = zip(&[
+            ("README.txt", b"Read me"),
+            ("dist/", b""),
+            ("dist/docs.tar.gz", &inner),
+            ("../escape.txt", b"nope"),
+        ]);
+
+        let members = expand(
+            "https://example.org/bundle.zip",
+            &outer,
+            ArchiveFormat::Zip,
+            &ArchiveOptions::default(),
+        )
+        .unwrap();
+        let urls: Vec<&str> = members.iter().map(|m| m.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            vec![
+                "https://example.org/bundle.zip!/README.txt",
+                "https://example.org/bundle.zip!/dist/docs.tar.gz!/docs/intro.md",
+            ]
+        );
+        assert_eq!(
+            members[1].archive_url,
+            "https://example.org/bundle.zip!/dist/docs.tar.gz"
+        );
+        assert_eq!(members[1].bytes, b"# Intro");
+    }
+
+    #[test]
+    fn test_limits() {
+        let big = vec![b'a'; 2000];
+        let inner = zip(&[("deep.txt", b"deep")]);
+        let archive = zip(&[
+            ("big.txt", &big),
+            ("one.txt", b"1"),
+            ("nested.zip", &inner),
+            ("two.txt", b"2"),
+        ]);
+        let options = ArchiveOptions {
+            max_depth: 0,
+            max_entries: 2,
+            max_entry_size: 1000,
+            ..ArchiveOptions::default()
+        };
+
+        let members = expand("a.zip", &archive, ArchiveFormat::Zip, &options).unwrap();
+        let paths: Vec<&str> = members.iter().map(|m| m.path.as_str()).collect();
+        // The oversized member and the too-deep archive are skipped; the entry limit stops the rest
+        assert_eq!(paths, vec!["one.txt"]);
+
+        let options = ArchiveOptions {
+            max_total_size: 1500,
+            ..ArchiveOptions::default()
+        };
+        let archive = zip(&[("one.txt", &big[..1000]), ("two.txt", &big[..1000])]);
+        let members = expand("a.zip", &archive, ArchiveFormat::Zip, &options).unwrap();
+        assert_eq!(members.len(), 1);
+    }
+
+    #[test]
+    fn test_expand_to_pages() {
+        let archive = tar_gz(&[
+            ("docs/intro.md", b"# Intro\n\nSee [setup](setup.md)."),
+            ("logo.png", b"\x89PNG\r\n\x1a\n\x00\x00"),
+        ]);
+        let pages = expand_to_pages(
+            "https://example.org/release.tar.gz",
+            &archive,
+            ArchiveFormat::TarGz,
+            &ArchiveOptions::default(),
+            &ContentTypeDetector::default(),
+            &ParserRegistry::new(),
+            &ParserOptionsSelector::new(Default::default(), &[]).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(pages.len(), 1);
+        assert_eq!(
+            pages[0].url,
+            "https://example.org/release.tar.gz!/docs/intro.md"
+        );
+        assert_eq!(pages[0].title.as_deref(), Some("Intro"));
+        assert_eq!(
+            pages[0].link_details[0].url,
+            "https://example.org/release.tar.gz!/docs/setup.md"
+        );
+        assert_eq!(
+            pages[0].metadata.archive_url.as_deref(),
+            Some("https://example.org/release.tar.gz")
+        );
+    }
+}
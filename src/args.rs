@@ -1,16 +1,76 @@
-use clap::{Parser, ValueEnum};
+use clap::{Parser, Subcommand, ValueEnum};
+use std::path::{Path, PathBuf};
 use yield_page::UriType;
+use yield_page::sinks::{Compression, MirrorFiles, SearchIndex, SqliteSink};
 
 #[derive(Parser, Debug)]
 #[command(name = "yield-page")]
 #[command(author = "Ryan Northey <ryan@synca.io>")]
 #[command(about = "Crawler that yields pages from various URI types")]
 #[command(version)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// Source URI to crawl (web URL, git repo, file path, etc.)
-    pub uri: String,
+    #[arg(required = true)]
+    pub uri: Option<String>,
+
+    #[command(flatten)]
+    pub crawl: CrawlArgs,
+
+    /// Write pages to this file instead of stdout
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Output format (defaults to the output file's extension: .db, .sqlite, .sqlite3 for SQLite;
+    /// an existing search index or other directory for a search index or mirror)
+    #[arg(short, long, value_enum)]
+    pub format: Option<OutputFormatArg>,
+
+    /// Output compression (defaults to the output file's extension: .gz, .zst)
+    #[arg(long, value_enum)]
+    pub compression: Option<CompressionArg>,
+
+    /// Only write these page fields (comma-separated, e.g. url,title,content)
+    #[arg(long, value_delimiter = ',')]
+    pub fields: Vec<String>,
+
+    /// Don't write a summary record after the last page
+    #[arg(long)]
+    pub no_summary: bool,
+
+    /// Files to write for each page of a mirror (comma-separated)
+    #[arg(long, value_enum, value_delimiter = ',', default_value = "raw")]
+    pub mirror_files: Vec<MirrorFileArg>,
 
-    /// URI type (web, git, file, s3)
+    /// Keep the original links in mirrored HTML pages instead of pointing them at the local copies
+    #[arg(long)]
+    pub no_rewrite_links: bool,
+
+    /// Export the link graph to this file (.graphml, .dot or .csv edge list; repeatable)
+    #[arg(long)]
+    pub graph: Vec<PathBuf>,
+
+    /// Write link graph metrics (degrees, PageRank, click depth, orphans) to this file (.json or .csv)
+    #[arg(long)]
+    pub graph_metrics: Option<PathBuf>,
+
+    /// Keep ETags, Last-Modified dates and content hashes in this file, and only scrape
+    /// pages that changed since the last crawl using it
+    #[arg(long, value_name = "STATE")]
+    pub incremental: Option<PathBuf>,
+
+    /// With --incremental, still write unchanged pages to JSON Lines, marked `"unchanged": true`
+    #[arg(long, requires = "incremental")]
+    pub emit_unchanged: bool,
+}
+
+/// Options for how a source is crawled, shared by the crawl and `check-links`
+#[derive(clap::Args, Debug)]
+pub struct CrawlArgs {
+    /// URI type (web, warc, git, file, s3)
     #[arg(short, long, value_enum, default_value_t = UriTypeArg::Web)]
     pub type_: UriTypeArg,
 
@@ -27,9 +87,123 @@ pub struct Args {
     pub total_timeout: u64,
 }
 
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Search a full-text index written by a crawl with `--format search`
+    Search(SearchArgs),
+    /// Crawl a site and check every link on it, exiting with 1 if any are broken or fail to crawl
+    CheckLinks(CheckLinksArgs),
+    /// Crawl a site and report SEO and content quality issues on its pages
+    Audit(AuditArgs),
+    /// Compare two stored crawls (JSON Lines or SQLite) and report the pages that changed
+    Diff(DiffArgs),
+}
+
+#[derive(clap::Args, Debug)]
+pub struct CheckLinksArgs {
+    /// Source URI to crawl
+    pub uri: String,
+
+    #[command(flatten)]
+    pub crawl: CrawlArgs,
+
+    /// Number of links probed at once
+    #[arg(long, default_value_t = 8)]
+    pub probe_concurrency: usize,
+
+    /// Seconds to wait for each probe
+    #[arg(long, default_value_t = 30)]
+    pub probe_timeout: u64,
+
+    /// Redirects followed before a link counts as broken
+    #[arg(long, default_value_t = 10)]
+    pub max_redirects: usize,
+
+    /// Don't check that `#fragment` links point at an element on the target page
+    #[arg(long)]
+    pub no_anchors: bool,
+
+    /// List redirected links too, not only failures
+    #[arg(long)]
+    pub show_redirects: bool,
+
+    /// Print the report as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct AuditArgs {
+    /// Source URI to crawl
+    pub uri: String,
+
+    #[command(flatten)]
+    pub crawl: CrawlArgs,
+
+    /// Write every issue found to this file (CSV for `.csv`, otherwise JSON)
+    #[arg(long)]
+    pub report: Option<PathBuf>,
+
+    /// Print the full report as JSON instead of the summary table
+    #[arg(long)]
+    pub json: bool,
+
+    /// Pages with fewer words than this are reported as thin content
+    #[arg(long, default_value_t = 200)]
+    pub min_words: usize,
+
+    /// Pages larger than this many KiB are reported as oversized
+    #[arg(long, default_value_t = 1024)]
+    pub max_page_kb: usize,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct DiffArgs {
+    /// Earlier crawl: a JSON Lines file (optionally `.gz`/`.zst`) or a `.db`/`.sqlite`/`.sqlite3` database
+    pub old: PathBuf,
+
+    /// Later crawl, in either format
+    pub new: PathBuf,
+
+    /// Only list the pages that changed, without diffs of their text
+    #[arg(long)]
+    pub no_content_diff: bool,
+
+    /// Unchanged lines shown around each change
+    #[arg(long, default_value_t = 3)]
+    pub context: usize,
+
+    /// Ignore pages whose text changed by less than this share (0.0 to 1.0)
+    #[arg(long, default_value_t = 0.0)]
+    pub min_change: f64,
+
+    /// Print the report as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct SearchArgs {
+    /// Directory of the search index
+    pub index: PathBuf,
+
+    /// Query, e.g. `install guide`, `title:faq` or `"exact phrase"`
+    #[arg(required = true, num_args = 1..)]
+    pub query: Vec<String>,
+
+    /// Maximum number of results
+    #[arg(short = 'n', long, default_value_t = 10)]
+    pub limit: usize,
+
+    /// Print results as JSON Lines
+    #[arg(long)]
+    pub json: bool,
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
 pub enum UriTypeArg {
     Web,
+    Warc,
     // Uncomment these as they're implemented
     // Git,
     // File,
@@ -40,6 +214,74 @@ pub enum UriTypeArg {
 pub fn convert_uri_type(arg_type: UriTypeArg, uri: &str) -> UriType {
     match arg_type {
         UriTypeArg::Web => UriType::Web(uri.to_string()),
+        UriTypeArg::Warc => UriType::Warc(uri.to_string()),
         // Add other URI types as they're implemented
     }
 }
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormatArg {
+    /// One JSON object per page
+    Jsonl,
+    /// A SQLite database of pages, links, errors and crawl runs
+    Sqlite,
+    /// A directory tree of page files mirroring the site's URLs
+    Mirror,
+    /// A full-text search index directory, queried with the `search` subcommand
+    Search,
+}
+
+/// Resolve the output format, falling back to the output file's extension
+pub fn output_format(arg: Option<OutputFormatArg>, output: Option<&Path>) -> OutputFormatArg {
+    if let Some(format) = arg {
+        return format;
+    }
+    if let Some(path) = output.filter(|path| path.is_dir()) {
+        return if SearchIndex::exists(path) {
+            OutputFormatArg::Search
+        } else {
+            OutputFormatArg::Mirror
+        };
+    }
+    if output.is_some_and(SqliteSink::is_database_path) {
+        OutputFormatArg::Sqlite
+    } else {
+        OutputFormatArg::Jsonl
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum CompressionArg {
+    None,
+    Gzip,
+    Zstd,
+}
+
+/// Convert from CLI compression argument, falling back to the output file's extension
+pub fn convert_compression(arg: Option<CompressionArg>, output: Option<&Path>) -> Compression {
+    match arg {
+        Some(CompressionArg::None) => Compression::None,
+        Some(CompressionArg::Gzip) => Compression::Gzip,
+        Some(CompressionArg::Zstd) => Compression::Zstd,
+        None => output.map_or(Compression::None, Compression::from_path),
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum MirrorFileArg {
+    /// The page as fetched
+    Raw,
+    /// The extracted text
+    Text,
+    /// The extracted text as Markdown
+    Markdown,
+}
+
+/// Convert from CLI mirror file arguments to the files written for each page
+pub fn convert_mirror_files(args: &[MirrorFileArg]) -> MirrorFiles {
+    MirrorFiles {
+        raw: args.contains(&MirrorFileArg::Raw),
+        text: args.contains(&MirrorFileArg::Text),
+        markdown: args.contains(&MirrorFileArg::Markdown),
+    }
+}
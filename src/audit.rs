@@ -0,0 +1,552 @@
+use crate::parsers::{LinkKind, MediaKind};
+use crate::results::{CrawlError, PageData};
+use crate::utils::without_fragment;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::io::Write;
+use std::path::Path;
+
+/// Error type for writing audit reports
+pub type AuditError = Box<dyn Error + Send + Sync>;
+
+/// Thresholds for the audit checks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditOptions {
+    /// Pages with fewer words of text than this are thin
+    #[serde(default = "default_min_words")]
+    pub min_words: usize,
+
+    /// Pages whose source is larger than this many bytes are oversized
+    #[serde(default = "default_max_page_bytes")]
+    pub max_page_bytes: usize,
+}
+
+fn default_min_words() -> usize {
+    200
+}
+
+fn default_max_page_bytes() -> usize {
+    1024 * 1024
+}
+
+impl Default for AuditOptions {
+    fn default() -> Self {
+        Self {
+            min_words: default_min_words(),
+            max_page_bytes: default_max_page_bytes(),
+        }
+    }
+}
+
+/// Kind of problem found on a page
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    /// No `<title>`
+    MissingTitle,
+    /// The same title as other pages
+    DuplicateTitle,
+    /// No `<meta name="description">`
+    MissingDescription,
+    /// The same meta description as other pages
+    DuplicateDescription,
+    /// More than one `<h1>`
+    MultipleH1,
+    /// Less text than the configured minimum
+    ThinContent,
+    /// An image without an `alt` attribute
+    MissingAltText,
+    /// Kept out of search indexes by `<meta name="robots">`
+    Noindex,
+    /// `<link rel="canonical">` points at another URL
+    CanonicalMismatch,
+    /// An HTTPS page loading a resource, frame or media over HTTP
+    MixedContent,
+    /// The page source is larger than the configured maximum
+    OversizedPage,
+}
+
+impl IssueKind {
+    /// Every kind of issue, in report order
+    pub const ALL: [IssueKind; 11] = [
+        IssueKind::MissingTitle,
+        IssueKind::DuplicateTitle,
+        IssueKind::MissingDescription,
+        IssueKind::DuplicateDescription,
+        IssueKind::MultipleH1,
+        IssueKind::ThinContent,
+        IssueKind::MissingAltText,
+        IssueKind::Noindex,
+        IssueKind::CanonicalMismatch,
+        IssueKind::MixedContent,
+        IssueKind::OversizedPage,
+    ];
+
+    /// Name of the issue as it appears in reports
+    pub fn name(self) -> &'static str {
+        match self {
+            IssueKind::MissingTitle => "missing_title",
+            IssueKind::DuplicateTitle => "duplicate_title",
+            IssueKind::MissingDescription => "missing_description",
+            IssueKind::DuplicateDescription => "duplicate_description",
+            IssueKind::MultipleH1 => "multiple_h1",
+            IssueKind::ThinContent => "thin_content",
+            IssueKind::MissingAltText => "missing_alt_text",
+            IssueKind::Noindex => "noindex",
+            IssueKind::CanonicalMismatch => "canonical_mismatch",
+            IssueKind::MixedContent => "mixed_content",
+            IssueKind::OversizedPage => "oversized_page",
+        }
+    }
+}
+
+/// A problem found on a page
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditIssue {
+    /// URL of the page
+    pub url: String,
+    /// Kind of problem
+    pub kind: IssueKind,
+    /// What was found, e.g. the duplicated title or the image missing alt text
+    pub detail: String,
+}
+
+/// Number of issues of one kind
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IssueCount {
+    /// Kind of problem
+    pub kind: IssueKind,
+    /// Number of pages with the problem
+    pub pages: usize,
+    /// Number of times the problem was found
+    pub issues: usize,
+}
+
+/// Results of auditing the pages of a crawl
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditReport {
+    /// Number of HTML pages audited
+    pub pages_audited: usize,
+    /// Counts per kind of issue, including kinds that weren't found
+    pub summary: Vec<IssueCount>,
+    /// Every issue found, by page URL then kind
+    pub issues: Vec<AuditIssue>,
+    /// URLs the crawl failed on, which couldn't be audited
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub crawl_errors: Vec<CrawlError>,
+}
+
+impl AuditReport {
+    /// Writes the summary as an aligned table
+    pub fn write_summary(&self, mut writer: impl Write) -> std::io::Result<()> {
+        writeln!(writer, "{:<24} {:>6} {:>7}", "Issue", "Pages", "Issues")?;
+        for count in &self.summary {
+            writeln!(
+                writer,
+                "{:<24} {:>6} {:>7}",
+                count.kind.name(),
+                count.pages,
+                count.issues
+            )?;
+        }
+
+        if !self.crawl_errors.is_empty() {
+            writeln!(writer, "Failed to crawl")?;
+            for error in &self.crawl_errors {
+                writeln!(writer, "  {} ({})", error.url, error.message)?;
+            }
+        }
+
+        write!(writer, "{} pages audited", self.pages_audited)?;
+        if !self.crawl_errors.is_empty() {
+            write!(
+                writer,
+                ", {} pages failed to crawl",
+                self.crawl_errors.len()
+            )?;
+        }
+        writeln!(writer)
+    }
+
+    /// Writes the issues as CSV, one row per issue, followed by the pages that failed
+    /// to crawl as `crawl_error` rows
+    pub fn write_csv(&self, writer: impl Write) -> Result<(), AuditError> {
+        let mut csv = csv::Writer::from_writer(writer);
+        csv.write_record(["url", "issue", "detail"])?;
+        for issue in &self.issues {
+            csv.write_record([issue.url.as_str(), issue.kind.name(), issue.detail.as_str()])?;
+        }
+        for error in &self.crawl_errors {
+            csv.write_record([error.url.as_str(), "crawl_error", error.message.as_str()])?;
+        }
+        csv.flush()?;
+        Ok(())
+    }
+
+    /// Writes the report to a file: CSV of the issues for `.csv`, otherwise JSON
+    pub fn write_file(&self, path: &Path) -> Result<(), AuditError> {
+        let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        let csv = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+        if csv {
+            self.write_csv(file)
+        } else {
+            serde_json::to_writer_pretty(file, self)?;
+            Ok(())
+        }
+    }
+}
+
+/// Audits crawled HTML pages for common SEO and content quality problems
+///
+/// Most checks look at one page at a time; duplicate titles and descriptions are
+/// found across all pages when the report is made. Pages that aren't HTML are
+/// skipped, and the size check needs pages crawled with their source kept.
+#[derive(Debug, Default)]
+pub struct Auditor {
+    options: AuditOptions,
+    pages: usize,
+    issues: Vec<AuditIssue>,
+    /// Pages by normalized title
+    titles: HashMap<String, Vec<String>>,
+    /// Pages by normalized description
+    descriptions: HashMap<String, Vec<String>>,
+    /// URLs the crawl failed on
+    crawl_errors: Vec<CrawlError>,
+}
+
+impl Auditor {
+    /// Creates an auditor with the given thresholds
+    pub fn new(options: AuditOptions) -> Self {
+        Self {
+            options,
+            ..Self::default()
+        }
+    }
+
+    /// Checks a crawled page
+    pub fn add_page(&mut self, page: &PageData) {
+        let html = page
+            .metadata
+            .content_type
+            .as_deref()
+            .is_none_or(|ct| ct.contains("html"));
+        if !html {
+            return;
+        }
+        self.pages += 1;
+        let url = page.url.as_str();
+
+        match page
+            .title
+            .as_deref()
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+        {
+            Some(title) => self
+                .titles
+                .entry(title.to_lowercase())
+                .or_default()
+                .push(url.to_string()),
+            None => self.issue(url, IssueKind::MissingTitle, String::new()),
+        }
+        match page.metadata.description.as_deref() {
+            Some(description) => self
+                .descriptions
+                .entry(description.to_lowercase())
+                .or_default()
+                .push(url.to_string()),
+            None => self.issue(url, IssueKind::MissingDescription, String::new()),
+        }
+
+        let h1s: Vec<&str> = page
+            .headings
+            .iter()
+            .filter(|heading| heading.level == 1)
+            .map(|heading| heading.text.as_str())
+            .collect();
+        if h1s.len() > 1 {
+            self.issue(url, IssueKind::MultipleH1, h1s.join(" | "));
+        }
+
+        let words = page.content.split_whitespace().count();
+        if words < self.options.min_words {
+            self.issue(url, IssueKind::ThinContent, format!("{} words", words));
+        }
+
+        for image in page.media.iter().filter(|m| m.kind == MediaKind::Image) {
+            if image.alt.is_none() {
+                let src = image.url.clone().unwrap_or_default();
+                self.issue(url, IssueKind::MissingAltText, src);
+            }
+        }
+
+        let robots = &page.metadata.robots;
+        if robots.iter().any(|d| d == "noindex" || d == "none") {
+            self.issue(url, IssueKind::Noindex, robots.join(", "));
+        }
+
+        for canonical in page
+            .link_details
+            .iter()
+            .filter(|link| link.kind == LinkKind::Canonical)
+        {
+            if !same_url(&canonical.url, url) {
+                self.issue(url, IssueKind::CanonicalMismatch, canonical.url.clone());
+            }
+        }
+
+        if url.starts_with("https://") {
+            let loaded = page
+                .link_details
+                .iter()
+                .filter(|link| {
+                    matches!(
+                        link.kind,
+                        LinkKind::Resource | LinkKind::Iframe | LinkKind::Frame
+                    )
+                })
+                .map(|link| link.url.clone())
+                .chain(page.media.iter().filter_map(|m| m.url.clone()));
+            let insecure: Vec<String> = loaded.filter(|u| u.starts_with("http://")).collect();
+            for resource in insecure {
+                self.issue(url, IssueKind::MixedContent, resource);
+            }
+        }
+
+        if let Some(source) = &page.source
+            && source.len() > self.options.max_page_bytes
+        {
+            self.issue(
+                url,
+                IssueKind::OversizedPage,
+                format!("{} bytes", source.len()),
+            );
+        }
+    }
+
+    fn issue(&mut self, url: &str, kind: IssueKind, detail: String) {
+        self.issues.push(AuditIssue {
+            url: url.to_string(),
+            kind,
+            detail,
+        });
+    }
+
+    /// Records a URL the crawl failed on, so the report lists it
+    pub fn add_error(&mut self, error: &CrawlError) {
+        self.crawl_errors.push(error.clone());
+    }
+
+    /// Finds duplicates across the pages checked and returns the report
+    pub fn report(mut self) -> AuditReport {
+        for (kind, groups) in [
+            (IssueKind::DuplicateTitle, &self.titles),
+            (IssueKind::DuplicateDescription, &self.descriptions),
+        ] {
+            for pages in groups.values().filter(|pages| pages.len() > 1) {
+                for url in pages {
+                    let others: Vec<&str> = pages
+                        .iter()
+                        .filter(|other| *other != url)
+                        .map(String::as_str)
+                        .collect();
+                    self.issues.push(AuditIssue {
+                        url: url.clone(),
+                        kind,
+                        detail: format!("also on {}", others.join(", ")),
+                    });
+                }
+            }
+        }
+
+        self.issues
+            .sort_by(|a, b| (&a.url, a.kind).cmp(&(&b.url, b.kind)));
+
+        let mut counts: BTreeMap<IssueKind, (Vec<&str>, usize)> = BTreeMap::new();
+        for issue in &self.issues {
+            let (pages, issues) = counts.entry(issue.kind).or_default();
+            if pages.last() != Some(&issue.url.as_str()) {
+                pages.push(&issue.url);
+            }
+            *issues += 1;
+        }
+        let summary = IssueKind::ALL
+            .iter()
+            .map(|&kind| {
+                let (pages, issues) = counts.get(&kind).cloned().unwrap_or_default();
+                IssueCount {
+                    kind,
+                    pages: pages.len(),
+                    issues,
+                }
+            })
+            .collect();
+
+        AuditReport {
+            pages_audited: self.pages,
+            summary,
+            issues: self.issues,
+            crawl_errors: self.crawl_errors,
+        }
+    }
+}
+
+/// Compares URLs ignoring fragments and the difference between `/path` and `/path/`
+fn same_url(a: &str, b: &str) -> bool {
+    without_fragment(a).trim_end_matches('/') == without_fragment(b).trim_end_matches('/')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::html;
+
+    fn page(url: &str, html: &str) -> PageData {
+        let mut page = PageData::from_parse_result(url, html::parse_with_url(html, url));
+        page.metadata.content_type = Some("text/html".to_string());
+        page.source = Some(html.as_bytes().to_vec());
+        page
+    }
+
+    #[test]
+    fn test_audit() {
+        let mut auditor = Auditor::new(AuditOptions {
+            min_words: 5,
+            max_page_bytes: 400,
+        });
+        auditor.add_page(&page(
+            "https://example.org/",
+            r#"<html><head><title>Docs</title>
+                <meta name="description" content="All the docs">
+                <link rel="canonical" href="https://example.org/">
+                </head><body><h1>Docs</h1><p>Welcome to the documentation site.</p>
+                <img src="/logo.png" alt="Logo"></body></html>"#,
+        ));
+        auditor.add_page(&page(
+            "https://example.org/guide",
+            r#"<html><head><title> docs </title>
+                <meta name="description" content="All the docs">
+                <meta name="robots" content="noindex">
+                <link rel="canonical" href="https://example.org/guide/v2">
+                <link rel="stylesheet" href="http://cdn.example.org/site.css">
+                </head><body><h1>Guide</h1><h1>Again</h1><p>Short.</p>
+                <img src="/diagram.png"></body></html>"#,
+        ));
+        auditor.add_page(&page(
+            "https://example.org/bare",
+            "<p>No head at all, only text here.</p>",
+        ));
+        let mut feed = PageData::new(
+            "https://example.org/feed.json".to_string(),
+            None,
+            String::new(),
+            Vec::new(),
+        );
+        feed.metadata.content_type = Some("application/json".to_string());
+        auditor.add_page(&feed);
+
+        let report = auditor.report();
+        assert_eq!(report.pages_audited, 3);
+
+        let kinds = |url: &str| -> Vec<IssueKind> {
+            report
+                .issues
+                .iter()
+                .filter(|issue| issue.url == url)
+                .map(|issue| issue.kind)
+                .collect()
+        };
+        assert_eq!(
+            kinds("https://example.org/"),
+            vec![IssueKind::DuplicateTitle, IssueKind::DuplicateDescription]
+        );
+        assert_eq!(
+            kinds("https://example.org/guide"),
+            vec![
+                IssueKind::DuplicateTitle,
+                IssueKind::DuplicateDescription,
+                IssueKind::MultipleH1,
+                IssueKind::ThinContent,
+                IssueKind::MissingAltText,
+                IssueKind::Noindex,
+                IssueKind::CanonicalMismatch,
+                IssueKind::MixedContent,
+                IssueKind::OversizedPage,
+            ]
+        );
+        assert_eq!(
+            kinds("https://example.org/bare"),
+            vec![IssueKind::MissingTitle, IssueKind::MissingDescription]
+        );
+
+        let detail = |url: &str, kind| {
+            report
+                .issues
+                .iter()
+                .find(|issue| issue.url == url && issue.kind == kind)
+                .map(|issue| issue.detail.as_str())
+                .unwrap()
+        };
+        assert_eq!(
+            detail("https://example.org/", IssueKind::DuplicateTitle),
+            "also on https://example.org/guide"
+        );
+        assert_eq!(
+            detail("https://example.org/guide", IssueKind::MixedContent),
+            "http://cdn.example.org/site.css"
+        );
+        assert_eq!(
+            detail("https://example.org/guide", IssueKind::MissingAltText),
+            "https://example.org/diagram.png"
+        );
+
+        let duplicates = &report.summary[1];
+        assert_eq!(duplicates.kind, IssueKind::DuplicateTitle);
+        assert_eq!((duplicates.pages, duplicates.issues), (2, 2));
+        assert_eq!(report.summary.len(), IssueKind::ALL.len());
+
+        let mut table = Vec::new();
+        report.write_summary(&mut table).unwrap();
+        let table = String::from_utf8(table).unwrap();
+        assert!(table.contains("\nduplicate_title               2       2\n"));
+        assert!(table.ends_with("3 pages audited\n"));
+
+        let mut csv = Vec::new();
+        report.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(csv.contains("https://example.org/guide,noindex,noindex\n"));
+    }
+
+    #[test]
+    fn test_crawl_errors() {
+        let mut auditor = Auditor::new(AuditOptions::default());
+        auditor.add_page(&page(
+            "https://example.org/",
+            "<html><head><title>Home</title></head><body></body></html>",
+        ));
+        auditor.add_error(&CrawlError::new("https://example.org/broken", "HTTP 500"));
+        let report = auditor.report();
+        assert_eq!(report.crawl_errors.len(), 1);
+
+        let mut table = Vec::new();
+        report.write_summary(&mut table).unwrap();
+        let table = String::from_utf8(table).unwrap();
+        assert!(table.contains("Failed to crawl\n  https://example.org/broken (HTTP 500)\n"));
+        assert!(table.ends_with("1 pages audited, 1 pages failed to crawl\n"));
+
+        let mut csv = Vec::new();
+        report.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(csv.ends_with("https://example.org/broken,crawl_error,HTTP 500\n"));
+    }
+
+    #[test]
+    fn test_issue_names() {
+        for kind in IssueKind::ALL {
+            assert_eq!(serde_json::to_value(kind).unwrap(), kind.name());
+        }
+    }
+}
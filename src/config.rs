@@ -1,3 +1,7 @@
+use crate::archive::ArchiveOptions;
+use crate::incremental::IncrementalOptions;
+use crate::parsers::{Link, LinkKind, ParserMapping, ParserOptions, ParserOptionsOverride};
+use crate::warc::WarcOptions;
 use serde::{Deserialize, Serialize};
 use std::error::Error;
 use std::fs::File;
@@ -29,6 +33,50 @@ pub struct WebCrawlerConfig {
     /// URL for the WebDriver instance
     #[serde(default = "default_webdriver_url")]
     pub webdriver_url: String,
+
+    /// Kinds of links the crawler follows (anchors, frames, pagination, ...)
+    #[serde(default = "LinkKind::default_follow")]
+    pub follow_links: Vec<LinkKind>,
+
+    /// Whether to skip links marked `rel="nofollow"`
+    #[serde(default)]
+    pub respect_nofollow: bool,
+
+    /// Parser overrides by MIME type or URL pattern, checked before the Content-Type header
+    #[serde(default)]
+    pub parser_mappings: Vec<ParserMapping>,
+
+    /// Languages to keep (e.g. `en`, `pt-br`); pages in other languages are dropped
+    #[serde(default)]
+    pub include_languages: Vec<String>,
+
+    /// Languages to drop (these take precedence over included languages)
+    #[serde(default)]
+    pub exclude_languages: Vec<String>,
+
+    /// Language to keep when a page lists `hreflang` translations of itself
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preferred_language: Option<String>,
+
+    /// Options for parsing content (text normalization, selectors, Markdown dialect, links)
+    #[serde(default)]
+    pub parser_options: ParserOptions,
+
+    /// Parser options for URLs matching a pattern, used instead of `parser_options`
+    #[serde(default)]
+    pub parser_overrides: Vec<ParserOptionsOverride>,
+
+    /// Whether and how far archives (`.zip`, `.jar`, `.tar`, `.tar.gz`) are expanded into their files
+    #[serde(default)]
+    pub archives: ArchiveOptions,
+
+    /// Where to archive raw responses and extracted text as WARC files, if at all
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warc: Option<WarcOptions>,
+
+    /// Where to keep what each URL looked like, to only scrape pages that changed since the last crawl
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub incremental: Option<IncrementalOptions>,
 }
 
 /// Configuration for Git repository crawler
@@ -48,6 +96,18 @@ pub struct GitCrawlerConfig {
     /// Patterns to exclude
     #[serde(default)]
     pub exclude_patterns: Vec<String>,
+
+    /// Options for parsing content (text normalization, selectors, Markdown dialect, links)
+    #[serde(default)]
+    pub parser_options: ParserOptions,
+
+    /// Parser options for URLs matching a pattern, used instead of `parser_options`
+    #[serde(default)]
+    pub parser_overrides: Vec<ParserOptionsOverride>,
+
+    /// Whether and how far archives (`.zip`, `.jar`, `.tar`, `.tar.gz`) are expanded into their files
+    #[serde(default)]
+    pub archives: ArchiveOptions,
 }
 
 /// Configuration for filesystem crawler
@@ -67,6 +127,18 @@ pub struct FilesystemCrawlerConfig {
     /// File patterns to exclude
     #[serde(default)]
     pub exclude_patterns: Vec<String>,
+
+    /// Options for parsing content (text normalization, selectors, Markdown dialect, links)
+    #[serde(default)]
+    pub parser_options: ParserOptions,
+
+    /// Parser options for URLs matching a pattern, used instead of `parser_options`
+    #[serde(default)]
+    pub parser_overrides: Vec<ParserOptionsOverride>,
+
+    /// Whether and how far archives (`.zip`, `.jar`, `.tar`, `.tar.gz`) are expanded into their files
+    #[serde(default)]
+    pub archives: ArchiveOptions,
 }
 
 /// Configuration for S3 crawler
@@ -89,11 +161,56 @@ pub struct S3CrawlerConfig {
     /// File patterns to exclude
     #[serde(default)]
     pub exclude_patterns: Vec<String>,
+
+    /// Options for parsing content (text normalization, selectors, Markdown dialect, links)
+    #[serde(default)]
+    pub parser_options: ParserOptions,
+
+    /// Parser options for URLs matching a pattern, used instead of `parser_options`
+    #[serde(default)]
+    pub parser_overrides: Vec<ParserOptionsOverride>,
+
+    /// Whether and how far archives (`.zip`, `.jar`, `.tar`, `.tar.gz`) are expanded into their files
+    #[serde(default)]
+    pub archives: ArchiveOptions,
+}
+
+/// Configuration for replaying WARC files through the parsers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WarcReplayConfig {
+    /// WARC file, or directory of `.warc` and `.warc.gz` files, to replay
+    pub path: String,
+
+    /// Regex patterns for URLs to include
+    #[serde(default)]
+    pub include_patterns: Vec<String>,
+
+    /// Regex patterns for URLs to exclude
+    #[serde(default)]
+    pub exclude_patterns: Vec<String>,
+
+    /// Parser overrides by MIME type or URL pattern, checked before the Content-Type header
+    #[serde(default)]
+    pub parser_mappings: Vec<ParserMapping>,
+
+    /// Options for parsing content (text normalization, selectors, Markdown dialect, links)
+    #[serde(default)]
+    pub parser_options: ParserOptions,
+
+    /// Parser options for URLs matching a pattern, used instead of `parser_options`
+    #[serde(default)]
+    pub parser_overrides: Vec<ParserOptionsOverride>,
+
+    /// Whether and how far archives (`.zip`, `.jar`, `.tar`, `.tar.gz`) are expanded into their files
+    #[serde(default)]
+    pub archives: ArchiveOptions,
 }
 
 /// Enum containing all crawler configuration types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
+// Only one configuration exists per crawl, so its size doesn't matter
+#[allow(clippy::large_enum_variant)]
 pub enum CrawlerConfigType {
     /// Web crawler configuration
     Web(WebCrawlerConfig),
@@ -106,6 +223,9 @@ pub enum CrawlerConfigType {
 
     /// S3 crawler configuration
     S3(S3CrawlerConfig),
+
+    /// WARC replay configuration
+    Warc(WarcReplayConfig),
 }
 
 impl CrawlerConfigType {
@@ -140,6 +260,21 @@ fn default_max_depth() -> usize {
     10
 }
 
+impl WarcReplayConfig {
+    /// Create a new configuration with default values
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            include_patterns: Vec::new(),
+            exclude_patterns: Vec::new(),
+            parser_mappings: Vec::new(),
+            parser_options: ParserOptions::default(),
+            parser_overrides: Vec::new(),
+            archives: ArchiveOptions::default(),
+        }
+    }
+}
+
 impl WebCrawlerConfig {
     /// Create a new configuration with default values
     pub fn new(start_url: &str) -> Self {
@@ -150,6 +285,25 @@ impl WebCrawlerConfig {
             include_patterns: Vec::new(),
             exclude_patterns: Vec::new(),
             webdriver_url: default_webdriver_url(),
+            follow_links: LinkKind::default_follow(),
+            respect_nofollow: false,
+            parser_mappings: Vec::new(),
+            include_languages: Vec::new(),
+            exclude_languages: Vec::new(),
+            preferred_language: None,
+            parser_options: ParserOptions::default(),
+            parser_overrides: Vec::new(),
+            archives: ArchiveOptions::default(),
+            warc: None,
+            incremental: None,
+        }
+    }
+
+    /// Returns whether the crawler should follow the given link
+    pub fn should_follow(&self, link: &Link) -> bool {
+        if self.respect_nofollow && link.nofollow {
+            return false;
         }
+        self.follow_links.contains(&link.kind)
     }
 }
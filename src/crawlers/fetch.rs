@@ -0,0 +1,103 @@
+use fantoccini::Client;
+use http_body_util::BodyExt;
+use http_body_util::combinators::BoxBody;
+use std::error::Error;
+
+pub use http::Method;
+
+/// A raw HTTP response fetched alongside the WebDriver session
+#[derive(Debug, Clone)]
+pub struct FetchedResource {
+    /// URL that was requested
+    pub url: String,
+
+    /// HTTP status code
+    pub status: u16,
+
+    /// Response headers, with lower-cased names
+    pub headers: Vec<(String, String)>,
+
+    /// Response body (empty for HEAD requests)
+    pub body: Vec<u8>,
+}
+
+impl FetchedResource {
+    /// Returns the first value of the given header (case-insensitive)
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Returns the Content-Type header, if present
+    pub fn content_type(&self) -> Option<&str> {
+        self.header("content-type")
+    }
+
+    /// Returns whether the status code is 2xx
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+/// Issues a raw HTTP request through the WebDriver client's HTTP connection
+///
+/// Unlike navigating with the browser this exposes status, headers and the exact
+/// response bytes. Session cookies are not sent.
+pub async fn fetch(
+    client: &Client,
+    method: Method,
+    url: &str,
+) -> Result<FetchedResource, Box<dyn Error + Send + Sync>> {
+    fetch_with_headers(client, method, url, &[]).await
+}
+
+/// Issues a raw HTTP request with extra request headers, such as `If-None-Match`
+///
+/// Headers with invalid names or values are left out.
+pub async fn fetch_with_headers(
+    client: &Client,
+    method: Method,
+    url: &str,
+    headers: &[(String, String)],
+) -> Result<FetchedResource, Box<dyn Error + Send + Sync>> {
+    let mut builder = client.raw_request();
+    builder.method(method).url(url).skip_cookie_navigation();
+    let headers = headers.to_vec();
+    let builder = builder.map_request(move |mut request| {
+        for (name, value) in headers {
+            match (
+                http::HeaderName::from_bytes(name.as_bytes()),
+                http::HeaderValue::from_str(&value),
+            ) {
+                (Ok(name), Ok(value)) => request = request.header(name, value),
+                _ => ::log::debug!("Leaving out invalid header {}: {}", name, value),
+            }
+        }
+        request
+            .body(BoxBody::new(http_body_util::Empty::new()))
+            .expect("request with valid headers")
+    });
+    let response = builder.send().await?;
+
+    let status = response.status().as_u16();
+    let headers = response
+        .headers()
+        .iter()
+        .filter_map(|(name, value)| {
+            value
+                .to_str()
+                .ok()
+                .map(|v| (name.as_str().to_ascii_lowercase(), v.to_string()))
+        })
+        .collect();
+    let body = response.into_body().collect().await?.to_bytes().to_vec();
+
+    Ok(FetchedResource {
+        url: url.to_string(),
+        status,
+        headers,
+        body,
+    })
+}
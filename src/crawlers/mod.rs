@@ -2,6 +2,8 @@
 #![allow(clippy::redundant_pattern_matching)]
 
 pub mod crawler;
+pub mod fetch;
+pub mod warc;
 pub mod web;
 
 pub use crawler::Crawler;
@@ -0,0 +1,270 @@
+use crate::archive::ArchiveOptions;
+use crate::config::WarcReplayConfig;
+use crate::crawlers::web;
+use crate::parsers::{ContentTypeDetector, ParserOptionsSelector, ParserRegistry};
+use crate::results::{CrawlError, PageData};
+use crate::warc::WarcReader;
+use crate::warc::reader::warc_files;
+use regex::Regex;
+use std::collections::HashSet;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use tokio::sync::mpsc;
+
+/// Starts replaying WARC files and returns a receiver that yields PageData for each archived page
+///
+/// # Arguments
+///
+/// * `config` - WARC replay configuration
+pub fn start(config: &WarcReplayConfig) -> Result<mpsc::Receiver<PageData>, Box<dyn Error>> {
+    start_with_parsers(config, ParserRegistry::new())
+}
+
+/// Starts replaying WARC files, parsing matching resources with user-defined parsers
+///
+/// # Arguments
+///
+/// * `config` - WARC replay configuration
+/// * `registry` - Parsers checked before the built-in ones
+pub fn start_with_parsers(
+    config: &WarcReplayConfig,
+    registry: ParserRegistry,
+) -> Result<mpsc::Receiver<PageData>, Box<dyn Error>> {
+    start_with_errors(config, registry, None)
+}
+
+/// Starts replaying WARC files, also reporting the archived resources that fail to parse
+///
+/// # Arguments
+///
+/// * `config` - WARC replay configuration
+/// * `registry` - Parsers checked before the built-in ones
+/// * `error_tx` - Receives an error for each resource that yields no pages because it failed
+pub fn start_with_errors(
+    config: &WarcReplayConfig,
+    registry: ParserRegistry,
+    error_tx: Option<mpsc::UnboundedSender<CrawlError>>,
+) -> Result<mpsc::Receiver<PageData>, Box<dyn Error>> {
+    ::log::info!("Starting WARC replay for: {}", config.path);
+
+    let replay = WarcReplay::new(config, registry)?;
+    let (result_tx, result_rx) = mpsc::channel::<PageData>(10000);
+
+    // Reading and parsing is blocking work; the channel closes when the replay is done
+    tokio::task::spawn_blocking(move || {
+        let pages = replay.replay(
+            |page| result_tx.blocking_send(page).is_ok(),
+            |error| {
+                if let Some(error_tx) = &error_tx {
+                    let _ = error_tx.send(error);
+                }
+            },
+        );
+        ::log::info!("WARC replay complete - {} pages", pages);
+    });
+
+    Ok(result_rx)
+}
+
+/// Parses the responses archived in WARC files as if they were being crawled
+///
+/// Responses are replayed in file and record order. Only successful responses
+/// are parsed, and only the first response for each URL.
+pub struct WarcReplay {
+    files: Vec<PathBuf>,
+    include_patterns: Vec<Regex>,
+    exclude_patterns: Vec<Regex>,
+    detector: ContentTypeDetector,
+    registry: ParserRegistry,
+    parser_options: ParserOptionsSelector,
+    archives: ArchiveOptions,
+}
+
+impl WarcReplay {
+    /// Creates a replay of the WARC files at the configured path
+    pub fn new(
+        config: &WarcReplayConfig,
+        registry: ParserRegistry,
+    ) -> Result<Self, Box<dyn Error>> {
+        let compile = |patterns: &[String]| -> Result<Vec<Regex>, regex::Error> {
+            patterns.iter().map(|p| Regex::new(p)).collect()
+        };
+
+        Ok(Self {
+            files: warc_files(Path::new(&config.path))?,
+            include_patterns: compile(&config.include_patterns)?,
+            exclude_patterns: compile(&config.exclude_patterns)?,
+            detector: ContentTypeDetector::new(&config.parser_mappings)?,
+            registry,
+            parser_options: ParserOptionsSelector::new(
+                config.parser_options.clone(),
+                &config.parser_overrides,
+            )?,
+            archives: config.archives.clone(),
+        })
+    }
+
+    /// Parses each archived page and passes it to `send`, stopping early if that returns false
+    ///
+    /// Returns the number of pages sent. Unreadable files and resources that fail
+    /// to parse are passed to `report`.
+    pub fn replay(
+        &self,
+        mut send: impl FnMut(PageData) -> bool,
+        mut report: impl FnMut(CrawlError),
+    ) -> usize {
+        let mut seen = HashSet::new();
+        let mut sent = 0;
+
+        for path in &self.files {
+            let reader = match WarcReader::open(path) {
+                Ok(reader) => reader,
+                Err(e) => {
+                    ::log::error!("Failed to open WARC file {}: {}", path.display(), e);
+                    report(CrawlError::new(
+                        &path.display().to_string(),
+                        format!("Failed to open WARC file: {}", e),
+                    ));
+                    continue;
+                }
+            };
+            ::log::info!("Replaying WARC file {}", path.display());
+
+            for record in reader {
+                let record = match record {
+                    Ok(record) => record,
+                    Err(e) => {
+                        ::log::error!("Failed to read WARC file {}: {}", path.display(), e);
+                        report(CrawlError::new(
+                            &path.display().to_string(),
+                            format!("Failed to read WARC file: {}", e),
+                        ));
+                        break;
+                    }
+                };
+                let Some(resource) = record.to_resource() else {
+                    continue;
+                };
+                if !resource.is_success() {
+                    ::log::debug!(
+                        "Skipping archived {} response: {}",
+                        resource.status,
+                        resource.url
+                    );
+                    continue;
+                }
+                if !self.accepts(&resource.url) || !seen.insert(resource.url.clone()) {
+                    continue;
+                }
+
+                let worker_start = std::time::Instant::now();
+                let parser_type = self.detector.detect(
+                    &resource.url,
+                    resource.content_type(),
+                    Some(&resource.body),
+                );
+                let Some(pages) = web::parse_downloaded(
+                    &resource.url,
+                    &resource,
+                    parser_type,
+                    &self.detector,
+                    &self.registry,
+                    &self.parser_options,
+                    &self.archives,
+                    0,
+                    worker_start,
+                ) else {
+                    report(CrawlError::new(&resource.url, "Failed to parse"));
+                    continue;
+                };
+
+                for page in pages {
+                    if !send(page) {
+                        return sent;
+                    }
+                    sent += 1;
+                }
+            }
+        }
+
+        sent
+    }
+
+    /// Returns whether a URL passes the include and exclude patterns
+    fn accepts(&self, url: &str) -> bool {
+        (self.include_patterns.is_empty() || self.include_patterns.iter().any(|p| p.is_match(url)))
+            && !self.exclude_patterns.iter().any(|p| p.is_match(url))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crawlers::fetch::FetchedResource;
+    use crate::warc::{WarcOptions, WarcWriter};
+
+    fn resource(url: &str, status: u16, content_type: &str, body: &str) -> FetchedResource {
+        FetchedResource {
+            url: url.to_string(),
+            status,
+            headers: vec![("content-type".to_string(), content_type.to_string())],
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_replay() {
+        let directory =
+            std::env::temp_dir().join(format!("yield-page-replay-{}", uuid::Uuid::new_v4()));
+        let mut writer = WarcWriter::new(WarcOptions {
+            directory: directory.clone(),
+            ..WarcOptions::default()
+        });
+        for resource in [
+            resource(
+                "https://example.org/",
+                200,
+                "text/html",
+                "<html><head><title>Home</title></head><body><a href=\"/about\">About</a></body></html>",
+            ),
+            resource("https://example.org/missing", 404, "text/html", "Not found"),
+            resource(
+                "https://example.org/notes.md",
+                200,
+                "text/markdown",
+                "# Notes\n\nText",
+            ),
+            resource(
+                "https://example.org/",
+                200,
+                "text/html",
+                "<title>Again</title>",
+            ),
+            resource("https://example.org/private/a", 200, "text/plain", "Secret"),
+        ] {
+            writer.write_exchange(&resource).unwrap();
+        }
+        writer.flush().unwrap();
+
+        let mut config = WarcReplayConfig::new(directory.to_str().unwrap());
+        config.exclude_patterns = vec!["/private/".to_string()];
+        let replay = WarcReplay::new(&config, ParserRegistry::new()).unwrap();
+        let mut pages = Vec::new();
+        let sent = replay.replay(
+            |page| {
+                pages.push(page);
+                true
+            },
+            |error| panic!("Unexpected error: {:?}", error),
+        );
+
+        assert_eq!(sent, 2);
+        assert_eq!(pages[0].url, "https://example.org/");
+        assert_eq!(pages[0].title.as_deref(), Some("Home"));
+        assert_eq!(pages[0].link_details[0].url, "https://example.org/about");
+        assert_eq!(pages[1].url, "https://example.org/notes.md");
+        assert_eq!(pages[1].title.as_deref(), Some("Notes"));
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
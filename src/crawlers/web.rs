@@ -1,7 +1,14 @@
+use crate::archive::{self, ArchiveFormat, ArchiveOptions};
 use crate::config::WebCrawlerConfig;
+use crate::crawlers::fetch::{self, FetchedResource, Method};
 use crate::filter::{UrlFilter, UrlFilterConfig};
-use crate::parsers::{self, ParserType};
-use crate::results::PageData;
+use crate::incremental::{CrawlState, UrlState};
+use crate::parsers::{
+    self, ContentParser, ContentTypeDetector, EncodingSource, EpubMode, ParserOptions,
+    ParserOptionsSelector, ParserRegistry, ParserType, epub, language,
+};
+use crate::results::{CrawlError, PageData};
+use crate::warc::WarcWriter;
 use fantoccini::{Client, ClientBuilder};
 use std::collections::HashSet;
 use std::sync::Arc;
@@ -15,6 +22,34 @@ use url::Url;
 ///
 /// * `config` - Web crawler configuration
 pub async fn start(config: &WebCrawlerConfig) -> mpsc::Receiver<PageData> {
+    start_with_parsers(config, ParserRegistry::new()).await
+}
+
+/// Starts an async web crawl that parses matching resources with user-defined parsers
+///
+/// # Arguments
+///
+/// * `config` - Web crawler configuration
+/// * `registry` - Parsers checked before the built-in ones
+pub async fn start_with_parsers(
+    config: &WebCrawlerConfig,
+    registry: ParserRegistry,
+) -> mpsc::Receiver<PageData> {
+    start_with_errors(config, registry, None).await
+}
+
+/// Starts an async web crawl that also reports the URLs it fails to scrape
+///
+/// # Arguments
+///
+/// * `config` - Web crawler configuration
+/// * `registry` - Parsers checked before the built-in ones
+/// * `error_tx` - Receives an error for each URL that yields no pages because it failed
+pub async fn start_with_errors(
+    config: &WebCrawlerConfig,
+    registry: ParserRegistry,
+    error_tx: Option<mpsc::UnboundedSender<CrawlError>>,
+) -> mpsc::Receiver<PageData> {
     ::log::info!("Starting web crawler for: {}", config.start_url);
 
     let root_url = Url::parse(&config.start_url).expect("Invalid start URL");
@@ -22,6 +57,38 @@ pub async fn start(config: &WebCrawlerConfig) -> mpsc::Receiver<PageData> {
     // Create URL filter configuration based on the start URL and config options
     let url_filter = create_url_filter(&root_url, config);
 
+    // Create the content type detector used to pick a parser for each resource
+    let detector = Arc::new(
+        ContentTypeDetector::new(&config.parser_mappings).expect("Invalid parser mapping pattern"),
+    );
+
+    // Create the selector that picks parser options for each URL
+    let parser_options = Arc::new(
+        ParserOptionsSelector::new(config.parser_options.clone(), &config.parser_overrides)
+            .expect("Invalid parser override pattern"),
+    );
+    let registry = Arc::new(registry);
+
+    // Archive raw responses if configured; files are created with the first record
+    let warc = config
+        .warc
+        .clone()
+        .map(|options| Arc::new(Mutex::new(WarcWriter::new(options))));
+
+    // Load what previous crawls saw, so pages that haven't changed aren't scraped again
+    let state = config.incremental.as_ref().map(|options| {
+        let state = CrawlState::load(&options.state_path).unwrap_or_else(|e| {
+            ::log::warn!(
+                "Failed to read crawl state {}, crawling every page: {}",
+                options.state_path.display(),
+                e
+            );
+            CrawlState::default()
+        });
+        ::log::info!("Loaded crawl state for {} URLs", state.len());
+        Arc::new(Mutex::new(state))
+    });
+
     // Create channels for communication
     let (crawl_tx, crawl_rx) = mpsc::channel::<String>(10000);
     let (result_tx, result_rx) = mpsc::channel::<PageData>(10000);
@@ -35,18 +102,27 @@ pub async fn start(config: &WebCrawlerConfig) -> mpsc::Receiver<PageData> {
     // Queue the initial URL
     crawl_tx.send(config.start_url.clone()).await.unwrap();
 
+    // Share the configuration between workers
+    let config = Arc::new(config.clone());
+
     // Start worker threads
     spawn_workers(
         config.max_concurrency,
         root_url,
         url_filter,
+        detector,
+        registry,
+        parser_options,
+        warc,
+        state,
         crawl_tx.clone(),
         crawl_rx,
         result_tx,
+        error_tx,
         visited,
         web_semaphore,
         active_workers,
-        &config.webdriver_url,
+        config,
     );
 
     // Drop the original sender to signal when all workers are done
@@ -88,6 +164,8 @@ fn create_url_filter(root_url: &Url, config: &WebCrawlerConfig) -> Arc<UrlFilter
         },
         include_patterns: config.include_patterns.clone(),
         exclude_patterns,
+        include_languages: config.include_languages.clone(),
+        exclude_languages: config.exclude_languages.clone(),
     };
 
     Arc::new(UrlFilter::new(filter_config).expect("Invalid regex pattern"))
@@ -98,13 +176,19 @@ fn spawn_workers(
     max_concurrency: usize,
     root_url: Url,
     url_filter: Arc<UrlFilter>,
+    detector: Arc<ContentTypeDetector>,
+    registry: Arc<ParserRegistry>,
+    parser_options: Arc<ParserOptionsSelector>,
+    warc: Option<Arc<Mutex<WarcWriter>>>,
+    state: Option<Arc<Mutex<CrawlState>>>,
     crawl_tx: mpsc::Sender<String>,
     crawl_rx: Arc<Mutex<mpsc::Receiver<String>>>,
     result_tx: mpsc::Sender<PageData>,
+    error_tx: Option<mpsc::UnboundedSender<CrawlError>>,
     visited: Arc<Mutex<HashSet<String>>>,
     web_semaphore: Arc<Semaphore>,
     active_workers: Arc<Mutex<usize>>,
-    webdriver_url: &str,
+    config: Arc<WebCrawlerConfig>,
 ) {
     // Reduce number of initial workers - we'll use lazy initialization
     // so extra workers don't unnecessarily connect to WebDriver
@@ -120,12 +204,18 @@ fn spawn_workers(
     for i in 0..num_workers {
         spawn_worker(
             i,
-            webdriver_url.to_string(),
+            Arc::clone(&config),
             root_url.clone(),
             Arc::clone(&url_filter),
+            Arc::clone(&detector),
+            Arc::clone(&registry),
+            Arc::clone(&parser_options),
+            warc.clone(),
+            state.clone(),
             crawl_tx.clone(),
             Arc::clone(&crawl_rx),
             result_tx.clone(),
+            error_tx.clone(),
             Arc::clone(&visited),
             Arc::clone(&web_semaphore),
             Arc::clone(&active_workers),
@@ -172,6 +262,22 @@ fn spawn_workers(
 
             if completed_workers == num_workers {
                 ::log::info!("All {} worker threads have completed", num_workers);
+                // Save the state before the crawl is seen to end
+                if let (Some(state), Some(options)) = (&state, &config.incremental) {
+                    let state = state.lock().await;
+                    match state.save(&options.state_path) {
+                        Ok(()) => ::log::info!(
+                            "Saved crawl state for {} URLs, {} unchanged this crawl",
+                            state.len(),
+                            state.unchanged()
+                        ),
+                        Err(e) => ::log::error!(
+                            "Failed to save crawl state {}: {}",
+                            options.state_path.display(),
+                            e
+                        ),
+                    }
+                }
                 // Once all workers are done, drop the result sender to close the channel
                 drop(result_tx);
                 break;
@@ -186,12 +292,18 @@ fn spawn_workers(
 /// the queue is empty or an error occurs.
 fn spawn_worker(
     worker_id: usize,
-    webdriver_url: String,
+    config: Arc<WebCrawlerConfig>,
     root_url: Url,
     url_filter: Arc<UrlFilter>,
+    detector: Arc<ContentTypeDetector>,
+    registry: Arc<ParserRegistry>,
+    parser_options: Arc<ParserOptionsSelector>,
+    warc: Option<Arc<Mutex<WarcWriter>>>,
+    state: Option<Arc<Mutex<CrawlState>>>,
     crawl_tx: mpsc::Sender<String>,
     crawl_rx: Arc<Mutex<mpsc::Receiver<String>>>,
     result_tx: mpsc::Sender<PageData>,
+    error_tx: Option<mpsc::UnboundedSender<CrawlError>>,
     visited: Arc<Mutex<HashSet<String>>>,
     web_semaphore: Arc<Semaphore>,
     active_workers: Arc<Mutex<usize>>,
@@ -208,12 +320,18 @@ fn spawn_worker(
         if let Err(_) = worker_processing_loop(
             worker_id,
             None, // No client yet - will connect lazily when needed
-            &webdriver_url,
+            &config,
             &root_url,
             &url_filter,
+            &detector,
+            &registry,
+            &parser_options,
+            warc.as_deref(),
+            state.as_deref(),
             &crawl_tx,
             &crawl_rx,
             &result_tx,
+            error_tx.as_ref(),
             &visited,
             &web_semaphore,
             Some(&initial_page_processed),
@@ -318,12 +436,18 @@ async fn connect_to_webdriver(worker_id: usize, webdriver_url: &str) -> Option<C
 async fn worker_processing_loop(
     worker_id: usize,
     client_opt: Option<Client>,
-    webdriver_url: &str,
+    config: &WebCrawlerConfig,
     root_url: &Url,
     url_filter: &Arc<UrlFilter>,
+    detector: &Arc<ContentTypeDetector>,
+    registry: &Arc<ParserRegistry>,
+    parser_options: &Arc<ParserOptionsSelector>,
+    warc: Option<&Mutex<WarcWriter>>,
+    state: Option<&Mutex<CrawlState>>,
     crawl_tx: &mpsc::Sender<String>,
     crawl_rx: &Arc<Mutex<mpsc::Receiver<String>>>,
     result_tx: &mpsc::Sender<PageData>,
+    error_tx: Option<&mpsc::UnboundedSender<CrawlError>>,
     visited: &Arc<Mutex<HashSet<String>>>,
     web_semaphore: &Arc<Semaphore>,
     initial_page_processed: Option<&Arc<Mutex<bool>>>,
@@ -332,6 +456,7 @@ async fn worker_processing_loop(
 
     // We'll connect to the WebDriver only if/when we actually have a URL to process
     let mut client_opt = client_opt;
+    let webdriver_url = config.webdriver_url.as_str();
 
     while let Some(url) = get_next_url(worker_id, crawl_rx).await {
         // Skip already visited URLs
@@ -350,6 +475,7 @@ async fn worker_processing_loop(
                 Some(client) => client_opt = Some(client),
                 None => {
                     // Failed to connect - release the permit and try another URL
+                    report_error(error_tx, &url, "Failed to connect to WebDriver");
                     continue;
                 }
             }
@@ -358,14 +484,103 @@ async fn worker_processing_loop(
         // We now have a client - unwrap safely
         let client = client_opt.as_mut().unwrap();
 
+        // Pages seen by a previous crawl are only scraped again if they changed;
+        // unchanged ones stand in for themselves so their links are still followed
+        let method = revalidation_method(&url, detector);
+        let mut response = None;
+        if let Some(state) = state {
+            let previous = state.lock().await.get(&url).cloned();
+            response = revalidate(client, method.clone(), &url, previous.as_ref()).await;
+            if let (Some(previous), Some(revalidation)) = (&previous, &response)
+                && previous.is_unchanged(revalidation)
+            {
+                ::log::info!("Unchanged since the last crawl: {}", url);
+                let current = previous.revalidated(revalidation);
+                let page = current.to_page(&url);
+                state.lock().await.insert_unchanged(url.clone(), current);
+                if !process_discovered_page(
+                    worker_id,
+                    &url,
+                    page,
+                    config,
+                    root_url,
+                    url_filter,
+                    result_tx,
+                    crawl_tx,
+                    visited,
+                    initial_page_processed,
+                )
+                .await
+                {
+                    if let Some(client) = client_opt
+                        && let Err(e) = client.close().await
+                    {
+                        ::log::warn!("Worker {} failed to close client: {}", worker_id, e);
+                    }
+                    return Err(());
+                }
+                continue;
+            }
+        }
+        let response = response.filter(FetchedResource::is_success);
+        // A downloaded body is parsed rather than downloaded again
+        let downloaded = response.as_ref().filter(|_| method == Method::GET);
+
         // Process the URL
-        let scrape_result = process_url(worker_id, client, &url, webdriver_url).await;
+        let scrape_result = process_url(
+            worker_id,
+            client,
+            &url,
+            webdriver_url,
+            detector,
+            registry,
+            parser_options,
+            &config.archives,
+            warc,
+            downloaded,
+        )
+        .await;
+
+        let Some(pages) = scrape_result else {
+            ::log::error!("Worker {} failed to scrape: {}", worker_id, url);
+            report_error(error_tx, &url, "Failed to scrape");
+            continue;
+        };
+
+        // Files inside an archive can't be fetched on their own, so never queue their URLs;
+        // links to members that weren't parsed are dropped when they're discovered
+        if pages.iter().any(|page| page.url != url) {
+            let mut seen = visited.lock().await;
+            seen.extend(pages.iter().map(|page| page.url.clone()));
+        }
+
+        // Archives and split e-books yield pages of their own, so they're always scraped again
+        if let (Some(state), Some(response), [page]) = (state, &response, pages.as_slice())
+            && page.url == url
+        {
+            let links = followed_links(page, config)
+                .into_iter()
+                .map(str::to_string)
+                .collect();
+            state
+                .lock()
+                .await
+                .insert(url.clone(), UrlState::new(response, page, links));
+        }
+
+        if pages.is_empty()
+            && let Some(flag) = initial_page_processed
+        {
+            *flag.lock().await = true;
+        }
 
-        if let Some(page) = scrape_result {
+        for page in pages {
+            let page_url = page.url.clone();
             if !process_discovered_page(
                 worker_id,
-                &url,
+                &page_url,
                 page,
+                config,
                 root_url,
                 url_filter,
                 result_tx,
@@ -376,23 +591,21 @@ async fn worker_processing_loop(
             .await
             {
                 // Clean up client before returning error
-                if let Some(client) = client_opt {
-                    if let Err(e) = client.close().await {
-                        ::log::warn!("Worker {} failed to close client: {}", worker_id, e);
-                    }
+                if let Some(client) = client_opt
+                    && let Err(e) = client.close().await
+                {
+                    ::log::warn!("Worker {} failed to close client: {}", worker_id, e);
                 }
                 return Err(());
             }
-        } else {
-            ::log::error!("Worker {} failed to scrape: {}", worker_id, url);
         }
     }
 
     // Close the client if we had one
-    if let Some(client) = client_opt {
-        if let Err(e) = client.close().await {
-            ::log::warn!("Worker {} failed to close client: {}", worker_id, e);
-        }
+    if let Some(client) = client_opt
+        && let Err(e) = client.close().await
+    {
+        ::log::warn!("Worker {} failed to close client: {}", worker_id, e);
     }
 
     ::log::debug!(
@@ -402,6 +615,14 @@ async fn worker_processing_loop(
     Ok(())
 }
 
+/// Reports a URL that failed, if anyone is listening
+fn report_error(error_tx: Option<&mpsc::UnboundedSender<CrawlError>>, url: &str, message: &str) {
+    if let Some(error_tx) = error_tx {
+        // The receiver may have been dropped; the error is already logged
+        let _ = error_tx.send(CrawlError::new(url, message));
+    }
+}
+
 /// Gets the next URL to process from the queue
 async fn get_next_url(
     worker_id: usize,
@@ -473,7 +694,13 @@ async fn process_url(
     client: &mut Client,
     url: &str,
     webdriver_url: &str,
-) -> Option<PageData> {
+    detector: &ContentTypeDetector,
+    registry: &ParserRegistry,
+    parser_options: &ParserOptionsSelector,
+    archive_options: &ArchiveOptions,
+    warc: Option<&Mutex<WarcWriter>>,
+    response: Option<&FetchedResource>,
+) -> Option<Vec<PageData>> {
     let mut reconnect_attempted = false;
     let mut scrape_result = None;
 
@@ -486,7 +713,18 @@ async fn process_url(
             }
         }
 
-        scrape_result = scrape(client, url, worker_id).await;
+        scrape_result = scrape(
+            client,
+            url,
+            worker_id,
+            detector,
+            registry,
+            parser_options,
+            archive_options,
+            warc,
+            response,
+        )
+        .await;
 
         // If scrape succeeded or it's not a session error, break the retry loop
         if scrape_result.is_some() || !reconnect_attempted {
@@ -529,6 +767,7 @@ async fn process_discovered_page(
     worker_id: usize,
     url: &str,
     page: PageData,
+    config: &WebCrawlerConfig,
     root_url: &Url,
     url_filter: &Arc<UrlFilter>,
     result_tx: &mpsc::Sender<PageData>,
@@ -536,10 +775,41 @@ async fn process_discovered_page(
     visited: &Arc<Mutex<HashSet<String>>>,
     initial_page_processed: Option<&Arc<Mutex<bool>>>,
 ) -> bool {
+    // A page listing a translation in the preferred language is a duplicate of that translation
+    let alternates = language::hreflang_alternates(&page.link_details);
+    let preferred_alternate = config.preferred_language.as_deref().and_then(|preferred| {
+        alternates
+            .iter()
+            .find(|(tag, _)| language::matches(tag, preferred))
+            .map(|(_, alternate)| *alternate)
+    });
+    let is_translation = preferred_alternate.is_some_and(|alternate| {
+        Url::parse(alternate)
+            .ok()
+            .map(|u| url_filter.normalize_url(&u))
+            != Url::parse(url).ok().map(|u| url_filter.normalize_url(&u))
+    });
+    let keep = !is_translation && url_filter.accepts_language(page.language.as_deref());
+
+    // Unchanged pages are only sent if asked for
+    let emit = !page.metadata.unchanged
+        || config
+            .incremental
+            .as_ref()
+            .is_some_and(|options| options.emit_unchanged);
+
     // Send the page data to the result channel
-    if let Err(e) = result_tx.send(page.clone()).await {
-        ::log::error!("Worker {} failed to send result: {}", worker_id, e);
-        return false;
+    if keep && emit {
+        if let Err(e) = result_tx.send(page.clone()).await {
+            ::log::error!("Worker {} failed to send result: {}", worker_id, e);
+            return false;
+        }
+    } else if !keep {
+        ::log::info!(
+            "Dropping {} page: {}",
+            page.language.as_deref().unwrap_or("unknown language"),
+            url
+        );
     }
 
     // If this is the initial page, mark it as processed
@@ -549,9 +819,41 @@ async fn process_discovered_page(
         ::log::debug!("Marked initial page as processed");
     }
 
-    // Process discovered links
-    for link in page.links.iter() {
+    // Other translations won't be kept, so don't fetch them at all
+    if preferred_alternate.is_some() {
+        let mut seen = visited.lock().await;
+        for (_, alternate) in &alternates {
+            if Some(*alternate) != preferred_alternate
+                && let Ok(alternate) = Url::parse(alternate)
+            {
+                seen.insert(url_filter.normalize_url(&alternate).to_string());
+            }
+        }
+    }
+
+    // Process discovered links, keeping untyped ones from parsers without typed links.
+    // Dropped pages only lead to their translations in a wanted language.
+    let links: Vec<&str> = if !keep {
+        match preferred_alternate {
+            Some(alternate) => vec![alternate],
+            None => alternates
+                .iter()
+                .filter(|(tag, _)| url_filter.accepts_language(Some(tag)))
+                .map(|(_, alternate)| *alternate)
+                .collect(),
+        }
+    } else {
+        followed_links(&page, config)
+    };
+
+    for link in links {
         if let Ok(resolved) = Url::parse(url).and_then(|base| base.join(link)) {
+            // Files inside an archive can't be fetched on their own, whether or not they were parsed
+            if resolved.as_str().contains(archive::MEMBER_SEPARATOR) {
+                ::log::debug!("Not queuing archive member: {}", resolved);
+                continue;
+            }
+
             // Use the URL filter to determine if we should crawl this link
             if !url_filter.should_crawl(&resolved, Some(root_url)) {
                 ::log::debug!("URL filter rejected: {}", resolved);
@@ -583,6 +885,15 @@ async fn process_discovered_page(
     true
 }
 
+/// Returns the links of a page the crawler follows, keeping untyped ones from parsers
+/// without typed links
+fn followed_links<'a>(page: &'a PageData, config: &WebCrawlerConfig) -> Vec<&'a str> {
+    page.outgoing_links()
+        .filter(|(_, link)| link.is_none_or(|link| config.should_follow(link)))
+        .map(|(url, _)| url)
+        .collect()
+}
+
 /// Decrements the active worker counter
 async fn decrement_active_worker(worker_id: usize, active_workers: &Arc<Mutex<usize>>) {
     let mut active = active_workers.lock().await;
@@ -595,22 +906,77 @@ async fn decrement_active_worker(worker_id: usize, active_workers: &Arc<Mutex<us
 }
 
 /// Scrapes a URL and returns the page data
-async fn scrape(client: &Client, url: &str, worker_id: usize) -> Option<PageData> {
+///
+/// Archives yield a page for each file inside them, other resources a single page.
+/// With a WARC writer, the raw response and the extracted text are archived too.
+async fn scrape(
+    client: &Client,
+    url: &str,
+    worker_id: usize,
+    detector: &ContentTypeDetector,
+    registry: &ParserRegistry,
+    parser_options: &ParserOptionsSelector,
+    archive_options: &ArchiveOptions,
+    warc: Option<&Mutex<WarcWriter>>,
+    response: Option<&FetchedResource>,
+) -> Option<Vec<PageData>> {
     // Add a worker-specific timeout to prevent individual scraping operations from hanging indefinitely
     let worker_start = std::time::Instant::now();
     ::log::debug!("SCRAPE: {}", url);
 
-    // Determine the appropriate parser type based on the URL
-    let parser_type = ParserType::from_url(url);
-    let should_parse_links = parser_type.should_extract_links();
-
     // Add timeout for the entire scrape operation
     let scrape_result = timeout(tokio::time::Duration::from_secs(45), async {
-        if !should_parse_links {
-            scrape_text_file(client, url, worker_id, worker_start).await
-        } else {
-            scrape_html_page(client, url, worker_id, worker_start).await
+        // Determine the appropriate parser type from the response headers and content
+        let detected = match (response, warc) {
+            // An incremental crawl already downloaded it
+            (Some(response), _) => {
+                detect_fetched_type(client, url, response.clone(), detector).await
+            }
+            (None, Some(_)) => download_for_archive(client, url, detector).await,
+            (None, None) => detect_parser_type(client, url, detector).await,
+        };
+        let (parser_type, content_type, fetched) = match detected {
+            Ok(detected) => detected,
+            Err(e) => {
+                return handle_navigation_error(e, "accessing", worker_id, url)
+                    .map(|page| vec![page]);
+            }
+        };
+
+        let response_id = match (warc, &fetched) {
+            (Some(warc), Some(fetched)) => archive_response(url, fetched, warc).await,
+            _ => None,
+        };
+        // Failed responses are only archived; the browser shows what it gets instead
+        let resource = fetched.filter(FetchedResource::is_success);
+
+        let pages = scrape_resource(
+            client,
+            url,
+            parser_type,
+            content_type,
+            resource,
+            detector,
+            registry,
+            parser_options,
+            archive_options,
+            worker_id,
+            worker_start,
+        )
+        .await;
+
+        if let (Some(warc), Some(response_id), Some(pages)) = (warc, &response_id, &pages) {
+            let mut warc = warc.lock().await;
+            for page in pages {
+                if let Err(e) = warc.write_page(page, response_id) {
+                    ::log::warn!("Failed to write WARC records for {}: {}", page.url, e);
+                }
+            }
+            if let Err(e) = warc.flush() {
+                ::log::warn!("Failed to flush WARC file: {}", e);
+            }
         }
+        pages
     })
     .await;
 
@@ -623,10 +989,456 @@ async fn scrape(client: &Client, url: &str, worker_id: usize) -> Option<PageData
     }
 }
 
-/// Scrapes a text-based file (non-HTML)
+/// Parses a resource whose type has been detected, downloading or rendering it as needed
+async fn scrape_resource(
+    client: &Client,
+    url: &str,
+    parser_type: ParserType,
+    content_type: Option<String>,
+    resource: Option<FetchedResource>,
+    detector: &ContentTypeDetector,
+    registry: &ParserRegistry,
+    parser_options: &ParserOptionsSelector,
+    archive_options: &ArchiveOptions,
+    worker_id: usize,
+    worker_start: std::time::Instant,
+) -> Option<Vec<PageData>> {
+    // Downloaded resources are parsed directly; only HTML needs the browser
+    if parser_type != ParserType::Html
+        && let Some(resource) = &resource
+    {
+        return parse_downloaded(
+            url,
+            resource,
+            parser_type,
+            detector,
+            registry,
+            parser_options,
+            archive_options,
+            worker_id,
+            worker_start,
+        );
+    }
+
+    let options = parser_options.for_url(url);
+
+    // Registered parsers take precedence over the built-in ones
+    if let Some(parser) = registry.find(url, content_type.as_deref(), parser_type) {
+        return scrape_with_parser(
+            client,
+            url,
+            parser.as_ref(),
+            resource,
+            options,
+            worker_id,
+            worker_start,
+        )
+        .await
+        .map(|page| vec![page]);
+    }
+
+    let page = if parser_type == ParserType::Html {
+        scrape_html_page(client, url, content_type, options, worker_id, worker_start).await
+    } else {
+        scrape_text_file(client, url, parser_type, options, worker_id, worker_start).await
+    };
+    page.map(|page| vec![page])
+}
+
+/// Parses a downloaded resource into pages without the browser
+///
+/// Registered parsers take precedence over the built-in ones. E-books may be split
+/// into chapters and archives are expanded into their files.
+pub(crate) fn parse_downloaded(
+    url: &str,
+    resource: &FetchedResource,
+    parser_type: ParserType,
+    detector: &ContentTypeDetector,
+    registry: &ParserRegistry,
+    parser_options: &ParserOptionsSelector,
+    archive_options: &ArchiveOptions,
+    worker_id: usize,
+    worker_start: std::time::Instant,
+) -> Option<Vec<PageData>> {
+    let options = parser_options.for_url(url);
+
+    if let Some(parser) = registry.find(url, resource.content_type(), parser_type) {
+        let mut parser_result =
+            parser.parse_bytes(&resource.body, resource.content_type(), Some(url), options);
+        parser_result.resolve_links(url);
+        ::log::debug!(
+            "Worker {} processed {} with a registered parser in {:.2} seconds",
+            worker_id,
+            url,
+            worker_start.elapsed().as_secs_f64()
+        );
+        let mut page = PageData::from_parse_result(url, parser_result);
+        page.source = options.keep_source.then(|| resource.body.clone());
+        return Some(vec![page]);
+    }
+
+    // E-books can be split into a page per chapter
+    if parser_type == ParserType::Epub && options.epub_mode == EpubMode::Chapters {
+        return match epub::parse_book(&resource.body, Some(url), options) {
+            Ok(book) => Some(book.into_pages(url)),
+            Err(e) => {
+                ::log::warn!("Failed to parse EPUB {}: {}", url, e);
+                None
+            }
+        };
+    }
+
+    // Archives are expanded into their files rather than parsed themselves
+    if let Some(format) = ArchiveFormat::detect(url, &resource.body) {
+        return expand_fetched_archive(
+            url,
+            resource,
+            format,
+            archive_options,
+            detector,
+            registry,
+            parser_options,
+            worker_id,
+            worker_start,
+        );
+    }
+
+    Some(vec![parse_fetched_resource(
+        url,
+        resource,
+        parser_type,
+        options,
+        worker_id,
+        worker_start,
+    )])
+}
+
+/// Writes the raw response for a URL to the WARC files, returning the record ID
+///
+/// Failed responses are archived too.
+async fn archive_response(
+    url: &str,
+    resource: &FetchedResource,
+    warc: &Mutex<WarcWriter>,
+) -> Option<String> {
+    let mut warc = warc.lock().await;
+    match warc.write_exchange(resource) {
+        Ok(response_id) => Some(response_id),
+        Err(e) => {
+            ::log::warn!("Failed to write WARC records for {}: {}", url, e);
+            None
+        }
+    }
+}
+
+/// Determines the parser type for a URL, loading it in the browser if it's HTML
+///
+/// URLs whose extension or a URL mapping names a type other than HTML are downloaded,
+/// so the headers and content can confirm it. Other URLs are loaded by the browser,
+/// which tells what it got, and only downloaded when that isn't HTML. Downloaded
+/// resources are returned so they can be parsed without fetching them again, along
+/// with the Content-Type of the response or of the loaded document.
+async fn detect_parser_type(
+    client: &Client,
+    url: &str,
+    detector: &ContentTypeDetector,
+) -> Result<(ParserType, Option<String>, Option<FetchedResource>), fantoccini::error::CmdError> {
+    if let Some(parser_type) = detector
+        .detect_from_url(url)
+        .filter(|parser_type| *parser_type != ParserType::Html)
+    {
+        match download(client, url, detector).await {
+            // Sniffed as HTML after all, so it's left to the browser
+            Some((ParserType::Html, _)) => {}
+            Some((parser_type, resource)) => {
+                let content_type = resource.content_type().map(|s| s.to_string());
+                return Ok((parser_type, content_type, Some(resource)));
+            }
+            None => return Ok((parser_type, None, None)),
+        }
+    }
+
+    client.goto(url).await?;
+
+    // Keep what the browser shows if the download fails
+    let content_type = document_content_type(client).await;
+    if let Some(content_type) = &content_type
+        && detector.detect_explicit(url, Some(content_type), None) != Some(ParserType::Html)
+        && let Some((parser_type, resource)) = download(client, url, detector).await
+        && parser_type != ParserType::Html
+    {
+        let content_type = resource.content_type().map(|s| s.to_string());
+        return Ok((parser_type, content_type, Some(resource)));
+    }
+
+    Ok((ParserType::Html, content_type, None))
+}
+
+/// Downloads a resource, determining its parser type from the headers and content
+///
+/// Returns `None` if the request failed.
+async fn download(
+    client: &Client,
+    url: &str,
+    detector: &ContentTypeDetector,
+) -> Option<(ParserType, FetchedResource)> {
+    match fetch::fetch(client, Method::GET, url).await {
+        Ok(resource) if resource.is_success() => {
+            let parser_type = detector.detect(url, resource.content_type(), Some(&resource.body));
+            Some((parser_type, resource))
+        }
+        Ok(resource) => {
+            ::log::debug!("GET {} returned status {}", url, resource.status);
+            None
+        }
+        Err(e) => {
+            ::log::debug!("GET {} failed: {}", url, e);
+            None
+        }
+    }
+}
+
+/// Downloads a URL whatever its type, so the raw response can be archived
+///
+/// The response, failed or not, is returned with the parser type and Content-Type it
+/// implies, and HTML is loaded in the browser too. If the request fails altogether,
+/// the type is determined as usual.
+async fn download_for_archive(
+    client: &Client,
+    url: &str,
+    detector: &ContentTypeDetector,
+) -> Result<(ParserType, Option<String>, Option<FetchedResource>), fantoccini::error::CmdError> {
+    match fetch::fetch(client, Method::GET, url).await {
+        Ok(resource) => detect_fetched_type(client, url, resource, detector).await,
+        Err(e) => {
+            ::log::debug!("GET {} failed: {}", url, e);
+            detect_parser_type(client, url, detector).await
+        }
+    }
+}
+
+/// Determines the parser type of a downloaded resource, loading it in the browser if it's HTML
+///
+/// The response, failed or not, is returned with the parser type and Content-Type it implies.
+async fn detect_fetched_type(
+    client: &Client,
+    url: &str,
+    resource: FetchedResource,
+    detector: &ContentTypeDetector,
+) -> Result<(ParserType, Option<String>, Option<FetchedResource>), fantoccini::error::CmdError> {
+    // A failed response is an error page, so it says nothing about the resource but its URL
+    let (parser_type, content_type) = if resource.is_success() {
+        let content_type = resource.content_type().map(|s| s.to_string());
+        let parser_type = detector.detect(url, content_type.as_deref(), Some(&resource.body));
+        (parser_type, content_type)
+    } else {
+        ::log::debug!("GET {} returned status {}", url, resource.status);
+        (detector.detect(url, None, None), None)
+    };
+
+    if parser_type == ParserType::Html {
+        client.goto(url).await?;
+    }
+    Ok((parser_type, content_type, Some(resource)))
+}
+
+/// Returns the Content-Type of the document the browser has loaded
+async fn document_content_type(client: &Client) -> Option<String> {
+    match client.execute("return document.contentType;", vec![]).await {
+        Ok(value) => value.as_str().map(str::to_string),
+        Err(e) => {
+            ::log::debug!("Failed to read the document content type: {}", e);
+            None
+        }
+    }
+}
+
+/// Returns how an incremental crawl asks whether a URL changed
+///
+/// URLs that could be HTML only get a `HEAD`, since the browser loads them anyway.
+/// Others get a `GET`, whose body is parsed rather than downloaded again.
+fn revalidation_method(url: &str, detector: &ContentTypeDetector) -> Method {
+    match detector.detect_from_url(url) {
+        Some(parser_type) if parser_type != ParserType::Html => Method::GET,
+        _ => Method::HEAD,
+    }
+}
+
+/// Requests a URL conditionally on it having changed since the previous crawl
+///
+/// Returns `None` if the request failed, in which case the URL is scraped as usual.
+async fn revalidate(
+    client: &Client,
+    method: Method,
+    url: &str,
+    previous: Option<&UrlState>,
+) -> Option<FetchedResource> {
+    let headers = previous
+        .map(UrlState::conditional_headers)
+        .unwrap_or_default();
+    match fetch::fetch_with_headers(client, method.clone(), url, &headers).await {
+        Ok(response) => Some(response),
+        Err(e) => {
+            ::log::debug!("Conditional {} {} failed: {}", method, url, e);
+            None
+        }
+    }
+}
+
+/// Parses a non-HTML resource that was downloaded directly
+fn parse_fetched_resource(
+    url: &str,
+    resource: &FetchedResource,
+    parser_type: ParserType,
+    parser_options: &ParserOptions,
+    worker_id: usize,
+    worker_start: std::time::Instant,
+) -> PageData {
+    // Decode the raw bytes using the declared or detected character encoding
+    let mut parser_result = parsers::Parser::parse_bytes_with_options(
+        &resource.body,
+        parser_type,
+        resource.content_type(),
+        parser_options,
+    );
+    parser_result.resolve_links(url);
+
+    // Log processing time for debugging
+    let elapsed = worker_start.elapsed().as_secs_f64();
+    ::log::debug!(
+        "Worker {} processed {:?} resource {} in {:.2} seconds",
+        worker_id,
+        parser_type,
+        url,
+        elapsed
+    );
+
+    let mut page = PageData::from_parse_result(url, parser_result);
+    page.source = parser_options.keep_source.then(|| resource.body.clone());
+    page
+}
+
+/// Parses the files inside a downloaded archive, one page per file
+fn expand_fetched_archive(
+    url: &str,
+    resource: &FetchedResource,
+    format: ArchiveFormat,
+    archive_options: &ArchiveOptions,
+    detector: &ContentTypeDetector,
+    registry: &ParserRegistry,
+    parser_options: &ParserOptionsSelector,
+    worker_id: usize,
+    worker_start: std::time::Instant,
+) -> Option<Vec<PageData>> {
+    if !archive_options.enabled {
+        ::log::info!("Skipping archive (expansion disabled): {}", url);
+        return Some(Vec::new());
+    }
+
+    match archive::expand_to_pages(
+        url,
+        &resource.body,
+        format,
+        archive_options,
+        detector,
+        registry,
+        parser_options,
+    ) {
+        Ok(pages) => {
+            let elapsed = worker_start.elapsed().as_secs_f64();
+            ::log::debug!(
+                "Worker {} expanded {:?} archive {} into {} pages in {:.2} seconds",
+                worker_id,
+                format,
+                url,
+                pages.len(),
+                elapsed
+            );
+            Some(pages)
+        }
+        Err(e) => {
+            ::log::warn!("Failed to expand archive {}: {}", url, e);
+            None
+        }
+    }
+}
+
+/// Parses a resource with a registered parser
+///
+/// The raw bytes are downloaded if they weren't already; if that fails, the
+/// source the browser shows is parsed instead.
+async fn scrape_with_parser(
+    client: &Client,
+    url: &str,
+    parser: &dyn ContentParser,
+    resource: Option<FetchedResource>,
+    parser_options: &ParserOptions,
+    worker_id: usize,
+    worker_start: std::time::Instant,
+) -> Option<PageData> {
+    let resource = match resource {
+        Some(resource) => Some(resource),
+        None => match fetch::fetch(client, Method::GET, url).await {
+            Ok(resource) if resource.is_success() => Some(resource),
+            Ok(resource) => {
+                ::log::debug!("GET {} returned status {}", url, resource.status);
+                None
+            }
+            Err(e) => {
+                ::log::debug!("GET {} failed: {}", url, e);
+                None
+            }
+        },
+    };
+
+    let (mut parser_result, source) = match resource {
+        Some(resource) => (
+            parser.parse_bytes(
+                &resource.body,
+                resource.content_type(),
+                Some(url),
+                parser_options,
+            ),
+            resource.body,
+        ),
+        None => {
+            if let Err(e) = client.goto(url).await {
+                return handle_navigation_error(e, "accessing", worker_id, url);
+            }
+            let source = match client.source().await {
+                Ok(source) => source,
+                Err(e) => return handle_navigation_error(e, "getting source for", worker_id, url),
+            };
+            (
+                parser.parse(&source, Some(url), parser_options),
+                source.into_bytes(),
+            )
+        }
+    };
+    parser_result.resolve_links(url);
+
+    // Log processing time for debugging
+    let elapsed = worker_start.elapsed().as_secs_f64();
+    ::log::debug!(
+        "Worker {} processed {} with a registered parser in {:.2} seconds",
+        worker_id,
+        url,
+        elapsed
+    );
+
+    let mut page = PageData::from_parse_result(url, parser_result);
+    page.source = parser_options.keep_source.then_some(source);
+    Some(page)
+}
+
+/// Scrapes a text-based file (non-HTML) through the browser
+///
+/// Used when the resource could not be downloaded directly.
 async fn scrape_text_file(
     client: &Client,
     url: &str,
+    parser_type: ParserType,
+    parser_options: &ParserOptions,
     worker_id: usize,
     worker_start: std::time::Instant,
 ) -> Option<PageData> {
@@ -648,15 +1460,10 @@ async fn scrape_text_file(
         }
     };
 
-    // Parse the content using our unified Parser interface with text options
-    let text_options = parsers::text::TextParserOptions {
-        preserve_paragraphs: true, // Keep paragraph structure with exactly one empty line
-        preserve_line_breaks: false, // Don't preserve every line break
-        normalize_whitespace: true, // Remove extra whitespace
-        detect_urls: true,         // Keep URLs intact
-    };
-    let parser_result =
-        parsers::Parser::parse_from_url_with_text_options(&source, url, &text_options);
+    // Parse the content using our unified Parser interface with the crawl's parser options
+    let mut parser_result =
+        parsers::Parser::parse_with_url_and_options(&source, parser_type, url, parser_options);
+    parser_result.metadata.final_url = redirected_url(client, url).await;
 
     // Log processing time for debugging
     let elapsed = worker_start.elapsed().as_secs_f64();
@@ -667,29 +1474,20 @@ async fn scrape_text_file(
         elapsed
     );
 
-    Some(PageData {
-        url: url.to_string(),
-        title: None, // Add missing title field
-        content: parser_result.content,
-        links: parser_result.links, // Will be empty for text files
-    })
+    let mut page = PageData::from_parse_result(url, parser_result);
+    page.source = parser_options.keep_source.then(|| source.into_bytes());
+    Some(page)
 }
 
-/// Scrapes an HTML page
+/// Scrapes the HTML page the browser has loaded
 async fn scrape_html_page(
     client: &Client,
     url: &str,
+    content_type: Option<String>,
+    parser_options: &ParserOptions,
     worker_id: usize,
     worker_start: std::time::Instant,
 ) -> Option<PageData> {
-    // Navigate to the URL
-    match client.goto(url).await {
-        Ok(_) => {}
-        Err(e) => {
-            return handle_navigation_error(e, "accessing", worker_id, url);
-        }
-    };
-
     // Get the page source
     let html = match client.source().await {
         Ok(source) => source,
@@ -698,16 +1496,17 @@ async fn scrape_html_page(
         }
     };
 
-    // Parse the HTML content using our unified Parser interface with custom text options
-    // for any text content inside the HTML
-    let text_options = parsers::text::TextParserOptions {
-        preserve_paragraphs: true, // Keep paragraph structure with exactly one empty line
-        preserve_line_breaks: false, // Don't preserve every line break
-        normalize_whitespace: true, // Remove extra whitespace
-        detect_urls: true,         // Keep URLs intact
-    };
-    let parser_result =
-        parsers::Parser::parse_from_url_with_text_options(&html, url, &text_options);
+    // Parse the HTML content using our unified Parser interface with the crawl's parser options
+    let mut parser_result =
+        parsers::Parser::parse_with_url_and_options(&html, ParserType::Html, url, parser_options);
+
+    // The browser has already decoded the page, so record the encoding it used
+    parser_result.metadata.content_type = content_type;
+    parser_result.metadata.final_url = redirected_url(client, url).await;
+    if let Some(encoding) = browser_character_set(client).await {
+        parser_result.metadata.encoding = Some(encoding);
+        parser_result.metadata.encoding_source = Some(EncodingSource::Browser);
+    }
 
     // Log the number of links found
     ::log::info!("Found {} links in {}", parser_result.links.len(), url);
@@ -721,12 +1520,39 @@ async fn scrape_html_page(
         elapsed
     );
 
-    Some(PageData {
-        url: url.to_string(),
-        title: None, // Add missing title field
-        content: parser_result.content,
-        links: parser_result.links,
-    })
+    // The browser's source is the document as rendered, already decoded
+    let mut page = PageData::from_parse_result(url, parser_result);
+    page.source = parser_options.keep_source.then(|| html.into_bytes());
+    Some(page)
+}
+
+/// Returns the URL the browser is on if navigating to `url` redirected elsewhere
+async fn redirected_url(client: &Client, url: &str) -> Option<String> {
+    let mut current = match client.current_url().await {
+        Ok(current) => current,
+        Err(e) => {
+            ::log::debug!("Failed to read the current URL for {}: {}", url, e);
+            return None;
+        }
+    };
+    current.set_fragment(None);
+    let mut requested = Url::parse(url).ok()?;
+    requested.set_fragment(None);
+    (current != requested).then(|| current.to_string())
+}
+
+/// Returns the character encoding the browser used to decode the current page
+async fn browser_character_set(client: &Client) -> Option<String> {
+    match client
+        .execute("return document.characterSet;", vec![])
+        .await
+    {
+        Ok(value) => value.as_str().map(|s| s.to_string()),
+        Err(e) => {
+            ::log::debug!("Failed to read document.characterSet: {}", e);
+            None
+        }
+    }
 }
 
 /// Handles errors that occur during navigation or page source retrieval
@@ -748,3 +1574,101 @@ fn handle_navigation_error(
     }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+    use zip::ZipWriter;
+    use zip::write::SimpleFileOptions;
+
+    /// Runs a page through `process_discovered_page`, returning the URLs sent and queued
+    async fn discover(config: &WebCrawlerConfig, page: PageData) -> (Vec<String>, Vec<String>) {
+        let root_url = Url::parse(&config.start_url).unwrap();
+        let url_filter = create_url_filter(&root_url, config);
+        let (result_tx, mut result_rx) = mpsc::channel(10);
+        let (crawl_tx, mut crawl_rx) = mpsc::channel(10);
+        let visited = Arc::new(Mutex::new(HashSet::new()));
+        let url = page.url.clone();
+        assert!(
+            process_discovered_page(
+                0,
+                &url,
+                page,
+                config,
+                &root_url,
+                &url_filter,
+                &result_tx,
+                &crawl_tx,
+                &visited,
+                None,
+            )
+            .await
+        );
+        drop((result_tx, crawl_tx));
+
+        let mut sent = Vec::new();
+        while let Some(page) = result_rx.recv().await {
+            sent.push(page.url);
+        }
+        let mut queued = Vec::new();
+        while let Some(link) = crawl_rx.recv().await {
+            queued.push(link);
+        }
+        (sent, queued)
+    }
+
+    #[tokio::test]
+    async fn test_archive_member_links_not_queued() {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        zip.start_file("docs/intro.md", SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(b"# Intro\n\n[Logo](logo.png) and [next](https://example.org/next).")
+            .unwrap();
+        // Binary members aren't parsed, so only the link to this one knows about it
+        zip.start_file("docs/logo.png", SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(b"\x89PNG\r\n\x1a\n\x00\x00").unwrap();
+        let archive = zip.finish().unwrap().into_inner();
+
+        let config = WebCrawlerConfig::new("https://example.org/");
+        let pages = archive::expand_to_pages(
+            "https://example.org/bundle.zip",
+            &archive,
+            ArchiveFormat::Zip,
+            &config.archives,
+            &ContentTypeDetector::default(),
+            &ParserRegistry::new(),
+            &ParserOptionsSelector::new(Default::default(), &[]).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(pages.len(), 1);
+        let page = pages.into_iter().next().unwrap();
+        assert!(
+            page.link_details
+                .iter()
+                .any(|link| link.url == "https://example.org/bundle.zip!/docs/logo.png")
+        );
+
+        let url = page.url.clone();
+        let (sent, queued) = discover(&config, page).await;
+        assert_eq!(sent, vec![url]);
+        assert_eq!(queued, vec!["https://example.org/next"]);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_language_page_kept() {
+        let mut config = WebCrawlerConfig::new("https://example.org/");
+        config.include_languages = vec!["fr".to_string()];
+        let page = PageData::new(
+            "https://example.org/data.json".to_string(),
+            None,
+            "{}".to_string(),
+            vec!["https://example.org/about".to_string()],
+        );
+
+        let (sent, queued) = discover(&config, page).await;
+        assert_eq!(sent, vec!["https://example.org/data.json"]);
+        assert_eq!(queued, vec!["https://example.org/about"]);
+    }
+}
@@ -0,0 +1,727 @@
+use crate::results::PageData;
+use crate::sinks::{Compression, SqliteSink};
+use rusqlite::{Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::Path;
+
+/// Error type for loading and comparing crawls
+pub type DiffError = Box<dyn Error + Send + Sync>;
+
+/// Largest number of line pairs compared exactly; bigger changes count every line as changed
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// The pages of a stored crawl, keyed by URL
+#[derive(Debug, Clone, Default)]
+pub struct CrawlSnapshot {
+    pages: BTreeMap<String, PageData>,
+    /// Whether pages unchanged since the previous crawl were left out, as an
+    /// incremental crawl does without `--emit-unchanged`
+    omits_unchanged: bool,
+}
+
+impl CrawlSnapshot {
+    /// Creates a snapshot from pages; a URL seen twice keeps its last page
+    pub fn from_pages(pages: impl IntoIterator<Item = PageData>) -> Self {
+        Self {
+            pages: pages
+                .into_iter()
+                .map(|page| (page.url.clone(), page))
+                .collect(),
+            omits_unchanged: false,
+        }
+    }
+
+    /// Marks the crawl as leaving out pages that didn't change, so pages missing
+    /// from it aren't taken as removed
+    pub fn omitting_unchanged(mut self, omits_unchanged: bool) -> Self {
+        self.omits_unchanged = omits_unchanged;
+        self
+    }
+
+    /// Loads a crawl written as a SQLite database (`.db`, `.sqlite`, `.sqlite3`) or as
+    /// JSON Lines, compressed or not
+    pub fn load(path: &Path) -> Result<Self, DiffError> {
+        if SqliteSink::is_database_path(path) {
+            return Self::read_sqlite(path);
+        }
+
+        let file = File::open(path)?;
+        let reader: Box<dyn Read> = match Compression::from_path(path) {
+            Compression::None => Box::new(file),
+            Compression::Gzip => Box::new(flate2::read::MultiGzDecoder::new(file)),
+            Compression::Zstd => Box::new(zstd::Decoder::new(file)?),
+        };
+        Self::read_jsonl(BufReader::new(reader))
+            .map_err(|e| format!("{}: {}", path.display(), e).into())
+    }
+
+    /// Reads pages written by the JSON Lines sink, taking from its summary record
+    /// whether unchanged pages were left out
+    pub fn read_jsonl(reader: impl BufRead) -> Result<Self, DiffError> {
+        let mut pages = Vec::new();
+        let mut omits_unchanged = false;
+        for (number, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let value: serde_json::Value =
+                serde_json::from_str(&line).map_err(|e| format!("line {}: {}", number + 1, e))?;
+            if let Some(summary) = value.get("summary")
+                && value.get("url").is_none()
+            {
+                omits_unchanged = summary
+                    .get("omits_unchanged")
+                    .and_then(serde_json::Value::as_bool)
+                    .unwrap_or(false);
+                continue;
+            }
+            let page: PageData =
+                serde_json::from_value(value).map_err(|e| format!("line {}: {}", number + 1, e))?;
+            pages.push(page);
+        }
+        Ok(Self::from_pages(pages).omitting_unchanged(omits_unchanged))
+    }
+
+    /// Reads the pages stored by the SQLite sink in its latest run
+    ///
+    /// A database crawled into repeatedly keeps every page ever seen, so pages
+    /// last seen in an earlier run are left out, as they're gone from the site.
+    pub fn read_sqlite(path: &Path) -> Result<Self, DiffError> {
+        let connection = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        let mut select = connection.prepare(
+            "SELECT data FROM pages WHERE last_run_id = (SELECT MAX(id) FROM crawl_runs)",
+        )?;
+        let rows = select.query_map([], |row| row.get::<_, String>(0))?;
+        let mut pages = Vec::new();
+        for data in rows {
+            pages.push(serde_json::from_str::<PageData>(&data?)?);
+        }
+        Ok(Self::from_pages(pages))
+    }
+
+    /// Returns the page crawled at a URL
+    pub fn get(&self, url: &str) -> Option<&PageData> {
+        self.pages.get(url)
+    }
+
+    /// Number of pages in the crawl
+    pub fn len(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Returns whether the crawl has no pages
+    pub fn is_empty(&self) -> bool {
+        self.pages.is_empty()
+    }
+}
+
+/// Options for comparing two crawls
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffOptions {
+    /// Include a unified diff of the text of each changed page
+    pub content_diffs: bool,
+    /// Unchanged lines shown around each change in content diffs
+    pub context_lines: usize,
+    /// Changed pages with a smaller magnitude than this are counted as unchanged
+    pub min_magnitude: f64,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            content_diffs: true,
+            context_lines: 3,
+            min_magnitude: 0.0,
+        }
+    }
+}
+
+/// How a page differs between two crawls
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    /// Only in the new crawl
+    Added,
+    /// Only in the old crawl
+    Removed,
+    /// Now redirects to another URL
+    Redirected,
+    /// In both crawls with different title, text, headings, links or metadata
+    Changed,
+}
+
+/// A page that differs between two crawls
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PageChange {
+    /// URL of the page
+    pub url: String,
+    /// How the page differs
+    pub kind: ChangeKind,
+    /// Title of the page, from the new crawl unless it was removed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Where the page redirects to now
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redirected_to: Option<String>,
+    /// Fields that changed: `title`, `description`, `content`, `headings`, `links`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<String>,
+    /// Share of lines of text added or removed, from 0.0 (same text) to 1.0 (all new)
+    pub magnitude: f64,
+    /// Lines of text added
+    pub lines_added: usize,
+    /// Lines of text removed
+    pub lines_removed: usize,
+    /// Unified diff of the text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+}
+
+/// Differences between two crawls
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CrawlDiff {
+    /// Pages in the old crawl
+    pub old_pages: usize,
+    /// Pages in the new crawl
+    pub new_pages: usize,
+    /// Pages only in the new crawl
+    pub added: usize,
+    /// Pages only in the old crawl
+    pub removed: usize,
+    /// Pages that now redirect elsewhere
+    pub redirected: usize,
+    /// Pages whose content changed
+    pub changed: usize,
+    /// Pages in both crawls that are the same
+    pub unchanged: usize,
+    /// Every page that differs, by kind then URL
+    pub pages: Vec<PageChange>,
+}
+
+impl CrawlDiff {
+    /// Returns whether the crawls differ
+    pub fn has_changes(&self) -> bool {
+        !self.pages.is_empty()
+    }
+
+    /// Writes the report as text: the totals, then one line per page and its diff
+    pub fn write_text(&self, mut writer: impl Write) -> std::io::Result<()> {
+        writeln!(
+            writer,
+            "{} added, {} removed, {} redirected, {} changed, {} unchanged ({} -> {} pages)",
+            self.added,
+            self.removed,
+            self.redirected,
+            self.changed,
+            self.unchanged,
+            self.old_pages,
+            self.new_pages
+        )?;
+        for change in &self.pages {
+            writeln!(writer)?;
+            match change.kind {
+                ChangeKind::Added => writeln!(writer, "+ {}", change.url)?,
+                ChangeKind::Removed => writeln!(writer, "- {}", change.url)?,
+                ChangeKind::Redirected => writeln!(
+                    writer,
+                    "> {} -> {}",
+                    change.url,
+                    change.redirected_to.as_deref().unwrap_or_default()
+                )?,
+                ChangeKind::Changed => writeln!(
+                    writer,
+                    "~ {} ({:.2}; {}; +{} -{} lines)",
+                    change.url,
+                    change.magnitude,
+                    change.fields.join(", "),
+                    change.lines_added,
+                    change.lines_removed
+                )?,
+            }
+            if let Some(diff) = &change.diff {
+                for line in diff.lines() {
+                    writeln!(writer, "    {}", line)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compares two crawls by URL
+///
+/// Pages an incremental crawl marked unchanged, or left out, are the same as in the old crawl.
+pub fn diff_crawls(old: &CrawlSnapshot, new: &CrawlSnapshot, options: &DiffOptions) -> CrawlDiff {
+    let mut diff = CrawlDiff {
+        old_pages: old.len(),
+        new_pages: new.len(),
+        added: 0,
+        removed: 0,
+        redirected: 0,
+        changed: 0,
+        unchanged: 0,
+        pages: Vec::new(),
+    };
+
+    let urls: BTreeSet<&String> = old.pages.keys().chain(new.pages.keys()).collect();
+    for url in urls {
+        let change = match (old.get(url), new.get(url)) {
+            (None, Some(page)) => {
+                let lines = text_lines(&page.content).len();
+                Some(PageChange {
+                    lines_added: lines,
+                    magnitude: 1.0,
+                    ..page_change(page, ChangeKind::Added)
+                })
+            }
+            (Some(_), None) if new.omits_unchanged => None,
+            (Some(_), Some(after)) if after.metadata.unchanged => None,
+            (Some(page), None) => {
+                let lines = text_lines(&page.content).len();
+                Some(PageChange {
+                    lines_removed: lines,
+                    magnitude: 1.0,
+                    ..page_change(page, ChangeKind::Removed)
+                })
+            }
+            (Some(before), Some(after)) => compare_pages(before, after, options),
+            (None, None) => None,
+        };
+        match change {
+            Some(change) => {
+                match change.kind {
+                    ChangeKind::Added => diff.added += 1,
+                    ChangeKind::Removed => diff.removed += 1,
+                    ChangeKind::Redirected => diff.redirected += 1,
+                    ChangeKind::Changed => diff.changed += 1,
+                }
+                diff.pages.push(change);
+            }
+            None => diff.unchanged += 1,
+        }
+    }
+
+    diff.pages
+        .sort_by(|a, b| (a.kind, &a.url).cmp(&(b.kind, &b.url)));
+    diff
+}
+
+fn page_change(page: &PageData, kind: ChangeKind) -> PageChange {
+    PageChange {
+        url: page.url.clone(),
+        kind,
+        title: page.title.clone(),
+        redirected_to: None,
+        fields: Vec::new(),
+        magnitude: 0.0,
+        lines_added: 0,
+        lines_removed: 0,
+        diff: None,
+    }
+}
+
+/// Compares the two versions of a page, returning `None` if nothing worth reporting changed
+fn compare_pages(before: &PageData, after: &PageData, options: &DiffOptions) -> Option<PageChange> {
+    if let Some(target) = &after.metadata.final_url
+        && before.metadata.final_url.as_ref() != Some(target)
+    {
+        return Some(PageChange {
+            redirected_to: Some(target.clone()),
+            magnitude: 1.0,
+            ..page_change(after, ChangeKind::Redirected)
+        });
+    }
+
+    let old_lines = text_lines(&before.content);
+    let new_lines = text_lines(&after.content);
+    let mut fields = Vec::new();
+    if before.title != after.title {
+        fields.push("title");
+    }
+    if before.metadata.description != after.metadata.description {
+        fields.push("description");
+    }
+    if old_lines != new_lines {
+        fields.push("content");
+    }
+    let headings = |page: &PageData| {
+        page.headings
+            .iter()
+            .map(|heading| (heading.level, heading.text.clone()))
+            .collect::<Vec<_>>()
+    };
+    if headings(before) != headings(after) {
+        fields.push("headings");
+    }
+    let links = |page: &PageData| page.links.iter().cloned().collect::<BTreeSet<_>>();
+    if links(before) != links(after) {
+        fields.push("links");
+    }
+    if fields.is_empty() {
+        return None;
+    }
+
+    let ops = diff_lines(&old_lines, &new_lines);
+    let lines_added = ops.iter().filter(|op| matches!(op, Op::Insert(_))).count();
+    let lines_removed = ops.iter().filter(|op| matches!(op, Op::Delete(_))).count();
+    let total = old_lines.len() + new_lines.len();
+    let magnitude = if total == 0 {
+        0.0
+    } else {
+        (lines_added + lines_removed) as f64 / total as f64
+    };
+    // Changes outside the text, such as a new title, always count
+    if magnitude < options.min_magnitude && fields == ["content"] {
+        return None;
+    }
+
+    let diff = (options.content_diffs && lines_added + lines_removed > 0)
+        .then(|| unified_diff(&old_lines, &new_lines, &ops, options.context_lines));
+    Some(PageChange {
+        fields: fields.into_iter().map(str::to_string).collect(),
+        magnitude,
+        lines_added,
+        lines_removed,
+        diff,
+        ..page_change(after, ChangeKind::Changed)
+    })
+}
+
+/// Splits text into trimmed, non-empty lines, so reflowed whitespace isn't a change
+fn text_lines(text: &str) -> Vec<&str> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+/// A step turning the old lines into the new ones, with the index of its line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Finds the steps turning `old` into `new` from their longest common subsequence
+fn diff_lines(old: &[&str], new: &[&str]) -> Vec<Op> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (a, b) = (
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    );
+
+    let mut ops: Vec<Op> = (0..prefix).map(|i| Op::Equal(i, i)).collect();
+    if (a.len() + 1) * (b.len() + 1) <= MAX_DIFF_CELLS {
+        // lengths[i][j] is the length of the common subsequence of a[i..] and b[j..]
+        let width = b.len() + 1;
+        let mut lengths = vec![0u32; (a.len() + 1) * width];
+        for i in (0..a.len()).rev() {
+            for j in (0..b.len()).rev() {
+                lengths[i * width + j] = if a[i] == b[j] {
+                    lengths[(i + 1) * width + j + 1] + 1
+                } else {
+                    lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() || j < b.len() {
+            if i < a.len() && j < b.len() && a[i] == b[j] {
+                ops.push(Op::Equal(prefix + i, prefix + j));
+                i += 1;
+                j += 1;
+            } else if j == b.len()
+                || (i < a.len() && lengths[(i + 1) * width + j] >= lengths[i * width + j + 1])
+            {
+                ops.push(Op::Delete(prefix + i));
+                i += 1;
+            } else {
+                ops.push(Op::Insert(prefix + j));
+                j += 1;
+            }
+        }
+    } else {
+        ops.extend((0..a.len()).map(|i| Op::Delete(prefix + i)));
+        ops.extend((0..b.len()).map(|j| Op::Insert(prefix + j)));
+    }
+    ops.extend((0..suffix).map(|k| Op::Equal(old.len() - suffix + k, new.len() - suffix + k)));
+    ops
+}
+
+/// Formats the steps as unified diff hunks with `context` unchanged lines around changes
+fn unified_diff(old: &[&str], new: &[&str], ops: &[Op], context: usize) -> String {
+    let changed: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, Op::Equal(..)))
+        .map(|(index, _)| index)
+        .collect();
+
+    // Group changes whose context overlaps into the same hunk
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for &index in &changed {
+        let start = index.saturating_sub(context);
+        let end = (index + context + 1).min(ops.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut out = String::new();
+    for (start, end) in hunks {
+        let hunk = &ops[start..end];
+        // Line numbers where the hunk starts, counting the lines before it
+        let old_start = ops[..start]
+            .iter()
+            .filter(|op| !matches!(op, Op::Insert(_)))
+            .count();
+        let new_start = ops[..start]
+            .iter()
+            .filter(|op| !matches!(op, Op::Delete(_)))
+            .count();
+        let old_count = hunk
+            .iter()
+            .filter(|op| !matches!(op, Op::Insert(_)))
+            .count();
+        let new_count = hunk
+            .iter()
+            .filter(|op| !matches!(op, Op::Delete(_)))
+            .count();
+        let _ = writeln!(
+            out,
+            "@@ -{},{} +{},{} @@",
+            old_start + 1,
+            old_count,
+            new_start + 1,
+            new_count
+        );
+        for op in hunk {
+            let _ = match *op {
+                Op::Equal(i, _) => writeln!(out, " {}", old[i]),
+                Op::Delete(i) => writeln!(out, "-{}", old[i]),
+                Op::Insert(j) => writeln!(out, "+{}", new[j]),
+            };
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(url: &str, title: &str, content: &str) -> PageData {
+        PageData::new(
+            url.to_string(),
+            Some(title.to_string()),
+            content.to_string(),
+            Vec::new(),
+        )
+    }
+
+    #[test]
+    fn test_diff_crawls() {
+        let old = CrawlSnapshot::from_pages([
+            page("https://example.org/", "Home", "Welcome\nNews\nContact"),
+            page("https://example.org/old", "Old", "Gone soon"),
+            page("https://example.org/moved", "Moved", "Here"),
+            page("https://example.org/same", "Same", "Nothing new"),
+            page("https://example.org/retitled", "Before", "Text"),
+        ]);
+        let mut moved = page("https://example.org/moved", "Elsewhere", "There");
+        moved.metadata.final_url = Some("https://example.org/elsewhere".to_string());
+        let new = CrawlSnapshot::from_pages([
+            page(
+                "https://example.org/",
+                "Home",
+                "Welcome\nLatest news\nContact",
+            ),
+            page("https://example.org/new", "New", "Fresh\npage"),
+            moved,
+            page("https://example.org/same", "Same", "  Nothing new  "),
+            page("https://example.org/retitled", "After", "Text"),
+        ]);
+
+        let diff = diff_crawls(&old, &new, &DiffOptions::default());
+        assert_eq!(
+            (diff.added, diff.removed, diff.redirected, diff.changed),
+            (1, 1, 1, 2)
+        );
+        // Only surrounding whitespace changed on /same
+        assert_eq!((diff.old_pages, diff.new_pages, diff.unchanged), (5, 5, 1));
+
+        let summary: Vec<(ChangeKind, &str)> = diff
+            .pages
+            .iter()
+            .map(|change| (change.kind, change.url.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (ChangeKind::Added, "https://example.org/new"),
+                (ChangeKind::Removed, "https://example.org/old"),
+                (ChangeKind::Redirected, "https://example.org/moved"),
+                (ChangeKind::Changed, "https://example.org/"),
+                (ChangeKind::Changed, "https://example.org/retitled"),
+            ]
+        );
+
+        let home = &diff.pages[3];
+        assert_eq!(home.fields, vec!["content"]);
+        assert_eq!((home.lines_added, home.lines_removed), (1, 1));
+        assert!((home.magnitude - 2.0 / 6.0).abs() < 1e-9);
+        assert_eq!(
+            home.diff.as_deref(),
+            Some("@@ -1,3 +1,3 @@\n Welcome\n-News\n+Latest news\n Contact\n")
+        );
+        assert_eq!(
+            diff.pages[2].redirected_to.as_deref(),
+            Some("https://example.org/elsewhere")
+        );
+
+        let retitled = &diff.pages[4];
+        assert_eq!(retitled.fields, vec!["title"]);
+        assert_eq!((retitled.magnitude, retitled.diff.as_deref()), (0.0, None));
+
+        let strict = diff_crawls(
+            &old,
+            &new,
+            &DiffOptions {
+                min_magnitude: 0.5,
+                ..DiffOptions::default()
+            },
+        );
+        assert_eq!((strict.changed, strict.unchanged), (1, 2));
+        assert_eq!(strict.pages[3].url, "https://example.org/retitled");
+
+        let mut text = Vec::new();
+        diff.write_text(&mut text).unwrap();
+        let text = String::from_utf8(text).unwrap();
+        assert!(text.starts_with(
+            "1 added, 1 removed, 1 redirected, 2 changed, 1 unchanged (5 -> 5 pages)\n"
+        ));
+        assert!(text.contains("\n> https://example.org/moved -> https://example.org/elsewhere\n"));
+        assert!(text.contains("\n~ https://example.org/ (0.33; content; +1 -1 lines)\n"));
+        assert!(text.contains("\n    -News\n    +Latest news\n"));
+    }
+
+    #[test]
+    fn test_unified_diff_hunks() {
+        let old: Vec<String> = (1..=20).map(|n| n.to_string()).collect();
+        let mut new = old.clone();
+        new[1] = "two".to_string();
+        new.remove(17);
+        let old: Vec<&str> = old.iter().map(String::as_str).collect();
+        let new: Vec<&str> = new.iter().map(String::as_str).collect();
+
+        let ops = diff_lines(&old, &new);
+        assert_eq!(
+            unified_diff(&old, &new, &ops, 1),
+            "@@ -1,3 +1,3 @@\n 1\n-2\n+two\n 3\n@@ -17,3 +17,2 @@\n 17\n-18\n 19\n"
+        );
+    }
+
+    #[test]
+    fn test_incremental_crawl_with_unchanged_pages() {
+        let old = CrawlSnapshot::from_pages([
+            page("https://example.org/", "Home", "Welcome"),
+            page("https://example.org/about", "About", "Us"),
+        ]);
+        let mut unchanged = page("https://example.org/about", "About", "");
+        unchanged.metadata.unchanged = true;
+        let new = CrawlSnapshot::from_pages([
+            page("https://example.org/", "Home", "Welcome back"),
+            unchanged,
+        ]);
+
+        let diff = diff_crawls(&old, &new, &DiffOptions::default());
+        assert_eq!((diff.changed, diff.removed, diff.unchanged), (1, 0, 1));
+        assert_eq!(diff.pages[0].url, "https://example.org/");
+    }
+
+    #[test]
+    fn test_incremental_crawl_omitting_unchanged_pages() {
+        let old = CrawlSnapshot::from_pages([
+            page("https://example.org/", "Home", "Welcome"),
+            page("https://example.org/about", "About", "Us"),
+        ]);
+        let lines = concat!(
+            r#"{"url":"https://example.org/","title":"Home","content":"Welcome back","links":[]}"#,
+            "\n",
+            r#"{"summary":{"uri":"https://example.org/","pages":1,"links":0,"started_at":0,"duration_secs":1.0,"omits_unchanged":true}}"#,
+            "\n",
+        );
+        let new = CrawlSnapshot::read_jsonl(lines.as_bytes()).unwrap();
+
+        let diff = diff_crawls(&old, &new, &DiffOptions::default());
+        assert_eq!((diff.changed, diff.removed, diff.unchanged), (1, 0, 1));
+
+        // A full crawl missing the page still reports it removed
+        let full = CrawlSnapshot::from_pages([page("https://example.org/", "Home", "Welcome")]);
+        assert_eq!(diff_crawls(&old, &full, &DiffOptions::default()).removed, 1);
+    }
+
+    #[test]
+    fn test_read_jsonl() {
+        let lines = concat!(
+            r#"{"url":"https://example.org/","title":"Home","content":"Hi","links":[]}"#,
+            "\n\n",
+            r#"{"summary":{"uri":"https://example.org/","pages":1,"links":0,"started_at":0,"duration_secs":1.0}}"#,
+            "\n",
+        );
+        let snapshot = CrawlSnapshot::read_jsonl(lines.as_bytes()).unwrap();
+        assert_eq!(snapshot.len(), 1);
+        assert_eq!(
+            snapshot
+                .get("https://example.org/")
+                .unwrap()
+                .title
+                .as_deref(),
+            Some("Home")
+        );
+
+        let error = CrawlSnapshot::read_jsonl("{\"url\": 1}\n".as_bytes()).unwrap_err();
+        assert!(error.to_string().starts_with("line 1: "));
+    }
+
+    #[test]
+    fn test_read_sqlite_latest_run() {
+        use crate::sinks::{CrawlSummary, PageSink};
+
+        let path = std::env::temp_dir().join(format!("yield-page-{}.db", uuid::Uuid::new_v4()));
+        let crawl = |urls: &[&str]| {
+            let mut sink = Box::new(SqliteSink::open(&path, "https://example.org/").unwrap());
+            for url in urls {
+                sink.write_page(&page(url, "Page", "Text")).unwrap();
+            }
+            sink.finish(&CrawlSummary::start("https://example.org/"))
+                .unwrap();
+        };
+        crawl(&["https://example.org/", "https://example.org/old"]);
+        crawl(&["https://example.org/", "https://example.org/new"]);
+
+        // The page only seen in the first run is gone from the second
+        let snapshot = CrawlSnapshot::load(&path).unwrap();
+        assert_eq!(snapshot.len(), 2);
+        assert!(snapshot.get("https://example.org/old").is_none());
+        assert!(snapshot.get("https://example.org/new").is_some());
+
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+    }
+}
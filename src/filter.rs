@@ -1,3 +1,4 @@
+use crate::parsers::language;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use url::Url;
@@ -24,6 +25,14 @@ pub struct UrlFilterConfig {
     /// Regex patterns for URLs to exclude (these take precedence over include patterns)
     #[serde(default)]
     pub exclude_patterns: Vec<String>,
+
+    /// Languages to keep (if empty, all languages are kept unless excluded)
+    #[serde(default)]
+    pub include_languages: Vec<String>,
+
+    /// Languages to drop (these take precedence over included languages)
+    #[serde(default)]
+    pub exclude_languages: Vec<String>,
 }
 
 /// Default value for allow_external field (false for safety)
@@ -44,6 +53,8 @@ impl Default for UrlFilterConfig {
                 // Common directories to exclude
                 r"/_sources/".to_string(),
             ],
+            include_languages: Vec::new(),
+            exclude_languages: Vec::new(),
         }
     }
 }
@@ -126,6 +137,33 @@ impl UrlFilter {
         true
     }
 
+    /// Determine if a page in the given language should be kept
+    ///
+    /// Languages match by prefix, so `en` also matches `en-gb`. Pages whose
+    /// language is unknown, such as data files or pages without a `lang`
+    /// attribute, are always kept so the crawl can follow their links.
+    pub fn accepts_language(&self, tag: Option<&str>) -> bool {
+        let Some(tag) = tag else {
+            return true;
+        };
+
+        if self
+            .config
+            .exclude_languages
+            .iter()
+            .any(|l| language::matches(tag, l))
+        {
+            return false;
+        }
+
+        self.config.include_languages.is_empty()
+            || self
+                .config
+                .include_languages
+                .iter()
+                .any(|l| language::matches(tag, l))
+    }
+
     /// Check if a URL should be parsed for links (some text-based files shouldn't be parsed)
     pub fn should_parse_links(&self, url: &Url) -> bool {
         // Don't parse text files, YAML files, etc. for links
@@ -135,10 +173,10 @@ impl UrlFilter {
         let no_parse_patterns = [r"\.txt$", r"\.ya?ml$", r"/_sources/"];
 
         for pattern in &no_parse_patterns {
-            if let Ok(regex) = Regex::new(pattern) {
-                if regex.is_match(url_str) {
-                    return false;
-                }
+            if let Ok(regex) = Regex::new(pattern)
+                && regex.is_match(url_str)
+            {
+                return false;
             }
         }
 
@@ -214,6 +252,8 @@ mod tests {
                 r"\.(jpg|jpeg|png|gif|css|js|ico|svg|woff|woff2|ttf|eot|pdf)$".to_string(),
                 r"/_sources/".to_string(),
             ],
+            include_languages: vec![],
+            exclude_languages: vec![],
         };
         let filter_allowing_external = UrlFilter::new(config).unwrap();
         assert!(filter_allowing_external.should_crawl(&html_url, None));
@@ -227,6 +267,8 @@ mod tests {
             required_path_prefix: None,
             include_patterns: vec![],
             exclude_patterns: vec![],
+            include_languages: vec![],
+            exclude_languages: vec![],
         };
         let filter = UrlFilter::new(config).unwrap();
 
@@ -247,6 +289,8 @@ mod tests {
             required_path_prefix: Some("/docs".to_string()),
             include_patterns: vec![],
             exclude_patterns: vec![],
+            include_languages: vec![],
+            exclude_languages: vec![],
         };
         let filter = UrlFilter::new(config).unwrap();
 
@@ -267,6 +311,8 @@ mod tests {
             required_path_prefix: None,
             include_patterns: vec![r"/docs/.*\.html$".to_string()],
             exclude_patterns: vec![r"/docs/draft/".to_string()],
+            include_languages: vec![],
+            exclude_languages: vec![],
         };
         let filter = UrlFilter::new(config).unwrap();
 
@@ -299,4 +345,27 @@ mod tests {
         let html_url = Url::parse("https://example.com/page.html").unwrap();
         assert!(filter.should_parse_links(&html_url));
     }
+
+    #[test]
+    fn test_language_filter() {
+        let config = UrlFilterConfig {
+            include_languages: vec!["en".to_string(), "pt-BR".to_string()],
+            exclude_languages: vec!["en-in".to_string()],
+            ..UrlFilterConfig::default()
+        };
+        let filter = UrlFilter::new(config).unwrap();
+
+        assert!(filter.accepts_language(Some("en")));
+        assert!(filter.accepts_language(Some("en-GB")));
+        assert!(filter.accepts_language(Some("pt-br")));
+        assert!(!filter.accepts_language(Some("pt")));
+        assert!(!filter.accepts_language(Some("en-IN")));
+        assert!(!filter.accepts_language(Some("fr")));
+        // Pages with no detected language aren't dropped, or the crawl would stop at them
+        assert!(filter.accepts_language(None));
+
+        let filter = UrlFilter::default();
+        assert!(filter.accepts_language(None));
+        assert!(filter.accepts_language(Some("de")));
+    }
 }
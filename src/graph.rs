@@ -0,0 +1,723 @@
+use crate::parsers::LinkKind;
+use crate::results::PageData;
+use crate::utils::without_fragment;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::error::Error;
+use std::io::Write;
+use std::path::Path;
+use url::Url;
+
+/// Error type for graph exports
+pub type GraphError = Box<dyn Error + Send + Sync>;
+
+/// Probability of following a link rather than jumping to a random page in PageRank
+const DAMPING: f64 = 0.85;
+
+/// PageRank stops once no score changes by more than this between iterations
+const TOLERANCE: f64 = 1e-9;
+
+const MAX_ITERATIONS: usize = 100;
+
+/// A link from one page to another
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LinkEdge {
+    /// URL of the page the link is on
+    pub source: String,
+    /// URL the link points to, without its fragment
+    pub target: String,
+    /// Anchor text of the link
+    pub text: Option<String>,
+    /// How the link was discovered, if the parser reports typed links
+    pub kind: Option<LinkKind>,
+    /// Whether the link is marked nofollow
+    pub nofollow: bool,
+    /// Whether the target is on the same host as the crawl's start URL
+    pub internal: bool,
+}
+
+/// A URL in the graph, with its metrics
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeMetrics {
+    /// URL of the page
+    pub url: String,
+    /// Whether the page was crawled, rather than only linked to
+    pub crawled: bool,
+    /// Whether the page is on the same host as the crawl's start URL
+    pub internal: bool,
+    /// Number of other pages linking here
+    pub in_degree: usize,
+    /// Number of other pages linked from here
+    pub out_degree: usize,
+    /// Share of PageRank; the scores of all nodes add up to 1
+    pub pagerank: f64,
+    /// Fewest clicks needed to reach the page from the start URL, if reachable
+    pub depth: Option<usize>,
+}
+
+/// Metrics computed over a link graph
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphMetrics {
+    /// URL the crawl started from
+    pub start_url: Option<String>,
+    /// Number of URLs in the graph
+    pub nodes: usize,
+    /// Number of distinct links between different URLs
+    pub edges: usize,
+    /// Crawled internal pages no other page links to, apart from the start URL
+    pub orphans: Vec<String>,
+    /// Metrics per URL, highest PageRank first
+    pub pages: Vec<NodeMetrics>,
+}
+
+/// Format of a link graph export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphFormat {
+    /// GraphML, for Gephi, yEd or NetworkX
+    GraphMl,
+    /// Graphviz DOT
+    Dot,
+    /// CSV edge list
+    Csv,
+}
+
+impl GraphFormat {
+    /// Infers the format from a file extension (`.graphml`, `.dot`/`.gv`, `.csv`)
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "graphml" => Some(Self::GraphMl),
+            "dot" | "gv" => Some(Self::Dot),
+            "csv" => Some(Self::Csv),
+            _ => None,
+        }
+    }
+}
+
+/// The directed graph of links between crawled pages
+///
+/// Nodes are URLs, both crawled pages and the targets they link to. Only links
+/// the crawler would follow by default are recorded (anchors, frames, pagination
+/// and refreshes), so stylesheets, icons and feeds don't skew the metrics.
+#[derive(Debug, Clone, Default)]
+pub struct LinkGraph {
+    start_url: Option<String>,
+    start_host: Option<String>,
+    nodes: Vec<String>,
+    crawled: Vec<bool>,
+    index: HashMap<String, usize>,
+    edges: Vec<LinkEdge>,
+}
+
+impl LinkGraph {
+    /// Creates an empty graph for a crawl starting at `start_url`
+    ///
+    /// If `start_url` isn't a web URL (e.g. a replayed WARC file), the first page
+    /// added is taken as the start.
+    pub fn new(start_url: &str) -> Self {
+        let start_url = without_fragment(start_url);
+        match host(&start_url) {
+            Some(start_host) => Self {
+                start_host: Some(start_host),
+                start_url: Some(start_url),
+                ..Self::default()
+            },
+            None => Self::default(),
+        }
+    }
+
+    /// Adds a crawled page and its links
+    pub fn add_page(&mut self, page: &PageData) {
+        let source = without_fragment(&page.url);
+        if self.start_url.is_none() {
+            self.start_host = host(&source);
+            self.start_url = Some(source.clone());
+        }
+        let id = self.node(&source);
+        self.crawled[id] = true;
+
+        let follow = LinkKind::default_follow();
+        for (url, link) in page.outgoing_links() {
+            if link.is_some_and(|link| !follow.contains(&link.kind)) {
+                continue;
+            }
+            // Skip `mailto:`, `javascript:` and other links that can't be crawled
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                continue;
+            }
+            let target = without_fragment(url);
+            self.node(&target);
+            self.edges.push(LinkEdge {
+                internal: self.is_internal(&source, &target),
+                source: source.clone(),
+                target,
+                text: link.and_then(|link| link.text.clone()),
+                kind: link.map(|link| link.kind),
+                nofollow: link.is_some_and(|link| link.nofollow),
+            });
+        }
+    }
+
+    /// Returns every link recorded, in the order found
+    pub fn edges(&self) -> &[LinkEdge] {
+        &self.edges
+    }
+
+    /// Returns the number of URLs in the graph
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn node(&mut self, url: &str) -> usize {
+        if let Some(&id) = self.index.get(url) {
+            return id;
+        }
+        let id = self.nodes.len();
+        self.nodes.push(url.to_string());
+        self.crawled.push(false);
+        self.index.insert(url.to_string(), id);
+        id
+    }
+
+    fn is_internal(&self, source: &str, target: &str) -> bool {
+        let reference = match &self.start_host {
+            Some(host) => Some(host.clone()),
+            None => host(source),
+        };
+        reference.is_some() && host(target) == reference
+    }
+
+    /// Distinct links between different URLs, as outgoing node IDs per node
+    fn adjacency(&self) -> Vec<BTreeSet<usize>> {
+        let mut adjacency = vec![BTreeSet::new(); self.nodes.len()];
+        for edge in &self.edges {
+            let (source, target) = (self.index[&edge.source], self.index[&edge.target]);
+            if source != target {
+                adjacency[source].insert(target);
+            }
+        }
+        adjacency
+    }
+
+    /// Computes degrees, PageRank, click depth and orphan pages
+    pub fn metrics(&self) -> GraphMetrics {
+        let adjacency = self.adjacency();
+        let n = self.nodes.len();
+
+        let mut in_degree = vec![0; n];
+        for targets in &adjacency {
+            for &target in targets {
+                in_degree[target] += 1;
+            }
+        }
+        let pagerank = pagerank(&adjacency);
+        let depth = self.click_depth(&adjacency);
+        let start = self.start_url.as_ref().and_then(|url| self.index.get(url));
+
+        let mut pages: Vec<NodeMetrics> = (0..n)
+            .map(|id| NodeMetrics {
+                url: self.nodes[id].clone(),
+                crawled: self.crawled[id],
+                internal: self.start_host.is_none() || host(&self.nodes[id]) == self.start_host,
+                in_degree: in_degree[id],
+                out_degree: adjacency[id].len(),
+                pagerank: pagerank[id],
+                depth: depth[id],
+            })
+            .collect();
+
+        let orphans = pages
+            .iter()
+            .enumerate()
+            .filter(|&(id, page)| {
+                page.crawled && page.internal && page.in_degree == 0 && Some(&id) != start
+            })
+            .map(|(_, page)| page.url.clone())
+            .collect();
+
+        pages.sort_by(|a, b| {
+            b.pagerank
+                .total_cmp(&a.pagerank)
+                .then_with(|| a.url.cmp(&b.url))
+        });
+
+        GraphMetrics {
+            start_url: self.start_url.clone(),
+            nodes: n,
+            edges: adjacency.iter().map(BTreeSet::len).sum(),
+            orphans,
+            pages,
+        }
+    }
+
+    /// Breadth-first distance of each node from the start URL
+    fn click_depth(&self, adjacency: &[BTreeSet<usize>]) -> Vec<Option<usize>> {
+        let mut depth = vec![None; self.nodes.len()];
+        let Some(&start) = self.start_url.as_ref().and_then(|url| self.index.get(url)) else {
+            return depth;
+        };
+
+        depth[start] = Some(0);
+        let mut queue = VecDeque::from([start]);
+        while let Some(node) = queue.pop_front() {
+            let next = depth[node].map(|d| d + 1);
+            for &target in &adjacency[node] {
+                if depth[target].is_none() {
+                    depth[target] = next;
+                    queue.push_back(target);
+                }
+            }
+        }
+        depth
+    }
+
+    /// Writes the graph in a format
+    pub fn write(&self, format: GraphFormat, writer: impl Write) -> Result<(), GraphError> {
+        match format {
+            GraphFormat::GraphMl => self.write_graphml(writer),
+            GraphFormat::Dot => self.write_dot(writer),
+            GraphFormat::Csv => self.write_csv(writer),
+        }
+    }
+
+    /// Writes the graph to a file, in the format given by its extension
+    pub fn write_file(&self, path: &Path) -> Result<(), GraphError> {
+        let format = GraphFormat::from_path(path).ok_or_else(|| {
+            format!(
+                "Unknown graph format for {} (use .graphml, .dot or .csv)",
+                path.display()
+            )
+        })?;
+        let file = std::fs::File::create(path)?;
+        self.write(format, std::io::BufWriter::new(file))
+    }
+
+    /// Writes the graph as GraphML, with node metrics as attributes
+    pub fn write_graphml(&self, mut writer: impl Write) -> Result<(), GraphError> {
+        let metrics = self.metrics();
+        let by_url: HashMap<&str, &NodeMetrics> =
+            metrics.pages.iter().map(|m| (m.url.as_str(), m)).collect();
+
+        writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            writer,
+            r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#
+        )?;
+        for (id, name, kind, on) in [
+            ("url", "url", "string", "node"),
+            ("crawled", "crawled", "boolean", "node"),
+            ("internal", "internal", "boolean", "node"),
+            ("in_degree", "in_degree", "int", "node"),
+            ("out_degree", "out_degree", "int", "node"),
+            ("pagerank", "pagerank", "double", "node"),
+            ("depth", "depth", "int", "node"),
+            ("text", "text", "string", "edge"),
+            ("kind", "kind", "string", "edge"),
+            ("nofollow", "nofollow", "boolean", "edge"),
+            ("edge_internal", "internal", "boolean", "edge"),
+        ] {
+            writeln!(
+                writer,
+                r#"  <key id="{}" for="{}" attr.name="{}" attr.type="{}"/>"#,
+                id, on, name, kind
+            )?;
+        }
+        writeln!(writer, r#"  <graph id="links" edgedefault="directed">"#)?;
+
+        for (id, url) in self.nodes.iter().enumerate() {
+            let node = by_url[url.as_str()];
+            writeln!(writer, r#"    <node id="n{}">"#, id)?;
+            writeln!(
+                writer,
+                r#"      <data key="url">{}</data>"#,
+                xml_escape(url)
+            )?;
+            writeln!(
+                writer,
+                r#"      <data key="crawled">{}</data>"#,
+                node.crawled
+            )?;
+            writeln!(
+                writer,
+                r#"      <data key="internal">{}</data>"#,
+                node.internal
+            )?;
+            writeln!(
+                writer,
+                r#"      <data key="in_degree">{}</data>"#,
+                node.in_degree
+            )?;
+            writeln!(
+                writer,
+                r#"      <data key="out_degree">{}</data>"#,
+                node.out_degree
+            )?;
+            writeln!(
+                writer,
+                r#"      <data key="pagerank">{}</data>"#,
+                node.pagerank
+            )?;
+            if let Some(depth) = node.depth {
+                writeln!(writer, r#"      <data key="depth">{}</data>"#, depth)?;
+            }
+            writeln!(writer, "    </node>")?;
+        }
+
+        for edge in &self.edges {
+            writeln!(
+                writer,
+                r#"    <edge source="n{}" target="n{}">"#,
+                self.index[&edge.source], self.index[&edge.target]
+            )?;
+            if let Some(text) = &edge.text {
+                writeln!(
+                    writer,
+                    r#"      <data key="text">{}</data>"#,
+                    xml_escape(text)
+                )?;
+            }
+            if let Some(kind) = edge.kind {
+                writeln!(
+                    writer,
+                    r#"      <data key="kind">{}</data>"#,
+                    kind_name(kind)
+                )?;
+            }
+            writeln!(
+                writer,
+                r#"      <data key="nofollow">{}</data>"#,
+                edge.nofollow
+            )?;
+            writeln!(
+                writer,
+                r#"      <data key="edge_internal">{}</data>"#,
+                edge.internal
+            )?;
+            writeln!(writer, "    </edge>")?;
+        }
+
+        writeln!(writer, "  </graph>")?;
+        writeln!(writer, "</graphml>")?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Writes the graph as Graphviz DOT; pages not crawled are drawn dashed
+    pub fn write_dot(&self, mut writer: impl Write) -> Result<(), GraphError> {
+        writeln!(writer, "digraph links {{")?;
+        for (id, url) in self.nodes.iter().enumerate() {
+            let style = if self.crawled[id] {
+                ""
+            } else {
+                ", style=dashed"
+            };
+            writeln!(
+                writer,
+                "  n{} [label=\"{}\"{}];",
+                id,
+                dot_escape(url),
+                style
+            )?;
+        }
+        for edge in &self.edges {
+            write!(
+                writer,
+                "  n{} -> n{}",
+                self.index[&edge.source], self.index[&edge.target]
+            )?;
+            match &edge.text {
+                Some(text) => writeln!(writer, " [label=\"{}\"];", dot_escape(text))?,
+                None => writeln!(writer, ";")?,
+            }
+        }
+        writeln!(writer, "}}")?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Writes the links as a CSV edge list
+    pub fn write_csv(&self, writer: impl Write) -> Result<(), GraphError> {
+        let mut csv = csv::Writer::from_writer(writer);
+        csv.write_record(["source", "target", "text", "kind", "nofollow", "internal"])?;
+        for edge in &self.edges {
+            csv.write_record([
+                edge.source.as_str(),
+                edge.target.as_str(),
+                edge.text.as_deref().unwrap_or_default(),
+                &edge.kind.map(kind_name).unwrap_or_default(),
+                bool_str(edge.nofollow),
+                bool_str(edge.internal),
+            ])?;
+        }
+        csv.flush()?;
+        Ok(())
+    }
+}
+
+impl GraphMetrics {
+    /// Writes the metrics to a file: CSV with a row per URL for `.csv`, otherwise JSON
+    pub fn write_file(&self, path: &Path) -> Result<(), GraphError> {
+        let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        if GraphFormat::from_path(path) == Some(GraphFormat::Csv) {
+            self.write_csv(file)
+        } else {
+            serde_json::to_writer_pretty(file, self)?;
+            Ok(())
+        }
+    }
+
+    /// Writes a CSV row of metrics per URL
+    pub fn write_csv(&self, writer: impl Write) -> Result<(), GraphError> {
+        let mut csv = csv::Writer::from_writer(writer);
+        csv.write_record([
+            "url",
+            "crawled",
+            "internal",
+            "in_degree",
+            "out_degree",
+            "pagerank",
+            "depth",
+            "orphan",
+        ])?;
+        for page in &self.pages {
+            csv.write_record([
+                page.url.clone(),
+                bool_str(page.crawled).to_string(),
+                bool_str(page.internal).to_string(),
+                page.in_degree.to_string(),
+                page.out_degree.to_string(),
+                format!("{:.6}", page.pagerank),
+                page.depth.map(|d| d.to_string()).unwrap_or_default(),
+                bool_str(self.orphans.contains(&page.url)).to_string(),
+            ])?;
+        }
+        csv.flush()?;
+        Ok(())
+    }
+}
+
+/// PageRank by power iteration; pages without links share their rank with every page
+fn pagerank(adjacency: &[BTreeSet<usize>]) -> Vec<f64> {
+    let n = adjacency.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let mut rank = vec![1.0 / n as f64; n];
+
+    for _ in 0..MAX_ITERATIONS {
+        let dangling: f64 = (0..n)
+            .filter(|&id| adjacency[id].is_empty())
+            .map(|id| rank[id])
+            .sum();
+        let base = (1.0 - DAMPING + DAMPING * dangling) / n as f64;
+        let mut next = vec![base; n];
+        for (id, targets) in adjacency.iter().enumerate() {
+            if targets.is_empty() {
+                continue;
+            }
+            let share = DAMPING * rank[id] / targets.len() as f64;
+            for &target in targets {
+                next[target] += share;
+            }
+        }
+
+        let change = rank
+            .iter()
+            .zip(&next)
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0, f64::max);
+        rank = next;
+        if change < TOLERANCE {
+            break;
+        }
+    }
+    rank
+}
+
+fn host(url: &str) -> Option<String> {
+    Url::parse(url)
+        .ok()?
+        .host_str()
+        .map(|host| host.to_ascii_lowercase())
+}
+
+/// Name of a link kind as it appears in JSON output
+fn kind_name(kind: LinkKind) -> String {
+    serde_json::to_value(kind)
+        .ok()
+        .and_then(|value| value.as_str().map(|name| name.to_string()))
+        .unwrap_or_default()
+}
+
+fn bool_str(value: bool) -> &'static str {
+    if value { "true" } else { "false" }
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::Link;
+
+    fn page(url: &str, links: &[(&str, LinkKind)]) -> PageData {
+        let mut page = PageData::new(
+            url.to_string(),
+            None,
+            String::new(),
+            links.iter().map(|(l, _)| l.to_string()).collect(),
+        );
+        page.link_details = links
+            .iter()
+            .map(|(l, kind)| Link {
+                url: l.to_string(),
+                href: l.to_string(),
+                kind: *kind,
+                text: Some(format!("to {}", l)),
+                rel: Vec::new(),
+                nofollow: false,
+                element: "a".to_string(),
+                hreflang: None,
+            })
+            .collect();
+        page
+    }
+
+    fn graph() -> LinkGraph {
+        use LinkKind::*;
+        let mut graph = LinkGraph::new("https://example.org/");
+        graph.add_page(&page(
+            "https://example.org/",
+            &[
+                ("https://example.org/a", Anchor),
+                ("https://example.org/b#top", Anchor),
+                ("https://example.org/style.css", Resource),
+                ("mailto:me@example.org", Anchor),
+            ],
+        ));
+        graph.add_page(&page(
+            "https://example.org/a",
+            &[
+                ("https://example.org/b", Anchor),
+                ("https://example.org/a", Anchor),
+                ("https://other.org/", Anchor),
+            ],
+        ));
+        graph.add_page(&page(
+            "https://example.org/b",
+            &[("https://example.org/c", Anchor)],
+        ));
+        graph.add_page(&page("https://example.org/c", &[]));
+        // Found through a sitemap, not a link
+        graph.add_page(&page(
+            "https://example.org/lost",
+            &[("https://example.org/", Anchor)],
+        ));
+        graph
+    }
+
+    #[test]
+    fn test_metrics() {
+        let graph = graph();
+        assert_eq!(graph.node_count(), 6);
+        assert_eq!(graph.edges().len(), 7);
+        assert!(
+            !graph
+                .edges()
+                .iter()
+                .find(|e| e.target == "https://other.org/")
+                .unwrap()
+                .internal
+        );
+
+        let metrics = graph.metrics();
+        assert_eq!(metrics.edges, 6);
+        assert_eq!(metrics.orphans, vec!["https://example.org/lost"]);
+
+        let node = |url: &str| metrics.pages.iter().find(|p| p.url == url).unwrap();
+        let b = node("https://example.org/b");
+        assert_eq!((b.in_degree, b.out_degree, b.depth), (2, 1, Some(1)));
+        assert_eq!(node("https://example.org/c").depth, Some(2));
+        assert_eq!(node("https://example.org/lost").depth, None);
+        assert!(!node("https://other.org/").crawled);
+        assert!(!node("https://other.org/").internal);
+
+        let total: f64 = metrics.pages.iter().map(|p| p.pagerank).sum();
+        assert!((total - 1.0).abs() < 1e-6);
+        // Linked from both the start page and /a, /b outranks /a
+        assert!(b.pagerank > node("https://example.org/a").pagerank);
+        assert!(metrics.pages[0].pagerank >= metrics.pages[1].pagerank);
+
+        // Without a start URL, the first page is the start
+        let mut replayed = LinkGraph::new("archive.warc.gz");
+        replayed.add_page(&page(
+            "https://example.org/",
+            &[("https://example.org/a", LinkKind::Anchor)],
+        ));
+        let metrics = replayed.metrics();
+        assert_eq!(metrics.start_url.as_deref(), Some("https://example.org/"));
+        let a = metrics
+            .pages
+            .iter()
+            .find(|p| p.url == "https://example.org/a");
+        assert_eq!(a.unwrap().depth, Some(1));
+    }
+
+    #[test]
+    fn test_exports() {
+        let graph = graph();
+
+        let mut csv = Vec::new();
+        graph.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(csv.starts_with("source,target,text,kind,nofollow,internal\n"));
+        assert!(csv.contains(
+            "https://example.org/,https://example.org/b,to https://example.org/b#top,anchor,false,true\n"
+        ));
+
+        let mut dot = Vec::new();
+        graph.write_dot(&mut dot).unwrap();
+        let dot = String::from_utf8(dot).unwrap();
+        assert!(dot.starts_with("digraph links {"));
+        assert!(dot.contains("[label=\"https://other.org/\", style=dashed];"));
+
+        let mut graphml = Vec::new();
+        graph.write_graphml(&mut graphml).unwrap();
+        let graphml = String::from_utf8(graphml).unwrap();
+        assert_eq!(graphml.matches("<node ").count(), 6);
+        assert_eq!(graphml.matches("<edge ").count(), 7);
+        assert!(graphml.contains(r#"<data key="depth">2</data>"#));
+
+        let mut metrics = Vec::new();
+        graph.metrics().write_csv(&mut metrics).unwrap();
+        let metrics = String::from_utf8(metrics).unwrap();
+        let lost = metrics
+            .lines()
+            .find(|line| line.starts_with("https://example.org/lost,"))
+            .unwrap();
+        // Unreachable from the start URL, so no depth, and an orphan
+        assert!(lost.starts_with("https://example.org/lost,true,true,0,1,"));
+        assert!(lost.ends_with(",,true"));
+
+        assert_eq!(
+            GraphFormat::from_path(Path::new("links.GraphML")),
+            Some(GraphFormat::GraphMl)
+        );
+        assert_eq!(
+            GraphFormat::from_path(Path::new("links.gv")),
+            Some(GraphFormat::Dot)
+        );
+        assert_eq!(GraphFormat::from_path(Path::new("links.txt")), None);
+    }
+}
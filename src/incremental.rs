@@ -0,0 +1,323 @@
+use crate::crawlers::fetch::FetchedResource;
+use crate::results::PageData;
+use crate::utils::unix_timestamp;
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Error type for reading and writing crawl state
+pub type StateError = Box<dyn Error + Send + Sync>;
+
+/// Version of the state file format
+pub const STATE_VERSION: u32 = 1;
+
+/// Options for recrawling only the pages that changed since a previous crawl
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncrementalOptions {
+    /// File holding what was seen of each URL, read when the crawl starts and written when it ends
+    pub state_path: PathBuf,
+
+    /// Whether unchanged pages are still sent, with `metadata.unchanged` set and only
+    /// their URL, title, language and links filled in
+    #[serde(default)]
+    pub emit_unchanged: bool,
+}
+
+impl IncrementalOptions {
+    /// Creates options keeping the state in a file, skipping unchanged pages
+    pub fn new(state_path: impl Into<PathBuf>) -> Self {
+        Self {
+            state_path: state_path.into(),
+            emit_unchanged: false,
+        }
+    }
+}
+
+/// What a crawl recorded about a URL, to tell whether it changed by the next one
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UrlState {
+    /// `ETag` header of the response
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+
+    /// `Last-Modified` header of the response
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+
+    /// SHA-1 of the response body, or of the page's text when only headers were fetched, as hex
+    pub content_hash: String,
+
+    /// Title of the page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+
+    /// Language of the page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+
+    /// Links the crawler followed from the page, queued again when it's unchanged
+    #[serde(default)]
+    pub links: Vec<String>,
+
+    /// When the page was last fetched, in seconds since the Unix epoch
+    pub fetched_at: u64,
+}
+
+impl UrlState {
+    /// Records a page scraped after a response, with the links followed from it
+    ///
+    /// The response of a `HEAD` request only brings the validators, so the page's text
+    /// is hashed instead of its body.
+    pub fn new(response: &FetchedResource, page: &PageData, links: Vec<String>) -> Self {
+        let hashed = if response.body.is_empty() {
+            page.content.as_bytes()
+        } else {
+            &response.body
+        };
+        Self {
+            etag: response.header("etag").map(str::to_string),
+            last_modified: response.header("last-modified").map(str::to_string),
+            content_hash: content_hash(hashed),
+            title: page.title.clone(),
+            language: page.language.clone(),
+            links,
+            fetched_at: unix_timestamp(),
+        }
+    }
+
+    /// Headers making a request conditional on the page having changed
+    pub fn conditional_headers(&self) -> Vec<(String, String)> {
+        let mut headers = Vec::new();
+        if let Some(etag) = &self.etag {
+            headers.push(("if-none-match".to_string(), etag.clone()));
+        }
+        if let Some(last_modified) = &self.last_modified {
+            headers.push(("if-modified-since".to_string(), last_modified.clone()));
+        }
+        headers
+    }
+
+    /// Returns whether a response shows the page hasn't changed
+    ///
+    /// Either the server answered `304 Not Modified`, or it sent the same body again.
+    /// A `HEAD` response without a body can only show it with `304`.
+    pub fn is_unchanged(&self, response: &FetchedResource) -> bool {
+        response.status == 304
+            || (response.is_success()
+                && !response.body.is_empty()
+                && content_hash(&response.body) == self.content_hash)
+    }
+
+    /// Returns the state after a response showing the page is unchanged, with any new validators
+    pub fn revalidated(&self, response: &FetchedResource) -> Self {
+        Self {
+            etag: response
+                .header("etag")
+                .map(str::to_string)
+                .or_else(|| self.etag.clone()),
+            last_modified: response
+                .header("last-modified")
+                .map(str::to_string)
+                .or_else(|| self.last_modified.clone()),
+            fetched_at: unix_timestamp(),
+            ..self.clone()
+        }
+    }
+
+    /// Returns the page sent in place of an unchanged one
+    pub fn to_page(&self, url: &str) -> PageData {
+        let mut page = PageData::new(
+            url.to_string(),
+            self.title.clone(),
+            String::new(),
+            self.links.clone(),
+        );
+        page.language = self.language.clone();
+        page.metadata.unchanged = true;
+        page
+    }
+}
+
+/// What was seen of each URL by previous crawls
+///
+/// URLs missing from a crawl are kept, so a page that failed once is still
+/// recognised the next time it's unchanged.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CrawlState {
+    version: u32,
+    urls: BTreeMap<String, UrlState>,
+    /// URLs found unchanged by this crawl
+    #[serde(skip)]
+    unchanged: usize,
+}
+
+impl CrawlState {
+    /// Reads the state written by a previous crawl; a missing file is an empty state
+    pub fn load(path: &Path) -> Result<Self, StateError> {
+        let data = match fs::read(path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e.into()),
+        };
+        let state: Self = serde_json::from_slice(&data)?;
+        if state.version != STATE_VERSION {
+            return Err(format!(
+                "unsupported state version {} (expected {})",
+                state.version, STATE_VERSION
+            )
+            .into());
+        }
+        Ok(state)
+    }
+
+    /// Writes the state, replacing the file only once it's complete
+    pub fn save(&self, path: &Path) -> Result<(), StateError> {
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent)?;
+        }
+        let mut partial = path.as_os_str().to_owned();
+        partial.push(".partial");
+        let partial = PathBuf::from(partial);
+
+        let value = serde_json::json!({
+            "version": STATE_VERSION,
+            "urls": &self.urls,
+        });
+        fs::write(&partial, serde_json::to_vec(&value)?)?;
+        fs::rename(&partial, path)?;
+        Ok(())
+    }
+
+    /// Returns what was recorded about a URL
+    pub fn get(&self, url: &str) -> Option<&UrlState> {
+        self.urls.get(url)
+    }
+
+    /// Records a URL that was scraped
+    pub fn insert(&mut self, url: String, state: UrlState) {
+        self.urls.insert(url, state);
+    }
+
+    /// Records a URL found unchanged
+    pub fn insert_unchanged(&mut self, url: String, state: UrlState) {
+        self.unchanged += 1;
+        self.urls.insert(url, state);
+    }
+
+    /// Number of URLs recorded
+    pub fn len(&self) -> usize {
+        self.urls.len()
+    }
+
+    /// Returns whether no URLs are recorded
+    pub fn is_empty(&self) -> bool {
+        self.urls.is_empty()
+    }
+
+    /// Number of URLs found unchanged since this state was loaded
+    pub fn unchanged(&self) -> usize {
+        self.unchanged
+    }
+}
+
+/// Hashes a response body to tell whether it changed
+pub fn content_hash(body: &[u8]) -> String {
+    data_encoding::HEXLOWER.encode(&Sha1::digest(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(status: u16, headers: &[(&str, &str)], body: &str) -> FetchedResource {
+        FetchedResource {
+            url: "https://example.org/".to_string(),
+            status,
+            headers: headers
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_revalidation() {
+        let mut page = PageData::new(
+            "https://example.org/".to_string(),
+            Some("Home".to_string()),
+            "Hello".to_string(),
+            Vec::new(),
+        );
+        page.language = Some("en".to_string());
+        let first = response(200, &[("etag", "\"v1\"")], "<p>Hello</p>");
+        let state = UrlState::new(&first, &page, vec!["https://example.org/about".to_string()]);
+        assert_eq!(
+            state.conditional_headers(),
+            vec![("if-none-match".to_string(), "\"v1\"".to_string())]
+        );
+
+        assert!(state.is_unchanged(&response(304, &[], "")));
+        // Servers ignoring the validators are compared by content
+        assert!(state.is_unchanged(&response(200, &[], "<p>Hello</p>")));
+        assert!(!state.is_unchanged(&response(200, &[], "<p>Hello!</p>")));
+        assert!(!state.is_unchanged(&response(404, &[], "<p>Hello</p>")));
+        // A HEAD response only has its status to go by
+        assert!(!state.is_unchanged(&response(200, &[], "")));
+
+        // Without a body, the page's text is hashed
+        let head = UrlState::new(&response(200, &[], ""), &page, Vec::new());
+        assert_eq!(head.content_hash, content_hash(b"Hello"));
+
+        let revalidated = state.revalidated(&response(
+            304,
+            &[("last-modified", "Tue, 01 Oct 2024 00:00:00 GMT")],
+            "",
+        ));
+        assert_eq!(revalidated.etag.as_deref(), Some("\"v1\""));
+        assert_eq!(
+            revalidated.last_modified.as_deref(),
+            Some("Tue, 01 Oct 2024 00:00:00 GMT")
+        );
+        assert_eq!(revalidated.content_hash, state.content_hash);
+
+        let unchanged = revalidated.to_page("https://example.org/");
+        assert!(unchanged.metadata.unchanged);
+        assert_eq!(unchanged.title.as_deref(), Some("Home"));
+        assert_eq!(unchanged.language.as_deref(), Some("en"));
+        assert_eq!(unchanged.links, vec!["https://example.org/about"]);
+        assert!(unchanged.content.is_empty());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = std::env::temp_dir().join(format!("yield-page-state-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("state.json");
+        assert!(CrawlState::load(&path).unwrap().is_empty());
+
+        let mut state = CrawlState::default();
+        let url_state = UrlState {
+            content_hash: content_hash(b"body"),
+            links: vec!["https://example.org/a".to_string()],
+            ..UrlState::default()
+        };
+        state.insert("https://example.org/".to_string(), url_state.clone());
+        state.insert_unchanged("https://example.org/a".to_string(), UrlState::default());
+        assert_eq!(state.unchanged(), 1);
+        state.save(&path).unwrap();
+
+        let loaded = CrawlState::load(&path).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded.unchanged(), 0);
+        assert_eq!(loaded.get("https://example.org/"), Some(&url_state));
+
+        fs::write(&path, r#"{"version": 99, "urls": {}}"#).unwrap();
+        assert!(CrawlState::load(&path).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,15 +1,23 @@
 #![allow(clippy::too_many_arguments)]
 
 // Re-export modules
+pub mod archive;
+pub mod audit;
 pub mod config;
 pub mod crawlers;
+pub mod diff;
 pub mod filter;
+pub mod graph;
+pub mod incremental;
+pub mod linkcheck;
 pub mod parsers;
 pub mod results;
+pub mod sinks;
 pub mod utils;
+pub mod warc;
 
 // Re-export commonly used types for convenience
-pub use results::PageData;
+pub use results::{CrawlError, PageData};
 
 use std::time::Duration;
 use tokio::sync::mpsc;
@@ -25,6 +33,8 @@ pub enum UriType {
     Filesystem(String),
     /// Amazon S3 bucket
     S3(String, String), // Bucket name, region
+    /// WARC file or directory of WARC files, replayed through the parsers
+    Warc(String),
 }
 
 /// Main builder for page generation from different URI types
@@ -33,6 +43,10 @@ pub struct Pages {
     max_concurrency: usize,
     idle_timeout: Option<Duration>,
     total_timeout: Option<Duration>,
+    config: Option<config::CrawlerConfigType>,
+    parsers: parsers::ParserRegistry,
+    keep_source: bool,
+    incremental: Option<incremental::IncrementalOptions>,
 }
 
 impl Pages {
@@ -43,6 +57,10 @@ impl Pages {
             max_concurrency: 4, // Default concurrency
             idle_timeout: None,
             total_timeout: None,
+            config: None,
+            parsers: parsers::ParserRegistry::new(),
+            keep_source: false,
+            incremental: None,
         }
     }
 
@@ -80,7 +98,39 @@ impl Pages {
             config::CrawlerConfigType::S3(_) => {
                 // Set S3-specific options
             }
+            config::CrawlerConfigType::Warc(_) => {
+                // Replay reads files in order, so there's nothing to set
+            }
         }
+        self.config = Some(config);
+        self
+    }
+
+    /// Register a parser for resources the matcher accepts, taking precedence over built-in parsers
+    pub fn with_parser(
+        mut self,
+        matcher: parsers::ParserMatcher,
+        parser: impl parsers::ContentParser + 'static,
+    ) -> Self {
+        self.parsers.register(matcher, parser);
+        self
+    }
+
+    /// Set the registry of user-defined parsers, replacing any registered so far
+    pub fn with_parser_registry(mut self, registry: parsers::ParserRegistry) -> Self {
+        self.parsers = registry;
+        self
+    }
+
+    /// Keep the source each page was parsed from in `PageData::source`, for every URL
+    pub fn with_keep_source(mut self, keep_source: bool) -> Self {
+        self.keep_source = keep_source;
+        self
+    }
+
+    /// Only scrape web pages that changed since the crawl that last used the same state file
+    pub fn with_incremental(mut self, options: incremental::IncrementalOptions) -> Self {
+        self.incremental = Some(options);
         self
     }
 
@@ -101,21 +151,59 @@ impl Pages {
 
     /// Start the crawler and get a receiver for pages
     pub async fn generate(self) -> Result<mpsc::Receiver<PageData>, Box<dyn std::error::Error>> {
+        self.start(None).await
+    }
+
+    /// Start the crawler and get receivers for pages and for the URLs that failed
+    ///
+    /// Both channels close when the crawl is over.
+    pub async fn generate_with_errors(
+        self,
+    ) -> Result<
+        (
+            mpsc::Receiver<PageData>,
+            mpsc::UnboundedReceiver<CrawlError>,
+        ),
+        Box<dyn std::error::Error>,
+    > {
+        let (error_tx, error_rx) = mpsc::unbounded_channel();
+        let receiver = self.start(Some(error_tx)).await?;
+        Ok((receiver, error_rx))
+    }
+
+    async fn start(
+        self,
+        error_tx: Option<mpsc::UnboundedSender<CrawlError>>,
+    ) -> Result<mpsc::Receiver<PageData>, Box<dyn std::error::Error>> {
         match self.uri_type {
             UriType::Web(url_str) => {
-                // Create web crawler configuration
-                let mut web_config = config::WebCrawlerConfig::new(&url_str);
+                // Create web crawler configuration, starting from the provided one if any
+                let mut web_config = match self.config {
+                    Some(config::CrawlerConfigType::Web(web_config)) => web_config,
+                    _ => config::WebCrawlerConfig::new(&url_str),
+                };
+                web_config.start_url = url_str;
                 web_config.max_concurrency = self.max_concurrency;
+                if self.keep_source {
+                    web_config.parser_options.keep_source = true;
+                    for parser_override in &mut web_config.parser_overrides {
+                        parser_override.options.keep_source = true;
+                    }
+                }
+                if self.incremental.is_some() {
+                    web_config.incremental = self.incremental;
+                }
 
                 // Override the WebDriver URL with an environment variable if provided
-                if let Ok(webdriver_url) = std::env::var("WEBDRIVER_URL") {
-                    if !webdriver_url.is_empty() {
-                        web_config.webdriver_url = webdriver_url;
-                    }
+                if let Ok(webdriver_url) = std::env::var("WEBDRIVER_URL")
+                    && !webdriver_url.is_empty()
+                {
+                    web_config.webdriver_url = webdriver_url;
                 }
 
                 // Start the web crawler
-                let receiver = crawlers::web::start(&web_config).await;
+                let receiver =
+                    crawlers::web::start_with_errors(&web_config, self.parsers, error_tx).await;
                 Ok(receiver)
             }
             UriType::Git(_) => {
@@ -130,6 +218,24 @@ impl Pages {
                 // Placeholder for S3 implementation
                 unimplemented!("S3 crawler not yet implemented")
             }
+            UriType::Warc(path) => {
+                // Replay the archive, starting from the provided configuration if any
+                let mut warc_config = match self.config {
+                    Some(config::CrawlerConfigType::Warc(warc_config)) => warc_config,
+                    _ => config::WarcReplayConfig::new(&path),
+                };
+                warc_config.path = path;
+                if self.keep_source {
+                    warc_config.parser_options.keep_source = true;
+                    for parser_override in &mut warc_config.parser_overrides {
+                        parser_override.options.keep_source = true;
+                    }
+                }
+
+                let receiver =
+                    crawlers::warc::start_with_errors(&warc_config, self.parsers, error_tx)?;
+                Ok(receiver)
+            }
         }
     }
 }
@@ -0,0 +1,756 @@
+use crate::archive;
+use crate::crawlers::fetch::{self, FetchedResource, Method};
+use crate::results::{CrawlError, PageData};
+use crate::utils::without_fragment;
+use fantoccini::ClientBuilder;
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::error::Error;
+use std::future::Future;
+use std::io::Write;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use url::Url;
+
+/// Error type for probing a URL
+pub type ProbeError = Box<dyn Error + Send + Sync>;
+
+/// Options for checking links
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkCheckOptions {
+    /// Number of URLs probed at once
+    #[serde(default = "default_concurrency")]
+    pub concurrency: usize,
+
+    /// Redirects followed before a link counts as broken
+    #[serde(default = "default_max_redirects")]
+    pub max_redirects: usize,
+
+    /// Whether `#fragment` links are checked against the IDs on the target page
+    #[serde(default = "default_check_anchors")]
+    pub check_anchors: bool,
+
+    /// Seconds to wait for each request before the URL counts as unreachable
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_concurrency() -> usize {
+    8
+}
+
+fn default_max_redirects() -> usize {
+    10
+}
+
+fn default_check_anchors() -> bool {
+    true
+}
+
+fn default_timeout_secs() -> u64 {
+    30
+}
+
+impl Default for LinkCheckOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: default_concurrency(),
+            max_redirects: default_max_redirects(),
+            check_anchors: default_check_anchors(),
+            timeout_secs: default_timeout_secs(),
+        }
+    }
+}
+
+/// One hop of a redirect chain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Redirect {
+    /// URL that answered with the redirect
+    pub url: String,
+    /// Redirect status code (301, 302, ...)
+    pub status: u16,
+}
+
+/// Outcome of checking a link
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "status", content = "detail")]
+pub enum LinkStatus {
+    /// The link works
+    Ok,
+    /// The link works, but only after redirects
+    Redirected,
+    /// The server answered with an error status
+    Broken,
+    /// The request failed, or redirected too many times
+    Unreachable(String),
+    /// The page exists but has no element with the fragment's ID
+    MissingAnchor(String),
+}
+
+impl LinkStatus {
+    /// Returns whether the link counts as a failure
+    pub fn is_failure(&self) -> bool {
+        matches!(
+            self,
+            LinkStatus::Broken | LinkStatus::Unreachable(_) | LinkStatus::MissingAnchor(_)
+        )
+    }
+}
+
+/// A checked link on a page
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LinkResult {
+    /// URL the link points to, including any fragment
+    pub url: String,
+    /// Anchor text or alt text of the link
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// How the link was found (`anchor`, `resource`, `image`, ...)
+    pub kind: String,
+    /// Outcome of the check
+    #[serde(flatten)]
+    pub status: LinkStatus,
+    /// Final HTTP status code, if the server answered
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<u16>,
+    /// Redirects followed, in order
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub redirects: Vec<Redirect>,
+    /// URL reached after following redirects
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub final_url: Option<String>,
+}
+
+/// The links of one page that failed or redirected
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PageLinkReport {
+    /// URL of the page the links are on
+    pub source: String,
+    /// Links that failed or redirected, in page order
+    pub links: Vec<LinkResult>,
+}
+
+/// Results of checking every link found in a crawl
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LinkReport {
+    /// Number of pages whose links were checked
+    pub pages_checked: usize,
+    /// Number of links checked
+    pub links_checked: usize,
+    /// Number of distinct URLs probed
+    pub urls_probed: usize,
+    /// Number of links that failed
+    pub failures: usize,
+    /// Number of links that work only after redirects
+    pub redirects: usize,
+    /// Pages with failed or redirected links, in URL order
+    pub pages: Vec<PageLinkReport>,
+    /// URLs the crawl failed on, whose links couldn't be checked
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub crawl_errors: Vec<CrawlError>,
+}
+
+impl LinkReport {
+    /// Returns whether any link failed, or any page failed to crawl
+    pub fn has_failures(&self) -> bool {
+        self.failures > 0 || !self.crawl_errors.is_empty()
+    }
+
+    /// Writes a readable report, grouped by page, leaving out redirects unless asked
+    pub fn write_text(&self, mut writer: impl Write, show_redirects: bool) -> std::io::Result<()> {
+        for page in &self.pages {
+            let links: Vec<&LinkResult> = page
+                .links
+                .iter()
+                .filter(|link| show_redirects || link.status.is_failure())
+                .collect();
+            if links.is_empty() {
+                continue;
+            }
+
+            writeln!(writer, "{}", page.source)?;
+            for link in links {
+                let outcome = match &link.status {
+                    LinkStatus::Ok => "ok".to_string(),
+                    LinkStatus::Redirected => "redirected".to_string(),
+                    LinkStatus::Broken => format!("broken ({})", link.code.unwrap_or_default()),
+                    LinkStatus::Unreachable(error) => format!("unreachable ({})", error),
+                    LinkStatus::MissingAnchor(fragment) => format!("missing anchor #{}", fragment),
+                };
+                writeln!(writer, "  {} {} [{}]", outcome, link.url, link.kind)?;
+                for redirect in &link.redirects {
+                    writeln!(writer, "    {} {}", redirect.status, redirect.url)?;
+                }
+                if !link.redirects.is_empty()
+                    && let Some(final_url) = &link.final_url
+                {
+                    writeln!(writer, "    -> {}", final_url)?;
+                }
+            }
+        }
+
+        if !self.crawl_errors.is_empty() {
+            writeln!(writer, "Failed to crawl")?;
+            for error in &self.crawl_errors {
+                writeln!(writer, "  {} ({})", error.url, error.message)?;
+            }
+        }
+
+        write!(
+            writer,
+            "{} links on {} pages checked ({} URLs): {} failed, {} redirected",
+            self.links_checked, self.pages_checked, self.urls_probed, self.failures, self.redirects
+        )?;
+        if !self.crawl_errors.is_empty() {
+            write!(
+                writer,
+                ", {} pages failed to crawl",
+                self.crawl_errors.len()
+            )?;
+        }
+        writeln!(writer)
+    }
+}
+
+/// A link found on a crawled page, waiting to be checked
+#[derive(Debug, Clone)]
+struct FoundLink {
+    url: String,
+    text: Option<String>,
+    kind: String,
+}
+
+/// What probing a URL (without fragment) found
+#[derive(Debug, Clone, Default)]
+struct Probe {
+    code: Option<u16>,
+    redirects: Vec<Redirect>,
+    final_url: Option<String>,
+    error: Option<String>,
+    /// IDs on the page, if it was fetched with its body and is HTML
+    anchors: Option<HashSet<String>>,
+}
+
+/// Checks the links found on crawled pages, including external and asset links
+///
+/// Pages are added as they're crawled; [`LinkChecker::check`] then probes each
+/// distinct target once, with `HEAD` and falling back to `GET` for servers that
+/// reject it, following redirects by hand to record the chain. Targets are not
+/// parsed, except for reading the IDs of HTML pages that `#fragment` links point
+/// into. Crawled pages that kept their source don't need fetching for that.
+#[derive(Debug, Default)]
+pub struct LinkChecker {
+    options: LinkCheckOptions,
+    /// Links by source page, in page order
+    pages: BTreeMap<String, Vec<FoundLink>>,
+    /// IDs on crawled pages, by URL without fragment
+    anchors: HashMap<String, HashSet<String>>,
+    /// URLs the crawl failed on
+    crawl_errors: Vec<CrawlError>,
+}
+
+impl LinkChecker {
+    /// Creates a checker with the given options
+    pub fn new(options: LinkCheckOptions) -> Self {
+        Self {
+            options,
+            ..Self::default()
+        }
+    }
+
+    /// Records the links of a crawled page, and its IDs if it kept its HTML source
+    pub fn add_page(&mut self, page: &PageData) {
+        if let Some(source) = &page.source
+            && page
+                .metadata
+                .content_type
+                .as_deref()
+                .is_none_or(|ct| ct.contains("html"))
+        {
+            self.anchors.insert(
+                without_fragment(&page.url),
+                anchor_ids(&String::from_utf8_lossy(source)),
+            );
+        }
+
+        let mut links: Vec<FoundLink> = page
+            .outgoing_links()
+            .map(|(url, link)| FoundLink {
+                url: url.to_string(),
+                text: link.and_then(|link| link.text.clone()),
+                kind: link.map_or_else(|| "link".to_string(), |link| serde_name(link.kind)),
+            })
+            .collect();
+        for item in &page.media {
+            let kind = serde_name(item.kind);
+            let urls = item.url.iter().chain(item.sources.iter().map(|s| &s.url));
+            for url in urls {
+                links.push(FoundLink {
+                    url: url.clone(),
+                    text: item.alt.clone().filter(|alt| !alt.is_empty()),
+                    kind: kind.clone(),
+                });
+            }
+        }
+
+        // Only web links can be checked; `mailto:` and the like are left alone, and
+        // files inside an archive can't be requested on their own
+        links.retain(|link| {
+            (link.url.starts_with("http://") || link.url.starts_with("https://"))
+                && !link.url.contains(archive::MEMBER_SEPARATOR)
+        });
+        self.pages
+            .entry(page.url.clone())
+            .or_default()
+            .extend(links);
+    }
+
+    /// Records a URL the crawl failed on, which counts as a failure
+    pub fn add_error(&mut self, error: &CrawlError) {
+        self.crawl_errors.push(error.clone());
+    }
+
+    /// Probes every link found so far with `fetch`, returning the report
+    ///
+    /// `fetch` issues one request without following redirects, as
+    /// [`crate::crawlers::fetch::fetch`] does.
+    pub async fn check<F, Fut>(self, fetch: F) -> LinkReport
+    where
+        F: Fn(Method, String) -> Fut + Clone + Send + Sync + 'static,
+        Fut: Future<Output = Result<FetchedResource, ProbeError>> + Send,
+    {
+        // Targets linked with a fragment need their body, unless already crawled
+        let mut targets: HashMap<String, bool> = HashMap::new();
+        for link in self.pages.values().flatten() {
+            let needs_body = self.options.check_anchors
+                && checkable_fragment(&link.url).is_some()
+                && !self.anchors.contains_key(&without_fragment(&link.url));
+            *targets.entry(without_fragment(&link.url)).or_default() |= needs_body;
+        }
+
+        let semaphore = Arc::new(Semaphore::new(self.options.concurrency.max(1)));
+        let mut tasks = JoinSet::new();
+        for (url, needs_body) in targets {
+            let fetch = fetch.clone();
+            let semaphore = semaphore.clone();
+            let max_redirects = self.options.max_redirects;
+            let timeout = Duration::from_secs(self.options.timeout_secs);
+            tasks.spawn(async move {
+                let _permit = semaphore.acquire_owned().await;
+                let fetch = |method, url| {
+                    let request = fetch(method, url);
+                    async move {
+                        match tokio::time::timeout(timeout, request).await {
+                            Ok(response) => response,
+                            Err(_) => Err("timed out".into()),
+                        }
+                    }
+                };
+                let probe = probe(&url, needs_body, max_redirects, fetch).await;
+                (url, probe)
+            });
+        }
+        let mut probes = HashMap::new();
+        while let Some(result) = tasks.join_next().await {
+            match result {
+                Ok((url, probe)) => {
+                    probes.insert(url, probe);
+                }
+                Err(e) => ::log::error!("Link check task failed: {}", e),
+            }
+        }
+
+        self.report(&probes)
+    }
+
+    /// Probes every link found so far through a WebDriver server's HTTP connection
+    pub async fn check_with_webdriver(self, webdriver_url: &str) -> Result<LinkReport, ProbeError> {
+        let client = ClientBuilder::native().connect(webdriver_url).await?;
+        let fetch_client = client.clone();
+        let report = self
+            .check(move |method, url: String| {
+                let client = fetch_client.clone();
+                async move { fetch::fetch(&client, method, &url).await }
+            })
+            .await;
+        if let Err(e) = client.close().await {
+            ::log::debug!("Failed to close WebDriver session: {}", e);
+        }
+        Ok(report)
+    }
+
+    /// Matches each link with the probe of its target
+    fn report(&self, probes: &HashMap<String, Probe>) -> LinkReport {
+        let mut report = LinkReport {
+            pages_checked: self.pages.len(),
+            links_checked: 0,
+            urls_probed: probes.len(),
+            failures: 0,
+            redirects: 0,
+            pages: Vec::new(),
+            crawl_errors: self.crawl_errors.clone(),
+        };
+
+        for (source, links) in &self.pages {
+            let mut results = Vec::new();
+            for link in links {
+                report.links_checked += 1;
+                let target = without_fragment(&link.url);
+                let Some(probe) = probes.get(&target) else {
+                    continue;
+                };
+
+                let status = if let Some(error) = &probe.error {
+                    LinkStatus::Unreachable(error.clone())
+                } else if probe.code.is_none_or(|code| code >= 400) {
+                    LinkStatus::Broken
+                } else if let Some(fragment) = checkable_fragment(&link.url)
+                    && self.options.check_anchors
+                    && let Some(ids) = self.anchors.get(&target).or(probe.anchors.as_ref())
+                    && !has_anchor(ids, &fragment)
+                {
+                    LinkStatus::MissingAnchor(fragment)
+                } else if !probe.redirects.is_empty() {
+                    LinkStatus::Redirected
+                } else {
+                    LinkStatus::Ok
+                };
+
+                match &status {
+                    LinkStatus::Ok => continue,
+                    LinkStatus::Redirected => report.redirects += 1,
+                    _ => report.failures += 1,
+                }
+                results.push(LinkResult {
+                    url: link.url.clone(),
+                    text: link.text.clone(),
+                    kind: link.kind.clone(),
+                    status,
+                    code: probe.code,
+                    redirects: probe.redirects.clone(),
+                    final_url: probe.final_url.clone(),
+                });
+            }
+            if !results.is_empty() {
+                report.pages.push(PageLinkReport {
+                    source: source.clone(),
+                    links: results,
+                });
+            }
+        }
+        report
+    }
+}
+
+/// Requests a URL, following redirects, with `HEAD` unless the body is needed
+async fn probe<F, Fut>(url: &str, needs_body: bool, max_redirects: usize, fetch: F) -> Probe
+where
+    F: Fn(Method, String) -> Fut,
+    Fut: Future<Output = Result<FetchedResource, ProbeError>>,
+{
+    let mut probe = Probe::default();
+    let mut current = url.to_string();
+
+    loop {
+        let response = if needs_body {
+            fetch(Method::GET, current.clone()).await
+        } else {
+            // Some servers reject or mishandle HEAD, so errors are retried with GET
+            match fetch(Method::HEAD, current.clone()).await {
+                Ok(response) if response.status < 400 => Ok(response),
+                _ => fetch(Method::GET, current.clone()).await,
+            }
+        };
+        let response = match response {
+            Ok(response) => response,
+            Err(e) => {
+                probe.error = Some(e.to_string());
+                return probe;
+            }
+        };
+
+        let location = response.header("location").map(|l| l.to_string());
+        if (300..400).contains(&response.status)
+            && let Some(location) = location
+        {
+            if probe.redirects.len() >= max_redirects {
+                probe.error = Some(format!("more than {} redirects", max_redirects));
+                return probe;
+            }
+            let next = match Url::parse(&current).and_then(|base| base.join(&location)) {
+                Ok(next) => next.to_string(),
+                Err(e) => {
+                    probe.error = Some(format!("invalid redirect to '{}': {}", location, e));
+                    return probe;
+                }
+            };
+            probe.redirects.push(Redirect {
+                url: current,
+                status: response.status,
+            });
+            current = next;
+            continue;
+        }
+
+        probe.code = Some(response.status);
+        if needs_body
+            && response.is_success()
+            && response
+                .content_type()
+                .is_some_and(|ct| ct.contains("html"))
+        {
+            probe.anchors = Some(anchor_ids(&String::from_utf8_lossy(&response.body)));
+        }
+        if !probe.redirects.is_empty() {
+            probe.final_url = Some(current);
+        }
+        return probe;
+    }
+}
+
+/// Collects the IDs a fragment can point at: `id` attributes and `<a name>`
+fn anchor_ids(html: &str) -> HashSet<String> {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse("[id], a[name]").unwrap();
+    document
+        .select(&selector)
+        .flat_map(|element| {
+            let value = element.value();
+            [
+                value.attr("id"),
+                value.attr("name").filter(|_| value.name() == "a"),
+            ]
+        })
+        .flatten()
+        .map(|id| id.to_string())
+        .collect()
+}
+
+/// Returns the fragment of a URL if it should name an element on the page
+///
+/// `#` and `#top` always work, and fragments used for client-side routing
+/// (`#!/path`, `#/path`) or text fragments (`#:~:text=`) aren't element IDs.
+fn checkable_fragment(url: &str) -> Option<String> {
+    let fragment = Url::parse(url).ok()?.fragment()?.to_string();
+    let skip = fragment.is_empty()
+        || fragment.eq_ignore_ascii_case("top")
+        || fragment.starts_with('!')
+        || fragment.starts_with('/')
+        || fragment.starts_with(":~:");
+    (!skip).then_some(fragment)
+}
+
+/// Returns whether a fragment names one of the IDs, as written or percent-decoded
+fn has_anchor(ids: &HashSet<String>, fragment: &str) -> bool {
+    ids.contains(fragment) || ids.contains(&percent_decode(fragment))
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && let Some(byte) = text
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            decoded.push(byte);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Name of a unit enum variant as it appears in JSON output
+fn serde_name(value: impl Serialize) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|value| value.as_str().map(|name| name.to_string()))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::{Link, LinkKind};
+
+    fn response(url: &str, status: u16, headers: &[(&str, &str)], body: &str) -> FetchedResource {
+        FetchedResource {
+            url: url.to_string(),
+            status,
+            headers: headers
+                .iter()
+                .map(|(n, v)| (n.to_string(), v.to_string()))
+                .collect(),
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    /// Serves canned responses; HEAD to /no-head fails as some servers do
+    async fn fake_fetch(method: Method, url: String) -> Result<FetchedResource, ProbeError> {
+        let html = [("content-type", "text/html")];
+        Ok(match url.as_str() {
+            "https://example.org/" => response(&url, 200, &html, ""),
+            "https://example.org/guide" => {
+                response(&url, 200, &html, "<h2 id=\"setup\">Setup</h2>")
+            }
+            "https://example.org/old" => response(&url, 301, &[("location", "/guide")], ""),
+            "https://example.org/gone" => response(&url, 404, &[], ""),
+            "https://example.org/loop" => response(&url, 302, &[("location", "/loop")], ""),
+            "https://example.org/no-head" if method == Method::HEAD => response(&url, 405, &[], ""),
+            "https://example.org/no-head" => response(&url, 200, &[], ""),
+            "https://example.org/logo.png" => response(&url, 200, &[], ""),
+            _ => return Err("connection refused".into()),
+        })
+    }
+
+    fn page(url: &str, source: &str, links: &[&str]) -> PageData {
+        let mut page = PageData::new(url.to_string(), None, String::new(), Vec::new());
+        page.link_details = links
+            .iter()
+            .map(|l| Link {
+                url: l.to_string(),
+                href: l.to_string(),
+                kind: LinkKind::Anchor,
+                text: Some("link".to_string()),
+                rel: Vec::new(),
+                nofollow: false,
+                element: "a".to_string(),
+                hreflang: None,
+            })
+            .collect();
+        page.metadata.content_type = Some("text/html".to_string());
+        page.source = Some(source.as_bytes().to_vec());
+        page
+    }
+
+    #[tokio::test]
+    async fn test_check() {
+        let mut checker = LinkChecker::new(LinkCheckOptions::default());
+        checker.add_page(&page(
+            "https://example.org/",
+            r#"<a name="intro"></a><div id="faq"></div>"#,
+            &[
+                "https://example.org/guide#setup",
+                "https://example.org/guide#install",
+                "https://example.org/old",
+                "https://example.org/gone",
+                "https://example.org/loop",
+                "https://example.org/no-head",
+                "https://example.org/#faq",
+                "https://example.org/#intro",
+                "https://example.org/#missing",
+                "https://down.example.com/",
+                "mailto:team@example.org",
+            ],
+        ));
+        checker.add_page(&page(
+            "https://example.org/about",
+            "",
+            &["https://example.org/logo.png"],
+        ));
+
+        let report = checker.check(fake_fetch).await;
+        assert_eq!(report.pages_checked, 2);
+        assert_eq!(report.links_checked, 11);
+        assert_eq!(report.failures, 5);
+        assert_eq!(report.redirects, 1);
+        assert!(report.has_failures());
+
+        // The about page has no problems, so only the start page is reported
+        assert_eq!(report.pages.len(), 1);
+        let status = |url: &str| {
+            report.pages[0]
+                .links
+                .iter()
+                .find(|link| link.url == url)
+                .map(|link| link.status.clone())
+        };
+        assert_eq!(status("https://example.org/guide#setup"), None);
+        assert_eq!(
+            status("https://example.org/guide#install"),
+            Some(LinkStatus::MissingAnchor("install".to_string()))
+        );
+        assert_eq!(
+            status("https://example.org/old"),
+            Some(LinkStatus::Redirected)
+        );
+        assert_eq!(status("https://example.org/gone"), Some(LinkStatus::Broken));
+        assert_eq!(
+            status("https://example.org/loop"),
+            Some(LinkStatus::Unreachable(
+                "more than 10 redirects".to_string()
+            ))
+        );
+        assert_eq!(status("https://example.org/no-head"), None);
+        assert_eq!(status("https://example.org/#intro"), None);
+        assert_eq!(
+            status("https://example.org/#missing"),
+            Some(LinkStatus::MissingAnchor("missing".to_string()))
+        );
+        assert_eq!(
+            status("https://down.example.com/"),
+            Some(LinkStatus::Unreachable("connection refused".to_string()))
+        );
+
+        let old = report.pages[0]
+            .links
+            .iter()
+            .find(|link| link.url == "https://example.org/old")
+            .unwrap();
+        assert_eq!(old.redirects[0].status, 301);
+        assert_eq!(old.final_url.as_deref(), Some("https://example.org/guide"));
+
+        let mut text = Vec::new();
+        report.write_text(&mut text, false).unwrap();
+        let text = String::from_utf8(text).unwrap();
+        assert!(text.starts_with("https://example.org/\n"));
+        assert!(text.contains("  broken (404) https://example.org/gone [anchor]\n"));
+        assert!(!text.contains("redirected https://example.org/old"));
+        assert!(text.ends_with("11 links on 2 pages checked (8 URLs): 5 failed, 1 redirected\n"));
+    }
+
+    #[tokio::test]
+    async fn test_archive_links_and_crawl_errors() {
+        let mut checker = LinkChecker::new(LinkCheckOptions::default());
+        checker.add_page(&page(
+            "https://example.org/bundle.zip!/index.html",
+            "",
+            &[
+                "https://example.org/bundle.zip!/logo.png",
+                "https://example.org/",
+            ],
+        ));
+
+        checker.add_error(&CrawlError::new("https://example.org/broken", "HTTP 500"));
+        let report = checker.check(fake_fetch).await;
+        assert_eq!((report.links_checked, report.failures), (1, 0));
+        assert!(report.has_failures());
+
+        let mut text = Vec::new();
+        report.write_text(&mut text, false).unwrap();
+        let text = String::from_utf8(text).unwrap();
+        assert!(text.contains("Failed to crawl\n  https://example.org/broken (HTTP 500)\n"));
+        assert!(text.ends_with("redirected, 1 pages failed to crawl\n"));
+    }
+
+    #[test]
+    fn test_fragments() {
+        assert_eq!(
+            checkable_fragment("https://example.org/#a%20b").as_deref(),
+            Some("a%20b")
+        );
+        assert_eq!(checkable_fragment("https://example.org/#top"), None);
+        assert_eq!(checkable_fragment("https://example.org/#!/route"), None);
+        assert_eq!(checkable_fragment("https://example.org/"), None);
+        let ids = HashSet::from(["a b".to_string()]);
+        assert!(has_anchor(&ids, "a%20b"));
+    }
+}
@@ -1,9 +1,22 @@
 use clap::Parser;
 use yield_page::Pages;
+use yield_page::audit::{AuditOptions, Auditor};
+use yield_page::config::WebCrawlerConfig;
+use yield_page::diff::{CrawlSnapshot, DiffOptions, diff_crawls};
+use yield_page::graph::{GraphFormat, LinkGraph};
+use yield_page::incremental::IncrementalOptions;
+use yield_page::linkcheck::{LinkCheckOptions, LinkChecker};
 use yield_page::results::PageData;
+use yield_page::sinks::{
+    CrawlSummary, JsonLinesSink, MirrorSink, PageSink, SearchIndex, SearchIndexSink, SinkError,
+    SqliteSink,
+};
 
 mod args;
-use args::{Args, convert_uri_type};
+use args::{
+    Args, AuditArgs, CheckLinksArgs, Command, CrawlArgs, DiffArgs, OutputFormatArg, SearchArgs,
+    convert_compression, convert_mirror_files, convert_uri_type, output_format,
+};
 
 #[tokio::main]
 async fn main() {
@@ -13,57 +26,427 @@ async fn main() {
     // Parse command-line arguments
     let args = Args::parse();
 
-    ::log::info!("Starting crawler for URI: {}", args.uri);
+    match &args.command {
+        Some(Command::Search(search)) => {
+            if let Err(e) = run_search(search) {
+                ::log::error!("Search failed: {}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Command::CheckLinks(check)) => std::process::exit(run_check_links(check).await),
+        Some(Command::Diff(diff)) => {
+            if let Err(e) = run_diff(diff) {
+                ::log::error!("Diff failed: {}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Command::Audit(audit)) => {
+            if let Err(e) = run_audit(audit).await {
+                ::log::error!("Audit failed: {}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        None => {}
+    }
+
+    // Without a subcommand, clap requires the URI
+    let Some(uri) = args.uri.as_deref() else {
+        return;
+    };
+
+    // Check the graph exports before crawling, so a bad path fails fast
+    for path in &args.graph {
+        if GraphFormat::from_path(path).is_none() {
+            ::log::error!(
+                "Unknown graph format for {} (use .graphml, .dot or .csv)",
+                path.display()
+            );
+            std::process::exit(1);
+        }
+    }
+
+    // Open the output before crawling, so a bad path or field list fails fast
+    let mut sink = match create_sink(&args, uri) {
+        Ok(sink) => sink,
+        Err(e) => {
+            ::log::error!("Failed to open output: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // Record the link graph only when it's exported
+    let mut graph =
+        (!args.graph.is_empty() || args.graph_metrics.is_some()).then(|| LinkGraph::new(uri));
+
+    // Create a Pages builder with the specified parameters
+    let format = output_format(args.format, args.output.as_deref());
+    let mut pages = crawl_pages(&args.crawl, uri)
+        // A mirror of the raw pages needs the bytes each page was parsed from
+        .with_keep_source(
+            format == OutputFormatArg::Mirror && convert_mirror_files(&args.mirror_files).raw,
+        );
+    // Outputs other than JSON Lines keep their earlier copy of unchanged pages, so they
+    // always get them
+    let emit_unchanged = args.emit_unchanged || format != OutputFormatArg::Jsonl;
+    if let Some(state_path) = &args.incremental {
+        pages = pages.with_incremental(IncrementalOptions {
+            emit_unchanged,
+            ..IncrementalOptions::new(state_path)
+        });
+    }
+
+    let mut summary = CrawlSummary::start(uri);
+    summary.omits_unchanged = args.incremental.is_some() && !emit_unchanged;
+
+    // Start the crawler and get receivers for pages and failed URLs
+    let (mut rx, mut error_rx) = match pages.generate_with_errors().await {
+        Ok(receivers) => receivers,
+        Err(e) => {
+            ::log::error!("Failed to start crawler: {}", e);
+            exit_with_error(sink, &mut summary);
+        }
+    };
+
+    // Process pages as they come in
+    ::log::info!("Started processing pages");
+
+    loop {
+        tokio::select! {
+            page = rx.recv() => {
+                let Some(page) = page else { break };
+                summary.record(&page);
+                process_page(&page, summary.pages);
+                if let Some(graph) = &mut graph {
+                    graph.add_page(&page);
+                }
+                if let Err(e) = sink.write_page(&page) {
+                    ::log::error!("Failed to write page {}: {}", page.url, e);
+                    exit_with_error(sink, &mut summary);
+                }
+            }
+            Some(error) = error_rx.recv() => {
+                summary.record_error();
+                if let Err(e) = sink.write_error(&error) {
+                    ::log::error!("Failed to write error for {}: {}", error.url, e);
+                }
+            }
+        }
+    }
+
+    // Errors reported after the last page
+    while let Some(error) = error_rx.recv().await {
+        summary.record_error();
+        if let Err(e) = sink.write_error(&error) {
+            ::log::error!("Failed to write error for {}: {}", error.url, e);
+        }
+    }
+
+    summary.stop();
+    if let Some(graph) = &graph {
+        write_graph(graph, &args);
+    }
+    if let Err(e) = sink.finish(&summary) {
+        ::log::error!("Failed to finish output: {}", e);
+        std::process::exit(1);
+    }
+    ::log::info!(
+        "Crawling complete - processed {} pages in {:.2} seconds",
+        summary.pages,
+        summary.duration_secs
+    );
+}
+
+/// Completes the output with what was written so far, so compressed files stay
+/// readable, then exits with an error
+fn exit_with_error(sink: Box<dyn PageSink>, summary: &mut CrawlSummary) -> ! {
+    summary.stop();
+    if let Err(e) = sink.finish(summary) {
+        ::log::error!("Failed to finish output: {}", e);
+    }
+    std::process::exit(1);
+}
+
+/// Creates the sink for the output file, or stdout
+fn create_sink(args: &Args, uri: &str) -> Result<Box<dyn PageSink>, SinkError> {
+    let format = output_format(args.format, args.output.as_deref());
+    if format != OutputFormatArg::Jsonl && (args.compression.is_some() || !args.fields.is_empty()) {
+        return Err("--compression and --fields only apply to JSON Lines output".into());
+    }
+
+    match format {
+        OutputFormatArg::Sqlite => {
+            let path = args
+                .output
+                .as_deref()
+                .ok_or("SQLite output needs --output")?;
+            return Ok(Box::new(SqliteSink::open(path, uri)?));
+        }
+        OutputFormatArg::Mirror => {
+            let path = args
+                .output
+                .as_deref()
+                .ok_or("Mirror output needs --output")?;
+            return Ok(Box::new(
+                MirrorSink::new(path)?
+                    .with_files(convert_mirror_files(&args.mirror_files))
+                    .with_link_rewriting(!args.no_rewrite_links),
+            ));
+        }
+        OutputFormatArg::Search => {
+            let path = args
+                .output
+                .as_deref()
+                .ok_or("Search index output needs --output")?;
+            return Ok(Box::new(SearchIndexSink::open(path)?));
+        }
+        OutputFormatArg::Jsonl => {}
+    }
+
+    let compression = convert_compression(args.compression, args.output.as_deref());
+    let sink = match &args.output {
+        Some(path) => JsonLinesSink::create(path, compression)?,
+        None => JsonLinesSink::stdout(compression)?,
+    };
+    Ok(Box::new(
+        sink.with_fields(args.fields.clone())?
+            .with_summary(!args.no_summary),
+    ))
+}
+
+/// Creates a Pages builder for a crawl of `uri`
+fn crawl_pages(crawl: &CrawlArgs, uri: &str) -> Pages {
+    ::log::info!("Starting crawler for URI: {}", uri);
 
     // Convert from CLI argument URI type to internal URI type
-    let uri_type = convert_uri_type(args.type_, &args.uri);
+    let uri_type = convert_uri_type(crawl.type_, uri);
 
     // Print WebDriver info message for web URIs
     if let yield_page::UriType::Web(_) = &uri_type {
-        println!("Note: Web crawling requires a WebDriver server (e.g., ChromeDriver).");
-        println!(
+        eprintln!("Note: Web crawling requires a WebDriver server (e.g., ChromeDriver).");
+        eprintln!(
             "Set WEBDRIVER_URL environment variable if not using the default http://localhost:4444"
         );
     };
 
-    // Create a Pages builder with the specified parameters
-    let pages = Pages::new(uri_type)
-        .with_max_concurrency(args.concurrency)
-        .with_idle_timeout(args.idle_timeout)
-        .with_total_timeout(args.total_timeout);
-
-    // Start the crawler and get a receiver for pages
-    let mut rx = match pages.generate().await {
-        Ok(rx) => rx,
+    Pages::new(uri_type)
+        .with_max_concurrency(crawl.concurrency)
+        .with_idle_timeout(crawl.idle_timeout)
+        .with_total_timeout(crawl.total_timeout)
+}
+
+/// Crawls a site, checks every link found and prints the report
+///
+/// Returns the exit code: 0 if all links work, 1 if any failed or a page failed to crawl,
+/// 2 if the check couldn't run, 3 if no pages were crawled.
+async fn run_check_links(args: &CheckLinksArgs) -> i32 {
+    let mut checker = LinkChecker::new(LinkCheckOptions {
+        concurrency: args.probe_concurrency,
+        max_redirects: args.max_redirects,
+        check_anchors: !args.no_anchors,
+        timeout_secs: args.probe_timeout,
+    });
+
+    // Sources are kept to find the IDs that fragment links point at
+    let pages = crawl_pages(&args.crawl, &args.uri).with_keep_source(!args.no_anchors);
+    let (mut rx, mut error_rx) = match pages.generate_with_errors().await {
+        Ok(receivers) => receivers,
         Err(e) => {
             ::log::error!("Failed to start crawler: {}", e);
-            return;
+            return 2;
         }
     };
 
-    // Process pages as they come in
-    let mut pages_processed = 0;
-    let start_time = std::time::Instant::now();
-    ::log::info!("Started processing pages at {:?}", start_time);
+    let mut count = 0;
+    loop {
+        tokio::select! {
+            page = rx.recv() => {
+                let Some(page) = page else { break };
+                count += 1;
+                process_page(&page, count);
+                checker.add_page(&page);
+            }
+            // Pages that failed to load count as failures, and are probed again as link targets
+            Some(error) = error_rx.recv() => {
+                ::log::warn!("Failed to crawl {}: {}", error.url, error.message);
+                checker.add_error(&error);
+            }
+        }
+    }
+    // Errors reported after the last page
+    while let Some(error) = error_rx.recv().await {
+        ::log::warn!("Failed to crawl {}: {}", error.url, error.message);
+        checker.add_error(&error);
+    }
+    if count == 0 {
+        ::log::error!("No pages were crawled from {}", args.uri);
+        return 3;
+    }
+    ::log::info!("Crawled {} pages, checking their links", count);
 
-    while let Some(page) = rx.recv().await {
-        pages_processed += 1;
-        process_page(&page, pages_processed);
+    let webdriver_url = std::env::var("WEBDRIVER_URL")
+        .ok()
+        .filter(|url| !url.is_empty())
+        .unwrap_or_else(|| WebCrawlerConfig::new(&args.uri).webdriver_url);
+    let report = match checker.check_with_webdriver(&webdriver_url).await {
+        Ok(report) => report,
+        Err(e) => {
+            ::log::error!("Failed to check links: {}", e);
+            return 2;
+        }
+    };
+
+    let printed = if args.json {
+        serde_json::to_string_pretty(&report)
+            .map_err(|e| e.to_string())
+            .map(|json| println!("{}", json))
+    } else {
+        report
+            .write_text(std::io::stdout().lock(), args.show_redirects)
+            .map_err(|e| e.to_string())
+    };
+    if let Err(e) = printed {
+        ::log::error!("Failed to print link report: {}", e);
+        return 2;
     }
 
-    let duration = start_time.elapsed();
-    ::log::info!(
-        "Crawling complete - processed {} pages in {:.2} seconds",
-        pages_processed,
-        duration.as_secs_f64()
+    if report.has_failures() { 1 } else { 0 }
+}
+
+/// Crawls a site, audits every HTML page and prints the summary table
+///
+/// Fails after printing the report if any page failed to crawl, so the exit code is non-zero.
+async fn run_audit(args: &AuditArgs) -> Result<(), SinkError> {
+    let mut auditor = Auditor::new(AuditOptions {
+        min_words: args.min_words,
+        max_page_bytes: args.max_page_kb * 1024,
+    });
+
+    // Sources are kept to measure page sizes
+    let pages = crawl_pages(&args.crawl, &args.uri).with_keep_source(true);
+    let (mut rx, mut error_rx) = pages
+        .generate_with_errors()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut count = 0;
+    loop {
+        tokio::select! {
+            page = rx.recv() => {
+                let Some(page) = page else { break };
+                count += 1;
+                process_page(&page, count);
+                auditor.add_page(&page);
+            }
+            Some(error) = error_rx.recv() => {
+                ::log::warn!("Failed to crawl {}: {}", error.url, error.message);
+                auditor.add_error(&error);
+            }
+        }
+    }
+    // Errors reported after the last page
+    while let Some(error) = error_rx.recv().await {
+        ::log::warn!("Failed to crawl {}: {}", error.url, error.message);
+        auditor.add_error(&error);
+    }
+    if count == 0 {
+        return Err(format!("No pages were crawled from {}", args.uri).into());
+    }
+
+    let report = auditor.report();
+    if let Some(path) = &args.report {
+        report.write_file(path)?;
+        ::log::info!("Wrote audit report to {}", path.display());
+    }
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        report.write_summary(std::io::stdout().lock())?;
+    }
+    if !report.crawl_errors.is_empty() {
+        return Err(format!("{} pages failed to crawl", report.crawl_errors.len()).into());
+    }
+    Ok(())
+}
+
+/// Compares two stored crawls and prints the pages that changed
+fn run_diff(args: &DiffArgs) -> Result<(), SinkError> {
+    let old = CrawlSnapshot::load(&args.old)?;
+    let new = CrawlSnapshot::load(&args.new)?;
+    let diff = diff_crawls(
+        &old,
+        &new,
+        &DiffOptions {
+            content_diffs: !args.no_content_diff,
+            context_lines: args.context,
+            min_magnitude: args.min_change,
+        },
     );
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+    } else {
+        diff.write_text(std::io::stdout().lock())?;
+    }
+    Ok(())
 }
 
-// Example function to process a page
-fn process_page(page: &PageData, count: i32) {
+/// Writes the link graph exports and metrics requested on the command line
+fn write_graph(graph: &LinkGraph, args: &Args) {
+    for path in &args.graph {
+        match graph.write_file(path) {
+            Ok(()) => ::log::info!("Wrote link graph to {}", path.display()),
+            Err(e) => ::log::error!("Failed to write link graph to {}: {}", path.display(), e),
+        }
+    }
+    if let Some(path) = &args.graph_metrics {
+        let metrics = graph.metrics();
+        match metrics.write_file(path) {
+            Ok(()) => ::log::info!(
+                "Wrote link graph metrics to {} ({} pages, {} orphans)",
+                path.display(),
+                metrics.nodes,
+                metrics.orphans.len()
+            ),
+            Err(e) => ::log::error!(
+                "Failed to write link graph metrics to {}: {}",
+                path.display(),
+                e
+            ),
+        }
+    }
+}
+
+/// Prints the pages of a search index matching a query
+fn run_search(args: &SearchArgs) -> Result<(), SinkError> {
+    let index = SearchIndex::open_existing(&args.index)?;
+    let hits = index.search(&args.query.join(" "), args.limit)?;
+
+    for hit in &hits {
+        if args.json {
+            println!("{}", serde_json::to_string(hit)?);
+        } else {
+            println!("{:.2}  {}", hit.score, hit.url);
+            if let Some(title) = &hit.title {
+                println!("      {}", title);
+            }
+            if !hit.snippet.is_empty() {
+                println!(
+                    "      {}",
+                    hit.snippet.split_whitespace().collect::<Vec<_>>().join(" ")
+                );
+            }
+        }
+    }
+    if !args.json {
+        eprintln!("{} results", hits.len());
+    }
+    Ok(())
+}
+
+fn process_page(page: &PageData, count: usize) {
     ::log::info!("Processed page {}: {}", count, page.url);
     ::log::debug!("Page has {} links", page.links.len());
-
-    // In a real application, you would do something with the page data here
-    // For example, save it to a database, index it for search, etc.
 }
@@ -0,0 +1,175 @@
+use scraper::{ElementRef, Html, Node};
+use serde::{Deserialize, Serialize};
+
+/// Class prefixes used by syntax highlighters to tag the language of a block
+const LANGUAGE_CLASS_PREFIXES: [&str; 4] =
+    ["language-", "lang-", "highlight-source-", "highlight-"];
+
+/// Classes that are not languages despite matching a prefix
+const NON_LANGUAGE_CLASSES: [&str; 3] =
+    ["highlight-default", "language-none", "language-plaintext"];
+
+/// A block of source code, kept verbatim
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CodeBlock {
+    /// The code exactly as written, without the surrounding markup
+    pub code: String,
+
+    /// Language the block is tagged with (e.g. `rust`, `python`), lower-cased
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+
+    /// Text of the closest heading before the block
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub heading: Option<String>,
+}
+
+impl CodeBlock {
+    /// Creates a code block, dropping blank lines around the code
+    pub fn new(code: &str, language: Option<&str>, heading: Option<&str>) -> Self {
+        let code = code.trim_end();
+        let code = match code.find(|c: char| c != '\n' && c != '\r') {
+            Some(start) => &code[start..],
+            None => "",
+        };
+
+        Self {
+            code: code.to_string(),
+            language: language.and_then(normalize_language),
+            heading: heading.map(str::to_string),
+        }
+    }
+}
+
+/// Extracts `<pre>` blocks from an HTML document, in document order
+///
+/// The language is taken from `language-*`/`lang-*` classes (Prism, highlight.js),
+/// Sphinx `highlight-*` wrappers or `data-lang` attributes on the block or its
+/// ancestors. Line-number gutters are left out of the code.
+pub fn extract_code_blocks(doc: &Html) -> Vec<CodeBlock> {
+    let mut blocks = Vec::new();
+    let mut heading: Option<String> = None;
+
+    for element in doc
+        .root_element()
+        .descendants()
+        .filter_map(ElementRef::wrap)
+    {
+        match element.value().name() {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let text = element
+                    .text()
+                    .collect::<Vec<_>>()
+                    .join(" ")
+                    .split_whitespace()
+                    // Sphinx and MkDocs append permalink markers to headings
+                    .filter(|word| !matches!(*word, "¶" | "#" | "§"))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                if !text.is_empty() {
+                    heading = Some(text);
+                }
+            }
+            "pre" if !has_ancestor(element, "pre") => {
+                let mut code = String::new();
+                collect_code(element, &mut code);
+                let block = CodeBlock::new(
+                    &code,
+                    block_language(element).as_deref(),
+                    heading.as_deref(),
+                );
+                if !block.code.is_empty() {
+                    blocks.push(block);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    blocks
+}
+
+/// Normalizes a language name from a class or fence info string
+///
+/// `{.rust}`, `rust,ignore` and `Rust` all become `rust`.
+pub fn normalize_language(language: &str) -> Option<String> {
+    let language = language
+        .trim()
+        .trim_start_matches('{')
+        .trim_start_matches('.')
+        .split([',', ' ', '}', '{'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    (!language.is_empty()).then_some(language)
+}
+
+/// Finds the language of a `<pre>` block from its code element, itself or its ancestors
+fn block_language(pre: ElementRef) -> Option<String> {
+    let code_children = pre
+        .children()
+        .filter_map(ElementRef::wrap)
+        .filter(|e| e.value().name() == "code");
+    // Only close wrappers, so page-level classes like `<body class="language-en">` don't count
+    let ancestors = pre
+        .ancestors()
+        .filter_map(ElementRef::wrap)
+        .take_while(|e| !matches!(e.value().name(), "body" | "html"))
+        .take(2);
+
+    code_children
+        .chain(std::iter::once(pre))
+        .chain(ancestors)
+        .find_map(element_language)
+}
+
+/// Reads a language from an element's attributes or classes
+fn element_language(element: ElementRef) -> Option<String> {
+    let el = element.value();
+    if let Some(language) = el
+        .attr("data-lang")
+        .or(el.attr("data-language"))
+        .and_then(normalize_language)
+    {
+        return Some(language);
+    }
+
+    el.classes().find_map(|class| {
+        let lowered = class.to_ascii_lowercase();
+        if NON_LANGUAGE_CLASSES.contains(&lowered.as_str()) {
+            return None;
+        }
+        LANGUAGE_CLASS_PREFIXES
+            .iter()
+            .find_map(|prefix| lowered.strip_prefix(prefix))
+            .and_then(normalize_language)
+    })
+}
+
+/// Collects the text of a code block, skipping line-number gutters
+fn collect_code(element: ElementRef, code: &mut String) {
+    for child in element.children() {
+        match child.value() {
+            Node::Text(text) => code.push_str(text),
+            Node::Element(el) if el.name() == "br" => code.push('\n'),
+            Node::Element(el)
+                if el.classes().any(|c| {
+                    matches!(c, "linenos" | "lineno" | "line-numbers-rows" | "gutter")
+                }) => {}
+            Node::Element(_) => {
+                if let Some(child) = ElementRef::wrap(child) {
+                    collect_code(child, code);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Returns whether an element is nested inside an element with the given name
+fn has_ancestor(element: ElementRef, name: &str) -> bool {
+    element
+        .ancestors()
+        .filter_map(ElementRef::wrap)
+        .any(|e| e.value().name() == name)
+}
@@ -0,0 +1,320 @@
+use crate::parsers::{ParserType, epub, office};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Maps resources to a parser by MIME type and/or URL pattern
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParserMapping {
+    /// MIME type to match; `*` acts as a wildcard (e.g. `application/*+json`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+
+    /// Regex pattern matched against the URL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url_pattern: Option<String>,
+
+    /// Parser to use for matching resources
+    pub parser: ParserType,
+}
+
+/// A parser mapping with its patterns compiled
+#[derive(Debug)]
+struct CompiledMapping {
+    mime_type: Option<Regex>,
+    url_pattern: Option<Regex>,
+    parser: ParserType,
+}
+
+impl CompiledMapping {
+    /// A mapping matches when every pattern it specifies matches
+    fn matches(&self, url: &str, mime_type: Option<&str>) -> bool {
+        if self.mime_type.is_none() && self.url_pattern.is_none() {
+            return false;
+        }
+
+        let mime_matches = match (&self.mime_type, mime_type) {
+            (Some(regex), Some(mime)) => regex.is_match(mime),
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
+        let url_matches = self
+            .url_pattern
+            .as_ref()
+            .is_none_or(|regex| regex.is_match(url));
+
+        mime_matches && url_matches
+    }
+}
+
+/// Picks a parser for a resource from its Content-Type header, leading bytes and URL
+///
+/// Sources are consulted in order: configured mappings, the Content-Type header,
+/// magic bytes sniffed from the body and finally `ParserType::from_url`.
+#[derive(Debug, Default)]
+pub struct ContentTypeDetector {
+    mappings: Vec<CompiledMapping>,
+}
+
+impl ContentTypeDetector {
+    /// Create a detector from configured mappings
+    pub fn new(mappings: &[ParserMapping]) -> Result<Self, regex::Error> {
+        let mut compiled = Vec::with_capacity(mappings.len());
+        for mapping in mappings {
+            compiled.push(CompiledMapping {
+                mime_type: mapping.mime_type.as_deref().map(mime_glob).transpose()?,
+                url_pattern: mapping.url_pattern.as_deref().map(Regex::new).transpose()?,
+                parser: mapping.parser,
+            });
+        }
+
+        Ok(Self { mappings: compiled })
+    }
+
+    /// Determines the parser type, falling back to the URL when nothing else is conclusive
+    pub fn detect(&self, url: &str, content_type: Option<&str>, body: Option<&[u8]>) -> ParserType {
+        if let Some(parser_type) = self.detect_explicit(url, content_type, body) {
+            return parser_type;
+        }
+
+        let parser_type = ParserType::from_url(url);
+
+        // A text/plain response is never HTML, whatever the URL looks like
+        if parser_type == ParserType::Html
+            && content_type.map(mime_type).as_deref() == Some("text/plain")
+        {
+            ::log::debug!("Classifying as Text (text/plain): {}", url);
+            return ParserType::Text;
+        }
+
+        parser_type
+    }
+
+    /// Determines the parser type from the URL alone, before requesting it
+    ///
+    /// That's the first configured mapping that could apply, if it only looks at the URL,
+    /// or else the file extension. Returns `None` when the URL doesn't tell, e.g. for paths
+    /// without an extension or when a mapping on the Content-Type could apply.
+    pub fn detect_from_url(&self, url: &str) -> Option<ParserType> {
+        let mapping = self.mappings.iter().find(|m| match &m.url_pattern {
+            Some(regex) => regex.is_match(url),
+            None => m.mime_type.is_some(),
+        });
+        if let Some(mapping) = mapping {
+            return mapping.mime_type.is_none().then_some(mapping.parser);
+        }
+
+        let path = url.split(['?', '#']).next().unwrap_or_default();
+        let path = path.to_ascii_lowercase();
+        if path.ends_with(".html") || path.ends_with(".htm") || path.ends_with(".xhtml") {
+            return Some(ParserType::Html);
+        }
+
+        // Anything `from_url` doesn't recognise is assumed to be HTML, which says nothing
+        Some(ParserType::from_url(url)).filter(|parser_type| *parser_type != ParserType::Html)
+    }
+
+    /// Determines the parser type from mappings, headers and sniffing only
+    ///
+    /// Returns `None` when none of them is conclusive, e.g. when the header is missing
+    /// or generic (`application/octet-stream`, `text/plain`) and no body is available.
+    pub fn detect_explicit(
+        &self,
+        url: &str,
+        content_type: Option<&str>,
+        body: Option<&[u8]>,
+    ) -> Option<ParserType> {
+        let mime = content_type.map(mime_type);
+
+        if let Some(mapping) = self
+            .mappings
+            .iter()
+            .find(|m| m.matches(url, mime.as_deref()))
+        {
+            ::log::debug!(
+                "Classifying as {:?} (configured mapping): {}",
+                mapping.parser,
+                url
+            );
+            return Some(mapping.parser);
+        }
+
+        if let Some(parser_type) = mime.as_deref().and_then(ParserType::from_content_type) {
+            ::log::debug!("Classifying as {:?} (Content-Type): {}", parser_type, url);
+            return Some(parser_type);
+        }
+
+        if let Some(parser_type) = body.and_then(ParserType::sniff) {
+            ::log::debug!("Classifying as {:?} (sniffed): {}", parser_type, url);
+            return Some(parser_type);
+        }
+
+        None
+    }
+}
+
+/// Extracts the lower-cased `type/subtype` from a Content-Type header value
+pub fn mime_type(content_type: &str) -> String {
+    content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+/// Extracts the lower-cased `charset` parameter from a Content-Type header value
+pub fn charset(content_type: &str) -> Option<String> {
+    content_type.split(';').skip(1).find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case("charset")
+            .then(|| value.trim().trim_matches('"').to_ascii_lowercase())
+    })
+}
+
+/// Returns whether a MIME type says nothing about the format of the content
+pub fn is_generic_mime_type(mime: &str) -> bool {
+    matches!(
+        mime,
+        "" | "text/plain"
+            | "application/octet-stream"
+            | "binary/octet-stream"
+            | "application/binary"
+            | "application/unknown"
+            | "application/download"
+            | "application/force-download"
+            | "application/x-download"
+    )
+}
+
+/// Maps a MIME type to a parser type, or `None` for generic or unknown types
+pub(crate) fn parser_for_mime_type(mime: &str) -> Option<ParserType> {
+    if is_generic_mime_type(mime) {
+        return None;
+    }
+
+    match mime {
+        "text/html" | "application/xhtml+xml" => Some(ParserType::Html),
+        "text/markdown" | "text/x-markdown" => Some(ParserType::Markdown),
+        "text/x-rst" | "text/prs.fallenstein.rst" => Some(ParserType::Rst),
+        "application/pdf" | "application/x-pdf" => Some(ParserType::Pdf),
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document" => {
+            Some(ParserType::Docx)
+        }
+        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet" => {
+            Some(ParserType::Xlsx)
+        }
+        "application/vnd.openxmlformats-officedocument.presentationml.presentation" => {
+            Some(ParserType::Pptx)
+        }
+        "application/epub+zip" => Some(ParserType::Epub),
+        _ if mime.starts_with("application/vnd.oasis.opendocument.") => {
+            Some(ParserType::OpenDocument)
+        }
+        "application/json" | "application/ld+json" | "text/json" => Some(ParserType::Json),
+        "application/yaml" | "application/x-yaml" | "text/yaml" | "text/x-yaml" => {
+            Some(ParserType::Yaml)
+        }
+        "application/toml" | "text/x-toml" => Some(ParserType::Toml),
+        "text/csv" | "text/tab-separated-values" => Some(ParserType::Csv),
+        "application/xml" => Some(ParserType::Text),
+        "application/javascript" | "application/zip" | "application/gzip" => {
+            Some(ParserType::Other)
+        }
+        _ if mime.starts_with("text/") => {
+            // Stylesheets and scripts are web assets, not documents
+            if mime == "text/css" || mime == "text/javascript" {
+                Some(ParserType::Other)
+            } else {
+                Some(ParserType::Text)
+            }
+        }
+        _ if mime.ends_with("+json") => Some(ParserType::Json),
+        _ if mime.ends_with("+xml") => Some(ParserType::Text),
+        _ if mime.starts_with("image/")
+            || mime.starts_with("audio/")
+            || mime.starts_with("video/")
+            || mime.starts_with("font/") =>
+        {
+            Some(ParserType::Other)
+        }
+        _ => None,
+    }
+}
+
+/// Identifies the format of a resource from its leading bytes
+pub(crate) fn sniff_bytes(bytes: &[u8]) -> Option<ParserType> {
+    const BINARY_SIGNATURES: [&[u8]; 10] = [
+        b"\x89PNG\r\n\x1a\n",
+        b"\xff\xd8\xff",
+        b"GIF87a",
+        b"GIF89a",
+        b"PK\x03\x04",
+        b"\x1f\x8b",
+        b"RIFF",
+        b"wOFF",
+        b"wOF2",
+        b"\x00\x00\x01\x00",
+    ];
+
+    // UTF-16 text contains NUL bytes, so leave it to the URL-based fallback
+    if bytes.starts_with(b"\xff\xfe") || bytes.starts_with(b"\xfe\xff") {
+        return None;
+    }
+
+    if bytes.starts_with(b"%PDF-") {
+        return Some(ParserType::Pdf);
+    }
+
+    if bytes.starts_with(b"PK\x03\x04") {
+        if let Some(parser_type) = office::sniff(bytes) {
+            return Some(parser_type);
+        }
+        if epub::sniff(bytes) {
+            return Some(ParserType::Epub);
+        }
+    }
+
+    if BINARY_SIGNATURES.iter().any(|sig| bytes.starts_with(sig)) {
+        return Some(ParserType::Other);
+    }
+
+    let head = &bytes[..bytes.len().min(512)];
+    let text = head.strip_prefix(b"\xef\xbb\xbf").unwrap_or(head);
+    let start = text
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(text.len());
+    let lowered = text[start..].to_ascii_lowercase();
+
+    let html_markers: [&[u8]; 4] = [b"<!doctype html", b"<html", b"<head", b"<body"];
+    if html_markers.iter().any(|m| lowered.starts_with(m))
+        || (lowered.starts_with(b"<?xml") && contains(&lowered, b"<html"))
+    {
+        return Some(ParserType::Html);
+    }
+
+    // Anything with NUL bytes in the first few hundred bytes is binary
+    if head.contains(&0) {
+        return Some(ParserType::Other);
+    }
+
+    None
+}
+
+/// Returns whether `haystack` contains `needle`
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|w| w == needle)
+}
+
+/// Compiles a MIME type pattern with `*` wildcards into an anchored regex
+pub(crate) fn mime_glob(pattern: &str) -> Result<Regex, regex::Error> {
+    let escaped = pattern
+        .trim()
+        .to_ascii_lowercase()
+        .split('*')
+        .map(regex::escape)
+        .collect::<Vec<_>>()
+        .join(".*");
+    Regex::new(&format!("^{}$", escaped))
+}
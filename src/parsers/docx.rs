@@ -0,0 +1,298 @@
+use crate::parsers::office::{
+    DocumentBuilder, OfficeError, Package, Relationship, attr, child, children,
+    collapse_whitespace, is, relationship_id, table_from_cells,
+};
+use crate::parsers::{ParseResult, ParserOptions};
+use roxmltree::Node;
+use std::collections::HashMap;
+
+/// Main document part of a WordprocessingML package
+const DOCUMENT_PART: &str = "word/document.xml";
+
+/// What a paragraph style makes of its paragraphs
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum StyleRole {
+    /// The document title
+    Title,
+    /// A heading of the given level
+    Heading(u8),
+}
+
+/// Parses a Word document (`.docx`)
+///
+/// Paragraphs, tables and content controls are read in document order. Headings
+/// come from heading styles or outline levels, the title from the document
+/// properties or the Title style. Headers, footers, notes, comments and deleted
+/// revisions are left out.
+pub(crate) fn parse(
+    package: &mut Package,
+    options: &ParserOptions,
+) -> Result<ParseResult, OfficeError> {
+    let xml = package.read_required(DOCUMENT_PART)?;
+    let styles = match package.read("word/styles.xml")? {
+        Some(styles) => style_roles(&styles)?,
+        None => HashMap::new(),
+    };
+    let relationships = package.relationships(DOCUMENT_PART)?;
+
+    let doc = roxmltree::Document::parse(&xml)?;
+    let body = doc
+        .descendants()
+        .find(|n| is(*n, "body"))
+        .ok_or("Document has no body")?;
+
+    let mut reader = Reader {
+        styles,
+        relationships,
+        builder: DocumentBuilder::with_title(package.title("docProps/core.xml")?),
+    };
+    reader.blocks(body);
+    Ok(reader.builder.finish(options))
+}
+
+/// Reads paragraphs and tables into a document builder
+struct Reader {
+    styles: HashMap<String, StyleRole>,
+    relationships: HashMap<String, Relationship>,
+    builder: DocumentBuilder,
+}
+
+impl Reader {
+    /// Reads the block-level content of a container in order
+    fn blocks(&mut self, container: Node) {
+        for node in container.children().filter(Node::is_element) {
+            match node.tag_name().name() {
+                "p" => self.paragraph(node),
+                "tbl" => self.table(node),
+                // Content controls and custom XML wrap ordinary blocks
+                "sdt" | "sdtContent" | "customXml" => self.blocks(node),
+                _ => {}
+            }
+        }
+    }
+
+    /// Reads a paragraph, recording it as a heading or title if its style says so
+    fn paragraph(&mut self, p: Node) {
+        let mut text = String::new();
+        self.inline_text(p, &mut text);
+
+        let properties = child(p, "pPr");
+        let role = properties
+            .and_then(|pr| child(pr, "pStyle"))
+            .and_then(|style| attr(style, "val"))
+            .and_then(|id| self.styles.get(id).copied())
+            .or_else(|| properties.and_then(outline_role));
+
+        match role {
+            Some(StyleRole::Heading(level)) => self.builder.heading(level, &text),
+            Some(StyleRole::Title) => {
+                if self.builder.title.is_none() {
+                    self.builder.title = Some(collapse_whitespace(&text)).filter(|t| !t.is_empty());
+                }
+                self.builder.paragraph(&text);
+            }
+            None => self.builder.paragraph(&text),
+        }
+    }
+
+    /// Collects the text of runs below a node, recording hyperlinks
+    fn inline_text(&mut self, node: Node, text: &mut String) {
+        for child in node.children() {
+            if child.is_text() {
+                // Only `w:t` text is content; other text nodes are formatting whitespace
+                if is(node, "t") {
+                    text.push_str(child.text().unwrap_or_default());
+                }
+                continue;
+            }
+            match child.tag_name().name() {
+                "t" => self.inline_text(child, text),
+                "tab" => text.push('\t'),
+                "br" | "cr" => text.push('\n'),
+                "noBreakHyphen" => text.push('-'),
+                "hyperlink" => {
+                    let mut link_text = String::new();
+                    self.inline_text(child, &mut link_text);
+                    if let Some(target) = relationship_id(child)
+                        .and_then(|id| self.relationships.get(id))
+                        .filter(|r| r.external)
+                    {
+                        self.builder.link(&target.target, &link_text, "w:hyperlink");
+                    }
+                    text.push_str(&link_text);
+                }
+                // Properties, deleted revisions, field codes and notes aren't running text
+                "pPr" | "rPr" | "del" | "delText" | "instrText" | "footnoteReference"
+                | "endnoteReference" | "commentReference" | "drawing" | "pict" | "object" => {}
+                _ => self.inline_text(child, text),
+            }
+        }
+    }
+
+    /// Reads a table, spreading merged cells over the positions they cover
+    fn table(&mut self, tbl: Node) {
+        // Each cell with its starting column, column span and vertical merge state
+        let mut rows: Vec<Vec<Cell>> = Vec::new();
+        let mut header_rows = 0;
+        let mut in_header = true;
+
+        for tr in children(tbl, "tr") {
+            let is_header = child(tr, "trPr")
+                .and_then(|pr| child(pr, "tblHeader"))
+                .is_some_and(|h| !matches!(attr(h, "val"), Some("0" | "false" | "off")));
+            if in_header && is_header {
+                header_rows += 1;
+            } else {
+                in_header = false;
+            }
+
+            let mut column = 0;
+            let mut row = Vec::new();
+            for tc in tr.children().filter(|n| is(*n, "tc") || is(*n, "sdt")) {
+                let tc = if is(tc, "sdt") {
+                    match child(tc, "sdtContent").and_then(|c| child(c, "tc")) {
+                        Some(tc) => tc,
+                        None => continue,
+                    }
+                } else {
+                    tc
+                };
+                let properties = child(tc, "tcPr");
+                let colspan = properties
+                    .and_then(|pr| child(pr, "gridSpan"))
+                    .and_then(|span| attr(span, "val"))
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(1usize)
+                    .max(1);
+                let merge = properties.and_then(|pr| child(pr, "vMerge")).map(|merge| {
+                    if attr(merge, "val") == Some("restart") {
+                        Merge::Restart
+                    } else {
+                        Merge::Continue
+                    }
+                });
+
+                let mut paragraphs = Vec::new();
+                for p in children(tc, "p") {
+                    let mut text = String::new();
+                    self.inline_text(p, &mut text);
+                    paragraphs.push(text);
+                }
+
+                row.push(Cell {
+                    text: collapse_whitespace(&paragraphs.join(" ")),
+                    column,
+                    colspan,
+                    merge,
+                });
+                column += colspan;
+            }
+            rows.push(row);
+        }
+
+        // Turn vertical merges into row spans on the first cell of each merge
+        let mut spanned: Vec<Vec<(String, usize, usize)>> = Vec::with_capacity(rows.len());
+        for (r, row) in rows.iter().enumerate() {
+            let mut cells = Vec::new();
+            for cell in row {
+                if cell.merge == Some(Merge::Continue) {
+                    continue;
+                }
+                let rowspan = if cell.merge == Some(Merge::Restart) {
+                    1 + rows[r + 1..]
+                        .iter()
+                        .take_while(|next| {
+                            next.iter().any(|c| {
+                                c.column == cell.column && c.merge == Some(Merge::Continue)
+                            })
+                        })
+                        .count()
+                } else {
+                    1
+                };
+                cells.push((cell.text.clone(), cell.colspan, rowspan));
+            }
+            spanned.push(cells);
+        }
+
+        self.builder
+            .table(table_from_cells(None, &spanned, header_rows));
+    }
+}
+
+/// A table cell before vertical merges are resolved
+struct Cell {
+    text: String,
+    column: usize,
+    colspan: usize,
+    merge: Option<Merge>,
+}
+
+/// Vertical merge state of a table cell
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Merge {
+    Restart,
+    Continue,
+}
+
+/// Maps paragraph style IDs to the role their name or outline level gives them
+///
+/// Style IDs are localized (`Heading1`, `berschrift1`), so headings are recognized
+/// by the built-in style name (`heading 1`) or the outline level, following
+/// `basedOn` inheritance.
+fn style_roles(xml: &str) -> Result<HashMap<String, StyleRole>, OfficeError> {
+    let doc = roxmltree::Document::parse(xml)?;
+    let mut own: HashMap<&str, (Option<StyleRole>, Option<&str>)> = HashMap::new();
+
+    for style in doc.descendants().filter(|n| is(*n, "style")) {
+        if attr(style, "type") != Some("paragraph") {
+            continue;
+        }
+        let Some(id) = attr(style, "styleId") else {
+            continue;
+        };
+        let name = child(style, "name")
+            .and_then(|n| attr(n, "val"))
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let role = if name == "title" {
+            Some(StyleRole::Title)
+        } else if let Some(level) = name
+            .strip_prefix("heading ")
+            .and_then(|level| level.trim().parse::<u8>().ok())
+        {
+            Some(StyleRole::Heading(level))
+        } else {
+            child(style, "pPr").and_then(outline_role)
+        };
+        let based_on = child(style, "basedOn").and_then(|b| attr(b, "val"));
+        own.insert(id, (role, based_on));
+    }
+
+    let mut roles = HashMap::new();
+    for &id in own.keys() {
+        let mut current = Some(id);
+        // Guard against cycles in malformed style sheets
+        for _ in 0..10 {
+            let Some((role, based_on)) = current.and_then(|c| own.get(c)) else {
+                break;
+            };
+            if let Some(role) = role {
+                roles.insert(id.to_string(), *role);
+                break;
+            }
+            current = *based_on;
+        }
+    }
+    Ok(roles)
+}
+
+/// Reads a heading role from an outline level in paragraph properties
+///
+/// Levels are zero-based; level 9 means body text.
+fn outline_role(properties: Node) -> Option<StyleRole> {
+    let level: u8 = attr(child(properties, "outlineLvl")?, "val")?
+        .parse()
+        .ok()?;
+    (level < 9).then_some(StyleRole::Heading(level + 1))
+}
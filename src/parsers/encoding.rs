@@ -0,0 +1,122 @@
+use crate::parsers::content_type;
+use encoding_rs::{Encoding, UTF_8};
+use regex::bytes::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+
+/// Number of leading bytes scanned for `<meta charset>` and XML declarations
+const PRESCAN_BYTES: usize = 1024;
+
+/// Where the character encoding of a document was determined from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EncodingSource {
+    /// A byte order mark at the start of the content
+    Bom,
+    /// The `charset` parameter of the Content-Type header
+    ContentType,
+    /// A `<meta charset>` or `<meta http-equiv="Content-Type">` tag
+    MetaTag,
+    /// The `encoding` attribute of an XML declaration
+    XmlDeclaration,
+    /// Statistical detection over the content
+    Detected,
+    /// Reported by the browser after rendering the page
+    Browser,
+}
+
+/// Text decoded from raw bytes, along with the encoding that was used
+#[derive(Debug, Clone)]
+pub struct DecodedText {
+    /// The content transcoded to UTF-8
+    pub text: String,
+
+    /// Canonical name of the encoding (e.g. `Shift_JIS`, `windows-1250`)
+    pub encoding: &'static str,
+
+    /// Where the encoding was determined from
+    pub source: EncodingSource,
+
+    /// Whether malformed sequences were replaced during decoding
+    pub had_errors: bool,
+}
+
+static META_CHARSET: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)<meta[^>]+charset\s*=\s*["']?\s*([a-z0-9_\-:.]+)"#).unwrap()
+});
+
+static XML_ENCODING: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^<\?xml[^>]+encoding\s*=\s*["']([a-zA-Z0-9_\-.]+)["']"#).unwrap()
+});
+
+/// Decodes raw bytes to UTF-8, detecting the character encoding
+///
+/// The encoding is taken from the first conclusive source in order: a byte order
+/// mark, the Content-Type `charset`, a `<meta>` tag or XML declaration near the
+/// start of the content, and finally statistical detection.
+pub fn decode(bytes: &[u8], content_type: Option<&str>) -> DecodedText {
+    let (encoding, source) = detect(bytes, content_type);
+
+    // Strip the BOM ourselves so it isn't left in the text
+    let (bom_encoding, bom_length) = Encoding::for_bom(bytes).unwrap_or((encoding, 0));
+    let body = if bom_encoding == encoding {
+        &bytes[bom_length..]
+    } else {
+        bytes
+    };
+
+    let (text, had_errors) = encoding.decode_without_bom_handling(body);
+    if had_errors {
+        ::log::debug!(
+            "Malformed {} sequences replaced while decoding",
+            encoding.name()
+        );
+    }
+
+    DecodedText {
+        text: text.into_owned(),
+        encoding: encoding.name(),
+        source,
+        had_errors,
+    }
+}
+
+/// Detects the character encoding of raw bytes
+pub fn detect(bytes: &[u8], content_type: Option<&str>) -> (&'static Encoding, EncodingSource) {
+    if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+        return (encoding, EncodingSource::Bom);
+    }
+
+    if let Some(encoding) = content_type
+        .and_then(content_type::charset)
+        .and_then(|label| Encoding::for_label(label.as_bytes()))
+    {
+        return (encoding, EncodingSource::ContentType);
+    }
+
+    let head = &bytes[..bytes.len().min(PRESCAN_BYTES)];
+
+    if let Some(encoding) = XML_ENCODING
+        .captures(head)
+        .and_then(|c| Encoding::for_label(&c[1]))
+    {
+        return (encoding, EncodingSource::XmlDeclaration);
+    }
+
+    if let Some(encoding) = META_CHARSET
+        .captures(head)
+        .and_then(|c| Encoding::for_label(&c[1]))
+    {
+        // A UTF-16 declaration in ASCII-compatible markup can't be right (HTML spec)
+        let encoding = if encoding == encoding_rs::UTF_16LE || encoding == encoding_rs::UTF_16BE {
+            UTF_8
+        } else {
+            encoding
+        };
+        return (encoding, EncodingSource::MetaTag);
+    }
+
+    let mut detector = chardetng::EncodingDetector::new();
+    detector.feed(bytes, true);
+    (detector.guess(None, true), EncodingSource::Detected)
+}
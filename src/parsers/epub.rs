@@ -0,0 +1,314 @@
+use crate::archive::MEMBER_SEPARATOR;
+use crate::parsers::office::{
+    OfficeError, Package, attr, child, children, collapse_whitespace, is, node_text, resolve_part,
+};
+use crate::parsers::{ParseResult, ParserOptions, html};
+use crate::results::PageData;
+use scraper::{Html, Selector};
+use std::collections::HashMap;
+
+/// Path of the container document pointing at the package document
+const CONTAINER_PART: &str = "META-INF/container.xml";
+
+/// Media type the `mimetype` file of an EPUB starts with
+const EPUB_MIME_TYPE: &str = "application/epub+zip";
+
+/// An e-book read from an EPUB file
+pub struct EpubBook {
+    /// Title from the package metadata
+    pub title: Option<String>,
+    /// Creators from the package metadata, in order
+    pub authors: Vec<String>,
+    /// Language from the package metadata, lower-cased
+    pub language: Option<String>,
+    /// Chapters in spine (reading) order
+    pub chapters: Vec<EpubChapter>,
+}
+
+/// One content document of an EPUB spine
+pub struct EpubChapter {
+    /// Path of the chapter inside the EPUB
+    pub path: String,
+    /// Title from the table of contents, or the chapter's first heading
+    pub title: Option<String>,
+    /// The parsed chapter
+    pub result: ParseResult,
+}
+
+/// Returns whether a ZIP archive is an EPUB
+pub fn sniff(bytes: &[u8]) -> bool {
+    let Ok(mut package) = Package::open(bytes) else {
+        return false;
+    };
+    matches!(package.read("mimetype"), Ok(Some(mime)) if mime.trim().starts_with(EPUB_MIME_TYPE))
+}
+
+/// Parses an EPUB into one document, chapters following each other in reading order
+///
+/// Links between chapters point at the book itself when `url` is given.
+pub fn parse(
+    bytes: &[u8],
+    url: Option<&str>,
+    options: &ParserOptions,
+) -> Result<ParseResult, OfficeError> {
+    Ok(parse_book(bytes, url, options)?.into_combined(url))
+}
+
+/// Parses an EPUB into its chapters, walking the spine in order
+///
+/// Chapters are parsed as HTML. With a `url`, each chapter is addressed as a
+/// member of the book (`book.epub!/OEBPS/ch1.xhtml`) and its links are resolved
+/// against that address.
+pub fn parse_book(
+    bytes: &[u8],
+    url: Option<&str>,
+    options: &ParserOptions,
+) -> Result<EpubBook, OfficeError> {
+    let mut package = Package::open(bytes)?;
+
+    let container = package.read_required(CONTAINER_PART)?;
+    let container = roxmltree::Document::parse(&container)?;
+    let opf_path = container
+        .descendants()
+        .find(|n| is(*n, "rootfile"))
+        .and_then(|n| attr(n, "full-path"))
+        .ok_or("Container has no rootfile")?
+        .to_string();
+    let opf_dir = opf_path.rsplit_once('/').map_or("", |(dir, _)| dir);
+
+    let opf = package.read_required(&opf_path)?;
+    let opf = roxmltree::Document::parse(&opf)?;
+    let root = opf.root_element();
+
+    let metadata = child(root, "metadata");
+    let meta_texts = |name: &'static str| -> Vec<String> {
+        metadata
+            .into_iter()
+            .flat_map(|m| children(m, name))
+            .map(|n| collapse_whitespace(&node_text(n)))
+            .filter(|t| !t.is_empty())
+            .collect()
+    };
+    let title = meta_texts("title").into_iter().next();
+    let authors = meta_texts("creator");
+    let language = meta_texts("language")
+        .into_iter()
+        .next()
+        .map(|l| l.to_ascii_lowercase());
+
+    // Manifest items by ID: (path, media type, properties)
+    let mut manifest: HashMap<&str, (String, &str, &str)> = HashMap::new();
+    if let Some(items) = child(root, "manifest") {
+        for item in children(items, "item") {
+            if let (Some(id), Some(href)) = (attr(item, "id"), attr(item, "href")) {
+                manifest.insert(
+                    id,
+                    (
+                        resolve_part(opf_dir, href),
+                        attr(item, "media-type").unwrap_or_default(),
+                        attr(item, "properties").unwrap_or_default(),
+                    ),
+                );
+            }
+        }
+    }
+
+    let spine = child(root, "spine");
+    let toc_titles = {
+        let nav = manifest
+            .values()
+            .find(|(_, _, properties)| properties.split_whitespace().any(|p| p == "nav"));
+        let ncx = spine
+            .and_then(|s| attr(s, "toc"))
+            .and_then(|id| manifest.get(id));
+        match (nav, ncx) {
+            (Some((path, ..)), _) => package
+                .read(path)?
+                .map(|xhtml| nav_titles(&xhtml, path))
+                .unwrap_or_default(),
+            (None, Some((path, ..))) => match package.read(path)? {
+                Some(xml) => ncx_titles(&xml, path)?,
+                None => HashMap::new(),
+            },
+            (None, None) => HashMap::new(),
+        }
+    };
+
+    let mut chapters = Vec::new();
+    for itemref in spine.into_iter().flat_map(|s| children(s, "itemref")) {
+        let Some((path, media_type, _)) = attr(itemref, "idref").and_then(|id| manifest.get(id))
+        else {
+            continue;
+        };
+        if !matches!(*media_type, "application/xhtml+xml" | "text/html") {
+            continue;
+        }
+        let Some(xhtml) = package.read(path)? else {
+            ::log::debug!("Spine references missing chapter {}", path);
+            continue;
+        };
+
+        let chapter_url = url.map(|url| member_url(url, path));
+        let mut result = html::parse_with_options(&xhtml, chapter_url.as_deref(), options);
+        if !options.extract_links {
+            result.links.clear();
+            result.link_details.clear();
+        }
+        let title = toc_titles
+            .get(path.as_str())
+            .cloned()
+            .or_else(|| result.headings.first().map(|h| h.text.clone()))
+            .or_else(|| result.title.clone());
+
+        chapters.push(EpubChapter {
+            path: path.clone(),
+            title,
+            result,
+        });
+    }
+
+    Ok(EpubBook {
+        title,
+        authors,
+        language,
+        chapters,
+    })
+}
+
+impl EpubBook {
+    /// Combines the chapters into one document
+    ///
+    /// Links between chapters are rewritten to point at the book (`url`) with the
+    /// target's fragment, since the chapters no longer have addresses of their own.
+    pub fn into_combined(self, url: Option<&str>) -> ParseResult {
+        let chapter_prefix = url.map(|url| format!("{}{}", url, MEMBER_SEPARATOR));
+        let mut combined = ParseResult::new(String::new(), Vec::new());
+        let mut contents = Vec::new();
+
+        for chapter in self.chapters {
+            let mut result = chapter.result;
+            if let (Some(url), Some(prefix)) = (url, &chapter_prefix) {
+                for link in &mut result.link_details {
+                    if link.url.starts_with(prefix.as_str()) {
+                        link.url = match link.url.split_once('#') {
+                            Some((_, fragment)) => format!("{}#{}", url, fragment),
+                            None => url.to_string(),
+                        };
+                    }
+                }
+            }
+
+            if !result.content.is_empty() {
+                contents.push(result.content);
+            }
+            combined.headings.extend(result.headings);
+            combined.links.extend(result.links);
+            combined.link_details.extend(result.link_details);
+            combined.tables.extend(result.tables);
+            combined.code_blocks.extend(result.code_blocks);
+            combined.media.extend(result.media);
+            combined.structured_data.extend(result.structured_data);
+            if combined.language.is_none() {
+                combined.language = result.language;
+                combined.metadata.language_source = result.metadata.language_source;
+            }
+            if combined.title.is_none() {
+                combined.title = chapter.title;
+            }
+        }
+
+        combined.content = contents.join("\n\n");
+        combined.title = self.title.or(combined.title);
+        if self.language.is_some() {
+            combined.language = self.language;
+            combined.metadata.language_source = None;
+        }
+        combined.metadata.authors = self.authors;
+        combined
+    }
+
+    /// Turns each chapter into a page addressed as a member of the book at `url`
+    ///
+    /// Chapters carry the book title as their section path and the book's authors.
+    pub fn into_pages(self, url: &str) -> Vec<PageData> {
+        let book_title = self.title;
+        let authors = self.authors;
+        let language = self.language;
+
+        self.chapters
+            .into_iter()
+            .map(|chapter| {
+                let mut result = chapter.result;
+                result.title = chapter.title;
+                result.section_path = book_title.iter().cloned().collect();
+                if result.language.is_none() {
+                    result.language = language.clone();
+                }
+                result.metadata.authors = authors.clone();
+                result.metadata.archive_url = Some(url.to_string());
+                PageData::from_parse_result(&member_url(url, &chapter.path), result)
+            })
+            .collect()
+    }
+}
+
+/// Address of a file inside the book
+fn member_url(url: &str, path: &str) -> String {
+    format!("{}{}{}", url, MEMBER_SEPARATOR, path)
+}
+
+/// Reads chapter titles from an EPUB 3 navigation document, keyed by chapter path
+///
+/// Only the first entry for each chapter is kept, so sections within a chapter
+/// don't replace its title.
+fn nav_titles(xhtml: &str, nav_path: &str) -> HashMap<String, String> {
+    let dir = nav_path.rsplit_once('/').map_or("", |(dir, _)| dir);
+    let doc = Html::parse_document(xhtml);
+    let nav_selector = Selector::parse("nav").unwrap();
+    let link_selector = Selector::parse("a[href]").unwrap();
+
+    let navs: Vec<_> = doc.select(&nav_selector).collect();
+    let Some(toc) = navs
+        .iter()
+        .find(|nav| nav.value().attr("epub:type") == Some("toc"))
+        .or(navs.first())
+    else {
+        return HashMap::new();
+    };
+
+    let mut titles = HashMap::new();
+    for link in toc.select(&link_selector) {
+        let href = link.value().attr("href").unwrap_or_default();
+        let label = collapse_whitespace(&link.text().collect::<String>());
+        add_title(&mut titles, dir, href, label);
+    }
+    titles
+}
+
+/// Reads chapter titles from an EPUB 2 NCX table of contents, keyed by chapter path
+fn ncx_titles(xml: &str, ncx_path: &str) -> Result<HashMap<String, String>, OfficeError> {
+    let dir = ncx_path.rsplit_once('/').map_or("", |(dir, _)| dir);
+    let doc = roxmltree::Document::parse(xml)?;
+
+    let mut titles = HashMap::new();
+    for point in doc.descendants().filter(|n| is(*n, "navPoint")) {
+        let label = child(point, "navLabel")
+            .map(node_text)
+            .map(|t| collapse_whitespace(&t))
+            .unwrap_or_default();
+        let src = child(point, "content")
+            .and_then(|c| attr(c, "src"))
+            .unwrap_or_default();
+        add_title(&mut titles, dir, src, label);
+    }
+    Ok(titles)
+}
+
+/// Records a table of contents entry unless its chapter already has a title
+fn add_title(titles: &mut HashMap<String, String>, dir: &str, href: &str, label: String) {
+    let path = href.split('#').next().unwrap_or_default();
+    if path.is_empty() || label.is_empty() {
+        return;
+    }
+    titles.entry(resolve_part(dir, path)).or_insert(label);
+}
@@ -1,20 +1,80 @@
-use crate::parsers::ParseResult;
-use scraper::{Html, Selector};
+use crate::parsers::text::{self, TextParserOptions};
+use crate::parsers::{Heading, ParseResult, ParserOptions};
+use crate::parsers::{code, language, links, media, schema_org, tables};
+use scraper::{ElementRef, Html, Node, Selector};
+
+/// Elements whose boundaries separate paragraphs of text
+const BLOCK_ELEMENTS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "dd",
+    "details",
+    "div",
+    "dl",
+    "dt",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "summary",
+    "table",
+    "tr",
+    "ul",
+];
 
 /// Parses HTML content to extract text and links
 pub fn parse(html: &str) -> ParseResult {
+    parse_document(html, None, &default_options())
+}
+
+/// Parses HTML content fetched from `url`, resolving typed links against the page
+pub fn parse_with_url(html: &str, url: &str) -> ParseResult {
+    parse_document(html, Some(url), &default_options())
+}
+
+/// Parses HTML content with specific parser options
+///
+/// The text options apply to the text content, with paragraphs taken from block
+/// elements and line breaks from `<br>` and `<pre>`. The content and exclude
+/// selectors only narrow the text content; links, tables and media come from the
+/// whole document.
+pub fn parse_with_options(
+    html: &str,
+    page_url: Option<&str>,
+    options: &ParserOptions,
+) -> ParseResult {
+    parse_document(html, page_url, options)
+}
+
+/// Options for `parse` and `parse_with_url`, which collapse all whitespace
+fn default_options() -> ParserOptions {
+    ParserOptions::with_text_options(TextParserOptions::default())
+}
+
+/// Shared implementation for the HTML parsing functions
+fn parse_document(html: &str, page_url: Option<&str>, options: &ParserOptions) -> ParseResult {
     let doc = Html::parse_document(html);
 
     // Extract text content
-    let content_selector = Selector::parse("body").unwrap();
-    let text = doc
-        .select(&content_selector)
-        .flat_map(|n| n.text())
-        .collect::<Vec<_>>()
-        .join(" ")
-        .split_whitespace()
-        .collect::<Vec<_>>()
-        .join(" ");
+    let text = extract_text(&doc, options);
 
     // Extract links
     let link_selector = Selector::parse("a").unwrap();
@@ -33,7 +93,207 @@ pub fn parse(html: &str) -> ParseResult {
         );
     }
 
-    ParseResult::new(text, links)
+    let detected_language = language::detect_from_document(&doc, page_url)
+        .or_else(|| language::detect_from_text(&text));
+
+    let mut result = ParseResult::new(text, links);
+    result.title = extract_title(&doc);
+    result.metadata.description = extract_meta_description(&doc);
+    result.metadata.robots = extract_robots_directives(&doc);
+    result.headings = extract_headings(&doc);
+    result.link_details = links::extract_links_from_document(&doc, page_url);
+    result.tables = tables::extract_tables(&doc);
+    result.code_blocks = code::extract_code_blocks(&doc);
+    result.media = media::extract_media(&doc, page_url);
+    result.structured_data = schema_org::extract_schema_objects(&doc, page_url);
+    result.section_path = schema_org::section_path(&result.structured_data);
+    if result.section_path.is_empty() {
+        result.section_path = extract_breadcrumb_trail(&doc);
+    }
+    if let Some(detected) = detected_language {
+        result.set_language(detected);
+    }
+    result
+}
+
+/// Extracts the text of the content element, leaving out excluded elements
+fn extract_text(doc: &Html, options: &ParserOptions) -> String {
+    let body_selector = Selector::parse("body").unwrap();
+    let content = options
+        .content_selector
+        .as_deref()
+        .and_then(|selector| parse_selector(selector, "content"))
+        .and_then(|selector| doc.select(&selector).next())
+        .or_else(|| doc.select(&body_selector).next());
+    let Some(content) = content else {
+        return String::new();
+    };
+
+    let excluded: Vec<ElementRef> = options
+        .exclude_selectors
+        .iter()
+        .filter_map(|selector| parse_selector(selector, "exclude"))
+        .flat_map(|selector| doc.select(&selector).collect::<Vec<_>>())
+        .collect();
+
+    let mut raw = String::new();
+    collect_text(content, &excluded, false, &mut raw);
+    text::parse_with_options(&raw, &options.text).content
+}
+
+/// Parses a configured CSS selector, logging invalid ones
+fn parse_selector(selector: &str, purpose: &str) -> Option<Selector> {
+    match Selector::parse(selector) {
+        Ok(selector) => Some(selector),
+        Err(e) => {
+            ::log::warn!(
+                "Ignoring invalid {} selector {:?}: {}",
+                purpose,
+                selector,
+                e
+            );
+            None
+        }
+    }
+}
+
+/// Collects text with blank lines between blocks and line breaks from `<br>` and `<pre>`
+fn collect_text(element: ElementRef, excluded: &[ElementRef], in_pre: bool, raw: &mut String) {
+    for child in element.children() {
+        match child.value() {
+            Node::Text(text) if in_pre => raw.push_str(text),
+            Node::Text(text) => {
+                // Whitespace in markup isn't a line break; text nodes stay separate words
+                raw.push(' ');
+                raw.push_str(&text.split_whitespace().collect::<Vec<_>>().join(" "));
+                raw.push(' ');
+            }
+            Node::Element(el) => {
+                let Some(child) = ElementRef::wrap(child) else {
+                    continue;
+                };
+                if excluded.contains(&child) {
+                    continue;
+                }
+                let name = el.name();
+                if name == "br" {
+                    raw.push('\n');
+                    continue;
+                }
+                let is_block = BLOCK_ELEMENTS.contains(&name);
+                if is_block {
+                    raw.push_str("\n\n");
+                }
+                collect_text(child, excluded, in_pre || name == "pre", raw);
+                if is_block {
+                    raw.push_str("\n\n");
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Extracts a breadcrumb trail from navigation markup without schema.org annotations
+///
+/// Looks for an element labelled or classed as a breadcrumb (`<nav aria-label="Breadcrumb">`,
+/// `<ol class="breadcrumb">`) and takes the text of its list items or links.
+fn extract_breadcrumb_trail(doc: &Html) -> Vec<String> {
+    let container_selector = Selector::parse("[aria-label], [class]").unwrap();
+    let item_selector = Selector::parse("li").unwrap();
+    let link_selector = Selector::parse("a").unwrap();
+
+    let Some(container) = doc.select(&container_selector).find(|e| {
+        let label = e.value().attr("aria-label").unwrap_or_default();
+        label.to_ascii_lowercase().contains("breadcrumb")
+            || e.value().classes().any(|c| {
+                c.eq_ignore_ascii_case("breadcrumb") || c.eq_ignore_ascii_case("breadcrumbs")
+            })
+    }) else {
+        return Vec::new();
+    };
+
+    let mut items: Vec<String> = container
+        .select(&item_selector)
+        .map(|e| normalize_text(e.text()))
+        .collect();
+    if items.is_empty() {
+        items = container
+            .select(&link_selector)
+            .map(|e| normalize_text(e.text()))
+            .collect();
+    }
+
+    // Separators such as `/` or `›` are sometimes rendered as their own items
+    items.retain(|item| item.chars().any(char::is_alphanumeric));
+    items
+}
+
+/// Extracts the document title from `<title>`
+fn extract_title(doc: &Html) -> Option<String> {
+    let title_selector = Selector::parse("title").unwrap();
+    doc.select(&title_selector)
+        .next()
+        .map(|e| normalize_text(e.text()))
+        .filter(|t| !t.is_empty())
+}
+
+/// Extracts the page summary from `<meta name="description">`
+fn extract_meta_description(doc: &Html) -> Option<String> {
+    let selector = Selector::parse("meta[name][content]").unwrap();
+    doc.select(&selector)
+        .find(|e| {
+            e.value()
+                .attr("name")
+                .is_some_and(|name| name.eq_ignore_ascii_case("description"))
+        })
+        .and_then(|e| e.value().attr("content"))
+        .map(|content| normalize_text(std::iter::once(content)))
+        .filter(|content| !content.is_empty())
+}
+
+/// Extracts the directives of all `<meta name="robots">` tags, lower-cased
+fn extract_robots_directives(doc: &Html) -> Vec<String> {
+    let selector = Selector::parse("meta[name][content]").unwrap();
+    let mut directives = Vec::new();
+    for element in doc.select(&selector) {
+        let value = element.value();
+        if !value
+            .attr("name")
+            .unwrap_or_default()
+            .eq_ignore_ascii_case("robots")
+        {
+            continue;
+        }
+        for directive in value.attr("content").unwrap_or_default().split(',') {
+            let directive = directive.trim().to_ascii_lowercase();
+            if !directive.is_empty() && !directives.contains(&directive) {
+                directives.push(directive);
+            }
+        }
+    }
+    directives
+}
+
+/// Extracts `<h1>` to `<h6>` headings in document order
+fn extract_headings(doc: &Html) -> Vec<Heading> {
+    let heading_selector = Selector::parse("h1, h2, h3, h4, h5, h6").unwrap();
+    doc.select(&heading_selector)
+        .filter_map(|e| {
+            let level = e.value().name()[1..].parse().ok()?;
+            let text = normalize_text(e.text());
+            (!text.is_empty()).then_some(Heading { level, text })
+        })
+        .collect()
+}
+
+/// Joins text nodes and collapses whitespace
+fn normalize_text<'a>(text: impl Iterator<Item = &'a str>) -> String {
+    text.collect::<Vec<_>>()
+        .join(" ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
 /// Parses HTML content but only extracts text (no links)
@@ -55,6 +315,9 @@ pub fn parse_text_only(html: &str) -> ParseResult {
 }
 
 /// Parses HTML content and only extracts links (no text)
+///
+/// Only `<a href>` values are returned; use `links::extract_links` for frames,
+/// pagination, meta refreshes and the other link kinds.
 pub fn parse_links_only(html: &str) -> Vec<String> {
     let doc = Html::parse_document(html);
 
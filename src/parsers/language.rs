@@ -0,0 +1,221 @@
+use crate::parsers::{Link, LinkKind};
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+
+/// Where the language of a document was determined from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LanguageSource {
+    /// The `lang` attribute of the `<html>` element
+    HtmlLang,
+    /// A `<meta http-equiv="Content-Language">` tag
+    ContentLanguage,
+    /// An `hreflang` alternate link pointing at the document itself
+    Hreflang,
+    /// Statistical detection over the text content
+    Detected,
+}
+
+/// Language of a document as a lower-cased BCP 47 tag (e.g. `en`, `pt-br`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct DetectedLanguage {
+    /// Normalized language tag
+    pub tag: String,
+
+    /// Where the language was determined from
+    pub source: LanguageSource,
+}
+
+/// Determines the language of an HTML document from its markup
+///
+/// Checks `<html lang>`, then `<meta http-equiv="Content-Language">`, then an
+/// `hreflang` alternate whose URL is the page itself.
+pub fn detect_from_document(doc: &Html, page_url: Option<&str>) -> Option<DetectedLanguage> {
+    let html_selector = Selector::parse("html").unwrap();
+    if let Some(tag) = doc
+        .select(&html_selector)
+        .next()
+        .and_then(|e| e.value().attr("lang").or(e.value().attr("xml:lang")))
+        .and_then(normalize_tag)
+    {
+        return Some(DetectedLanguage {
+            tag,
+            source: LanguageSource::HtmlLang,
+        });
+    }
+
+    let meta_selector = Selector::parse("meta[http-equiv][content]").unwrap();
+    if let Some(tag) = doc
+        .select(&meta_selector)
+        .find(|e| {
+            e.value()
+                .attr("http-equiv")
+                .is_some_and(|v| v.eq_ignore_ascii_case("content-language"))
+        })
+        .and_then(|e| e.value().attr("content"))
+        // The header form allows a list; the first entry is the primary language
+        .and_then(|content| content.split(',').next())
+        .and_then(normalize_tag)
+    {
+        return Some(DetectedLanguage {
+            tag,
+            source: LanguageSource::ContentLanguage,
+        });
+    }
+
+    let page_url = url::Url::parse(page_url?).ok()?;
+    let alternate_selector = Selector::parse("link[rel~=alternate][hreflang][href]").unwrap();
+    doc.select(&alternate_selector)
+        .find(|e| {
+            e.value()
+                .attr("href")
+                .and_then(|href| page_url.join(href.trim()).ok())
+                .is_some_and(|href| same_page(&href, &page_url))
+        })
+        .and_then(|e| e.value().attr("hreflang"))
+        .and_then(normalize_tag)
+        .map(|tag| DetectedLanguage {
+            tag,
+            source: LanguageSource::Hreflang,
+        })
+}
+
+/// Detects the language of plain text, if it can be identified reliably
+pub fn detect_from_text(text: &str) -> Option<DetectedLanguage> {
+    let info = whatlang::detect(text)?;
+    if !info.is_reliable() {
+        ::log::trace!(
+            "Unreliable language guess {} ({:.2})",
+            info.lang().code(),
+            info.confidence()
+        );
+        return None;
+    }
+
+    Some(DetectedLanguage {
+        tag: iso_639_1(info.lang().code())?.to_string(),
+        source: LanguageSource::Detected,
+    })
+}
+
+/// Normalizes a language tag: lower-cased, `_` replaced with `-`
+///
+/// Returns `None` for empty tags and `x-default`, which doesn't name a language.
+pub fn normalize_tag(tag: &str) -> Option<String> {
+    let tag = tag.trim().replace('_', "-").to_ascii_lowercase();
+    (!tag.is_empty() && tag != "x-default").then_some(tag)
+}
+
+/// Returns whether a language tag matches a configured language
+///
+/// A configured primary language matches all of its regional variants, so `en`
+/// matches `en-gb`, while `en-gb` only matches `en-gb`.
+pub fn matches(tag: &str, language: &str) -> bool {
+    let (Some(tag), Some(language)) = (normalize_tag(tag), normalize_tag(language)) else {
+        return false;
+    };
+    tag == language
+        || tag
+            .strip_prefix(language.as_str())
+            .is_some_and(|rest| rest.starts_with('-'))
+}
+
+/// Returns the `hreflang` alternates of a page as (normalized tag, URL) pairs
+///
+/// `x-default` alternates are skipped since they don't name a language.
+pub fn hreflang_alternates(links: &[Link]) -> Vec<(String, &str)> {
+    links
+        .iter()
+        .filter(|link| link.kind == LinkKind::Alternate)
+        .filter_map(|link| {
+            let tag = normalize_tag(link.hreflang.as_deref()?)?;
+            Some((tag, link.url.as_str()))
+        })
+        .collect()
+}
+
+/// Returns whether two URLs point at the same page, ignoring fragments
+fn same_page(a: &url::Url, b: &url::Url) -> bool {
+    let mut a = a.clone();
+    let mut b = b.clone();
+    a.set_fragment(None);
+    b.set_fragment(None);
+    a == b
+}
+
+/// Maps the ISO 639-3 codes used by the detector to two-letter codes
+fn iso_639_1(code: &str) -> Option<&'static str> {
+    let two_letter = match code {
+        "afr" => "af",
+        "aka" => "ak",
+        "amh" => "am",
+        "ara" => "ar",
+        "aze" => "az",
+        "bel" => "be",
+        "ben" => "bn",
+        "bul" => "bg",
+        "cat" => "ca",
+        "ces" => "cs",
+        "cmn" => "zh",
+        "dan" => "da",
+        "deu" => "de",
+        "ell" => "el",
+        "eng" => "en",
+        "epo" => "eo",
+        "est" => "et",
+        "fin" => "fi",
+        "fra" => "fr",
+        "guj" => "gu",
+        "heb" => "he",
+        "hin" => "hi",
+        "hrv" => "hr",
+        "hun" => "hu",
+        "hye" => "hy",
+        "ind" => "id",
+        "ita" => "it",
+        "jav" => "jv",
+        "jpn" => "ja",
+        "kan" => "kn",
+        "kat" => "ka",
+        "khm" => "km",
+        "kor" => "ko",
+        "lat" => "la",
+        "lav" => "lv",
+        "lit" => "lt",
+        "mal" => "ml",
+        "mar" => "mr",
+        "mkd" => "mk",
+        "mya" => "my",
+        "nep" => "ne",
+        "nld" => "nl",
+        "nob" => "nb",
+        "ori" => "or",
+        "pan" => "pa",
+        "pes" => "fa",
+        "pol" => "pl",
+        "por" => "pt",
+        "ron" => "ro",
+        "rus" => "ru",
+        "sin" => "si",
+        "slk" => "sk",
+        "slv" => "sl",
+        "sna" => "sn",
+        "spa" => "es",
+        "srp" => "sr",
+        "swe" => "sv",
+        "tam" => "ta",
+        "tel" => "te",
+        "tgl" => "tl",
+        "tha" => "th",
+        "tuk" => "tk",
+        "tur" => "tr",
+        "ukr" => "uk",
+        "urd" => "ur",
+        "uzb" => "uz",
+        "vie" => "vi",
+        "yid" => "yi",
+        "zul" => "zu",
+        _ => return None,
+    };
+    Some(two_letter)
+}
@@ -0,0 +1,247 @@
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// Kind of element or relation a link was discovered through
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkKind {
+    /// `<a href>`
+    Anchor,
+    /// `<area href>` inside an image map
+    Area,
+    /// `<iframe src>`
+    Iframe,
+    /// `<frame src>`
+    Frame,
+    /// `<link rel="next">`
+    Next,
+    /// `<link rel="prev">` or `<link rel="previous">`
+    Prev,
+    /// `<link rel="alternate">` (translations, feeds, other formats)
+    Alternate,
+    /// `<link rel="canonical">`
+    Canonical,
+    /// Any other `<link href>` (stylesheets, icons, preloads, ...)
+    Resource,
+    /// `<meta http-equiv="refresh" content="0; url=...">`
+    MetaRefresh,
+    /// URL-valued field in structured data (JSON, YAML, TOML, CSV)
+    Data,
+}
+
+impl LinkKind {
+    /// Link kinds followed by the crawler unless configured otherwise
+    pub fn default_follow() -> Vec<LinkKind> {
+        vec![
+            LinkKind::Anchor,
+            LinkKind::Area,
+            LinkKind::Iframe,
+            LinkKind::Frame,
+            LinkKind::Next,
+            LinkKind::Prev,
+            LinkKind::MetaRefresh,
+        ]
+    }
+}
+
+/// A link discovered in a document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Link {
+    /// Absolute URL resolved against the document base (the raw value if it cannot be resolved)
+    pub url: String,
+
+    /// The attribute value exactly as it appeared in the document
+    pub href: String,
+
+    /// How the link was discovered
+    pub kind: LinkKind,
+
+    /// Anchor text, or the alt/title attribute for elements without text
+    pub text: Option<String>,
+
+    /// Lower-cased values of the `rel` attribute
+    pub rel: Vec<String>,
+
+    /// Whether the link is marked `rel="nofollow"` (or the page has a robots nofollow meta tag)
+    pub nofollow: bool,
+
+    /// Name of the element the link came from (`a`, `link`, `iframe`, ...), or the field name for structured data
+    pub element: String,
+
+    /// Value of the `hreflang` attribute, if present
+    pub hreflang: Option<String>,
+}
+
+/// Extracts typed links from an HTML string
+///
+/// Relative URLs are resolved against `<base href>` if present, otherwise against `page_url`.
+pub fn extract_links(html: &str, page_url: Option<&str>) -> Vec<Link> {
+    let doc = Html::parse_document(html);
+    extract_links_from_document(&doc, page_url)
+}
+
+/// Extracts typed links from an already parsed document
+pub fn extract_links_from_document(doc: &Html, page_url: Option<&str>) -> Vec<Link> {
+    let base = document_base_url(doc, page_url);
+    let page_nofollow = has_robots_nofollow(doc);
+
+    let selector = Selector::parse(
+        "a[href], area[href], iframe[src], frame[src], link[href], meta[http-equiv][content]",
+    )
+    .unwrap();
+
+    let mut links = Vec::new();
+    for element in doc.select(&selector) {
+        let name = element.value().name();
+        let (href, kind) = match name {
+            "a" => (element.value().attr("href"), LinkKind::Anchor),
+            "area" => (element.value().attr("href"), LinkKind::Area),
+            "iframe" => (element.value().attr("src"), LinkKind::Iframe),
+            "frame" => (element.value().attr("src"), LinkKind::Frame),
+            "link" => (element.value().attr("href"), link_rel_kind(&element)),
+            "meta" => match meta_refresh_target(&element) {
+                Some(target) => (Some(target), LinkKind::MetaRefresh),
+                None => continue,
+            },
+            _ => continue,
+        };
+
+        let Some(href) = href.map(str::trim).filter(|h| !h.is_empty()) else {
+            continue;
+        };
+
+        let rel = rel_values(&element);
+        let nofollow = page_nofollow || rel.iter().any(|r| r == "nofollow");
+
+        links.push(Link {
+            url: resolve(base.as_ref(), href),
+            href: href.to_string(),
+            kind,
+            text: link_text(&element),
+            rel,
+            nofollow,
+            element: name.to_string(),
+            hreflang: element
+                .value()
+                .attr("hreflang")
+                .map(|s| s.trim().to_string()),
+        });
+    }
+
+    ::log::debug!("Link extractor found {} links", links.len());
+    links
+}
+
+/// Determines the base URL used to resolve relative links in the document
+pub fn document_base_url(doc: &Html, page_url: Option<&str>) -> Option<Url> {
+    let page_url = page_url.and_then(|u| Url::parse(u).ok());
+
+    let base_selector = Selector::parse("base[href]").unwrap();
+    let base_href = doc
+        .select(&base_selector)
+        .next()
+        .and_then(|e| e.value().attr("href"))
+        .map(str::trim);
+
+    match (base_href, page_url) {
+        (Some(href), Some(page)) => page.join(href).ok().or(Some(page)),
+        (Some(href), None) => Url::parse(href).ok(),
+        (None, page) => page,
+    }
+}
+
+/// Resolves an href against the base URL, falling back to the raw value
+pub(crate) fn resolve(base: Option<&Url>, href: &str) -> String {
+    match base {
+        Some(base) => base
+            .join(href)
+            .map(|u| u.to_string())
+            .unwrap_or_else(|_| href.to_string()),
+        None => href.to_string(),
+    }
+}
+
+/// Maps the `rel` attribute of a `<link>` element to a link kind
+fn link_rel_kind(element: &ElementRef) -> LinkKind {
+    let rel = rel_values(element);
+    if rel.iter().any(|r| r == "next") {
+        LinkKind::Next
+    } else if rel.iter().any(|r| r == "prev" || r == "previous") {
+        LinkKind::Prev
+    } else if rel.iter().any(|r| r == "alternate") {
+        LinkKind::Alternate
+    } else if rel.iter().any(|r| r == "canonical") {
+        LinkKind::Canonical
+    } else {
+        LinkKind::Resource
+    }
+}
+
+/// Splits the `rel` attribute into lower-cased values
+fn rel_values(element: &ElementRef) -> Vec<String> {
+    element
+        .value()
+        .attr("rel")
+        .map(|rel| {
+            rel.split_whitespace()
+                .map(|r| r.to_ascii_lowercase())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Extracts the target URL of a `<meta http-equiv="refresh">` tag
+fn meta_refresh_target<'a>(element: &ElementRef<'a>) -> Option<&'a str> {
+    let http_equiv = element.value().attr("http-equiv")?;
+    if !http_equiv.eq_ignore_ascii_case("refresh") {
+        return None;
+    }
+
+    // Content looks like `5; url=/next/page` (the url= prefix and quotes are optional)
+    let content = element.value().attr("content")?;
+    let (_, target) = content.split_once([';', ','])?;
+    let target = target.trim();
+    let target = match target.get(..4) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("url=") => &target[4..],
+        _ => target,
+    };
+    let target = target.trim().trim_matches(|c| c == '\'' || c == '"');
+
+    (!target.is_empty()).then_some(target)
+}
+
+/// Returns the visible text of a link, or its alt/title attribute
+fn link_text(element: &ElementRef) -> Option<String> {
+    let text = element
+        .text()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    if !text.is_empty() {
+        return Some(text);
+    }
+
+    element
+        .value()
+        .attr("alt")
+        .or_else(|| element.value().attr("title"))
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// Checks for `<meta name="robots" content="nofollow">`
+fn has_robots_nofollow(doc: &Html) -> bool {
+    let selector = Selector::parse("meta[name][content]").unwrap();
+    doc.select(&selector).any(|e| {
+        let name = e.value().attr("name").unwrap_or_default();
+        let content = e.value().attr("content").unwrap_or_default();
+        name.eq_ignore_ascii_case("robots")
+            && content
+                .split(',')
+                .any(|d| d.trim().eq_ignore_ascii_case("nofollow"))
+    })
+}
@@ -0,0 +1,223 @@
+use crate::parsers::text::{self, TextParserOptions};
+use crate::parsers::{
+    CodeBlock, Heading, Link, LinkKind, MarkdownMode, MediaItem, MediaKind, ParseResult,
+};
+use pulldown_cmark::{CodeBlockKind, Event, Options, Parser as MarkdownParser, Tag, TagEnd};
+
+/// A link whose text is still being collected
+struct OpenLink {
+    href: String,
+    text: String,
+}
+
+/// Parses Markdown source with default text options
+///
+/// Markup is removed, leaving headings, paragraphs, list items, table cells and
+/// code as plain text. Front matter is used for the title when it has a `title` key.
+pub fn parse(markdown: &str) -> ParseResult {
+    parse_with_options(markdown, &TextParserOptions::default())
+}
+
+/// Parses GitHub-flavored Markdown source with specific text options
+pub fn parse_with_options(markdown: &str, options: &TextParserOptions) -> ParseResult {
+    parse_with_mode(markdown, options, MarkdownMode::Gfm)
+}
+
+/// Parses Markdown source in the given dialect with specific text options
+pub fn parse_with_mode(
+    markdown: &str,
+    options: &TextParserOptions,
+    mode: MarkdownMode,
+) -> ParseResult {
+    let parser_options = match mode {
+        MarkdownMode::Gfm => {
+            Options::ENABLE_TABLES
+                | Options::ENABLE_FOOTNOTES
+                | Options::ENABLE_STRIKETHROUGH
+                | Options::ENABLE_TASKLISTS
+                | Options::ENABLE_HEADING_ATTRIBUTES
+                | Options::ENABLE_YAML_STYLE_METADATA_BLOCKS
+                | Options::ENABLE_PLUSES_DELIMITED_METADATA_BLOCKS
+        }
+        MarkdownMode::CommonMark => Options::empty(),
+    };
+
+    let mut blocks: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut headings = Vec::new();
+    let mut heading_level: Option<u8> = None;
+    let mut open_links: Vec<OpenLink> = Vec::new();
+    let mut link_details = Vec::new();
+    let mut front_matter = String::new();
+    let mut in_front_matter = false;
+    let mut open_images: Vec<(MediaItem, String)> = Vec::new();
+    let mut media = Vec::new();
+    let mut code_blocks = Vec::new();
+    let mut open_code: Option<(Option<String>, String)> = None;
+
+    for event in MarkdownParser::new_ext(markdown, parser_options) {
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => {
+                flush_block(&mut current, &mut blocks);
+                heading_level = Some(level as u8);
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                let text = collapse_whitespace(&current);
+                if let Some(level) = heading_level.take()
+                    && !text.is_empty()
+                {
+                    headings.push(Heading {
+                        level,
+                        text: text.clone(),
+                    });
+                }
+                current.clear();
+                blocks.push(text);
+            }
+            Event::Start(Tag::Link { dest_url, .. }) => open_links.push(OpenLink {
+                href: dest_url.to_string(),
+                text: String::new(),
+            }),
+            Event::End(TagEnd::Link) => {
+                if let Some(link) = open_links.pop() {
+                    link_details.push(markdown_link(link));
+                }
+            }
+            Event::Start(Tag::CodeBlock(kind)) => {
+                flush_block(&mut current, &mut blocks);
+                let language = match kind {
+                    CodeBlockKind::Fenced(info) => Some(info.to_string()),
+                    CodeBlockKind::Indented => None,
+                };
+                open_code = Some((language, String::new()));
+            }
+            Event::Text(text) if open_code.is_some() => {
+                if let Some((_, code)) = open_code.as_mut() {
+                    code.push_str(&text);
+                }
+                current.push_str(&text);
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                if let Some((language, code)) = open_code.take() {
+                    let heading = headings.last().map(|h: &Heading| h.text.as_str());
+                    code_blocks.push(CodeBlock::new(&code, language.as_deref(), heading));
+                }
+                flush_block(&mut current, &mut blocks);
+            }
+            Event::Start(Tag::Image {
+                dest_url, title, ..
+            }) => {
+                let mut image = MediaItem::new(MediaKind::Image, Some(dest_url.to_string()));
+                image.title = Some(title.to_string()).filter(|t| !t.is_empty());
+                open_images.push((image, String::new()));
+            }
+            Event::End(TagEnd::Image) => {
+                if let Some((mut image, alt)) = open_images.pop() {
+                    image.alt = Some(collapse_whitespace(&alt));
+                    media.push(image);
+                }
+            }
+            Event::Text(text) | Event::Code(text) if !open_images.is_empty() => {
+                // Image alt text isn't part of the readable content
+                if let Some((_, alt)) = open_images.last_mut() {
+                    alt.push_str(&text);
+                }
+            }
+            Event::Start(Tag::MetadataBlock(_)) => in_front_matter = true,
+            Event::End(TagEnd::MetadataBlock(_)) => in_front_matter = false,
+            Event::Text(text) if in_front_matter => front_matter.push_str(&text),
+            Event::Text(text)
+            | Event::Code(text)
+            | Event::InlineMath(text)
+            | Event::DisplayMath(text)
+                if open_images.is_empty() =>
+            {
+                append_text(&text, &mut current, &mut open_links);
+            }
+            Event::SoftBreak | Event::HardBreak => {
+                append_text("\n", &mut current, &mut open_links);
+            }
+            Event::End(TagEnd::TableCell) => current.push(' '),
+            Event::End(
+                TagEnd::Paragraph
+                | TagEnd::Item
+                | TagEnd::BlockQuote(_)
+                | TagEnd::TableHead
+                | TagEnd::TableRow
+                | TagEnd::FootnoteDefinition
+                | TagEnd::DefinitionListTitle
+                | TagEnd::DefinitionListDefinition,
+            )
+            | Event::Rule => flush_block(&mut current, &mut blocks),
+            // Raw HTML, footnote references and task markers carry no readable text
+            _ => {}
+        }
+    }
+    flush_block(&mut current, &mut blocks);
+
+    let content = text::parse_with_options(&blocks.join("\n\n"), options).content;
+    let links = link_details.iter().map(|l| l.href.clone()).collect();
+
+    let mut result = ParseResult::new(content, links);
+    result.title = front_matter_title(&front_matter)
+        .or_else(|| {
+            headings
+                .iter()
+                .find(|h| h.level == 1)
+                .map(|h| h.text.clone())
+        })
+        .or_else(|| headings.first().map(|h| h.text.clone()));
+    result.headings = headings;
+    result.link_details = link_details;
+    result.code_blocks = code_blocks;
+    result.media = media;
+    result
+}
+
+/// Appends text to the current block and any links being collected
+fn append_text(text: &str, current: &mut String, open_links: &mut [OpenLink]) {
+    current.push_str(text);
+    for link in open_links.iter_mut() {
+        link.text.push_str(text);
+    }
+}
+
+/// Moves the current block into the list of blocks
+fn flush_block(current: &mut String, blocks: &mut Vec<String>) {
+    if !current.trim().is_empty() {
+        blocks.push(current.trim().to_string());
+    }
+    current.clear();
+}
+
+/// Converts a collected Markdown link into a typed link
+fn markdown_link(link: OpenLink) -> Link {
+    let text = collapse_whitespace(&link.text);
+    Link {
+        url: link.href.clone(),
+        href: link.href,
+        kind: LinkKind::Anchor,
+        text: (!text.is_empty()).then_some(text),
+        rel: Vec::new(),
+        nofollow: false,
+        element: "a".to_string(),
+        hreflang: None,
+    }
+}
+
+/// Reads a `title` key from YAML or TOML front matter
+fn front_matter_title(front_matter: &str) -> Option<String> {
+    front_matter.lines().find_map(|line| {
+        let (key, value) = line.split_once([':', '='])?;
+        if key.trim() != "title" {
+            return None;
+        }
+        let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+        (!value.is_empty()).then(|| value.to_string())
+    })
+}
+
+/// Collapses runs of whitespace into single spaces
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
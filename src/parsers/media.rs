@@ -0,0 +1,278 @@
+use crate::parsers::links;
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// Kind of media element
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MediaKind {
+    /// `<img>`, including the image of a `<picture>`
+    Image,
+    /// `<video>`
+    Video,
+    /// `<audio>`
+    Audio,
+}
+
+/// An alternative resource for a media element (`srcset` entry or `<source>`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MediaSource {
+    /// Resolved URL of the resource
+    pub url: String,
+
+    /// Width or density descriptor from `srcset` (e.g. `640w`, `2x`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub descriptor: Option<String>,
+
+    /// MIME type from the `type` attribute
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+
+    /// Media query from the `media` attribute
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub media: Option<String>,
+}
+
+/// A media reference found in a document; the media itself isn't fetched
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MediaItem {
+    /// Kind of media element
+    pub kind: MediaKind,
+
+    /// Resolved URL of the main resource (`src`, or the first `<source>`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+
+    /// Alternative resources from `srcset` and `<source>` elements
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<MediaSource>,
+
+    /// Alt text; `None` when the attribute is missing, empty for decorative images
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alt: Option<String>,
+
+    /// Value of the `title` attribute
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+
+    /// Width from the `width` attribute, in pixels
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+
+    /// Height from the `height` attribute, in pixels
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+
+    /// Text of the enclosing `<figure>`'s `<figcaption>`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>,
+
+    /// Resolved URL of a video's poster image
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub poster: Option<String>,
+}
+
+impl MediaItem {
+    /// Creates a media item with only a kind and URL
+    pub fn new(kind: MediaKind, url: Option<String>) -> Self {
+        Self {
+            kind,
+            url,
+            sources: Vec::new(),
+            alt: None,
+            title: None,
+            width: None,
+            height: None,
+            caption: None,
+            poster: None,
+        }
+    }
+
+    /// Returns whether this is an image without an `alt` attribute
+    ///
+    /// Images with an empty `alt` are decorative and don't count as missing.
+    pub fn is_missing_alt(&self) -> bool {
+        self.kind == MediaKind::Image && self.alt.is_none()
+    }
+}
+
+/// Extracts images, videos and audio from a document, in document order
+///
+/// URLs are resolved against `<base href>` or `page_url`. Lazy-loading attributes
+/// (`data-src`, `data-srcset`) are used when `src` is missing or a placeholder.
+pub fn extract_media(doc: &Html, page_url: Option<&str>) -> Vec<MediaItem> {
+    let base = links::document_base_url(doc, page_url);
+    let selector = Selector::parse("img, video, audio").unwrap();
+
+    doc.select(&selector)
+        .map(|element| match element.value().name() {
+            "img" => image(element, base.as_ref()),
+            "video" => playable(element, MediaKind::Video, base.as_ref()),
+            _ => playable(element, MediaKind::Audio, base.as_ref()),
+        })
+        .collect()
+}
+
+/// Builds an image item, including the `<source>` elements of an enclosing `<picture>`
+fn image(img: ElementRef, base: Option<&Url>) -> MediaItem {
+    let el = img.value();
+    let src = el
+        .attr("src")
+        .filter(|src| !is_placeholder(src))
+        .or(el.attr("data-src"));
+
+    let mut item = MediaItem::new(MediaKind::Image, resolve(base, src));
+
+    if let Some(picture) = img
+        .parent()
+        .and_then(ElementRef::wrap)
+        .filter(|p| p.value().name() == "picture")
+    {
+        for source in child_sources(picture) {
+            let srcset = source.value().attr("srcset").unwrap_or_default();
+            for (url, descriptor) in parse_srcset(srcset, base) {
+                item.sources.push(MediaSource {
+                    url,
+                    descriptor,
+                    mime_type: attr(source, "type"),
+                    media: attr(source, "media"),
+                });
+            }
+        }
+    }
+
+    let srcset = el
+        .attr("srcset")
+        .or(el.attr("data-srcset"))
+        .unwrap_or_default();
+    for (url, descriptor) in parse_srcset(srcset, base) {
+        item.sources.push(MediaSource {
+            url,
+            descriptor,
+            mime_type: None,
+            media: None,
+        });
+    }
+
+    item.alt = el.attr("alt").map(|alt| alt.trim().to_string());
+    item.title = attr(img, "title");
+    item.width = dimension(img, "width");
+    item.height = dimension(img, "height");
+    item.caption = figure_caption(img);
+    item
+}
+
+/// Builds a video or audio item from its `src` and `<source>` children
+fn playable(element: ElementRef, kind: MediaKind, base: Option<&Url>) -> MediaItem {
+    let sources: Vec<MediaSource> = child_sources(element)
+        .filter_map(|source| {
+            Some(MediaSource {
+                url: resolve(base, source.value().attr("src"))?,
+                descriptor: None,
+                mime_type: attr(source, "type"),
+                media: attr(source, "media"),
+            })
+        })
+        .collect();
+
+    let url = resolve(base, element.value().attr("src"))
+        .or_else(|| sources.first().map(|s| s.url.clone()));
+
+    let mut item = MediaItem::new(kind, url);
+    item.sources = sources;
+    item.title = attr(element, "title").or_else(|| attr(element, "aria-label"));
+    item.width = dimension(element, "width");
+    item.height = dimension(element, "height");
+    item.caption = figure_caption(element);
+    item.poster = resolve(base, element.value().attr("poster"));
+    item
+}
+
+/// Returns the `<source>` children of a `<picture>`, `<video>` or `<audio>`
+fn child_sources<'a>(element: ElementRef<'a>) -> impl Iterator<Item = ElementRef<'a>> {
+    element
+        .children()
+        .filter_map(ElementRef::wrap)
+        .filter(|e| e.value().name() == "source")
+}
+
+/// Parses a `srcset` attribute into resolved URLs and descriptors
+fn parse_srcset(srcset: &str, base: Option<&Url>) -> Vec<(String, Option<String>)> {
+    // Candidates are separated by commas, but URLs (e.g. data: URLs) may contain commas too
+    let mut candidates = Vec::new();
+    let mut rest = srcset.trim();
+    while !rest.is_empty() {
+        let url_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let mut url = &rest[..url_end];
+        rest = &rest[url_end..];
+
+        let descriptor = if url.ends_with(',') {
+            url = url.trim_end_matches(',');
+            None
+        } else {
+            let descriptor_end = rest.find(',').unwrap_or(rest.len());
+            let descriptor = rest[..descriptor_end].trim();
+            rest = &rest[descriptor_end..];
+            Some(descriptor.to_string()).filter(|d| !d.is_empty())
+        };
+        rest = rest.trim_start_matches(',').trim_start();
+
+        if !url.is_empty() && !is_placeholder(url) {
+            candidates.push((links::resolve(base, url), descriptor));
+        }
+    }
+    candidates
+}
+
+/// Returns the caption of the `<figure>` an element belongs to
+fn figure_caption(element: ElementRef) -> Option<String> {
+    let figure = element
+        .ancestors()
+        .filter_map(ElementRef::wrap)
+        .find(|e| e.value().name() == "figure")?;
+    let caption_selector = Selector::parse("figcaption").unwrap();
+    let caption = figure
+        .select(&caption_selector)
+        .next()?
+        .text()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    (!caption.is_empty()).then_some(caption)
+}
+
+/// Reads a pixel dimension, accepting values like `640` and `640px`
+fn dimension(element: ElementRef, name: &str) -> Option<u32> {
+    element
+        .value()
+        .attr(name)?
+        .trim()
+        .trim_end_matches("px")
+        .parse()
+        .ok()
+}
+
+/// Reads a trimmed, non-empty attribute
+fn attr(element: ElementRef, name: &str) -> Option<String> {
+    element
+        .value()
+        .attr(name)
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+}
+
+/// Resolves an optional, non-empty URL attribute
+fn resolve(base: Option<&Url>, href: Option<&str>) -> Option<String> {
+    href.map(str::trim)
+        .filter(|h| !h.is_empty())
+        .map(|h| links::resolve(base, h))
+}
+
+/// Returns whether a `src` is an inline placeholder used by lazy loaders
+fn is_placeholder(src: &str) -> bool {
+    src.trim_start().starts_with("data:")
+}
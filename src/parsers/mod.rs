@@ -1,16 +1,73 @@
+pub mod code;
+pub mod content_type;
+pub mod docx;
+pub mod encoding;
+pub mod epub;
 pub mod html;
+pub mod language;
+pub mod links;
+pub mod markdown;
+pub mod media;
+pub mod odf;
+pub mod office;
+pub mod options;
+pub mod pptx;
+pub mod registry;
+pub mod rst;
+pub mod schema_org;
+pub mod structured;
+pub mod tables;
 pub mod text;
+pub mod xlsx;
+
+use crate::results::PageMetadata;
+pub use code::CodeBlock;
+pub use content_type::{ContentTypeDetector, ParserMapping};
+pub use encoding::EncodingSource;
+pub use language::LanguageSource;
+pub use links::{Link, LinkKind};
+pub use media::{MediaItem, MediaKind};
+pub use options::{
+    EpubMode, MarkdownMode, ParserOptions, ParserOptionsOverride, ParserOptionsSelector,
+};
+pub use registry::{BuiltinParser, ContentParser, ParserMatcher, ParserRegistry};
+pub use schema_org::{SchemaObject, SchemaSource};
+use serde::{Deserialize, Serialize};
+pub use tables::Table;
 
 #[cfg(test)]
 mod tests;
 
 /// Enum to represent different types of content parsers
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ParserType {
     /// HTML parser
     Html,
     /// Plain text parser
     Text,
+    /// Markdown source parser
+    Markdown,
+    /// reStructuredText source parser
+    Rst,
+    /// JSON parser
+    Json,
+    /// YAML parser
+    Yaml,
+    /// TOML parser
+    Toml,
+    /// CSV and TSV parser
+    Csv,
+    /// Word document parser (`.docx`)
+    Docx,
+    /// Excel workbook parser (`.xlsx`)
+    Xlsx,
+    /// PowerPoint presentation parser (`.pptx`)
+    Pptx,
+    /// OpenDocument text, spreadsheet and presentation parser
+    OpenDocument,
+    /// EPUB e-book parser
+    Epub,
     /// PDF parser (placeholder for future implementation)
     Pdf,
     /// Other formats (placeholder for future implementation)
@@ -20,20 +77,63 @@ pub enum ParserType {
 impl ParserType {
     /// Determines the parser type based on the URL or file path
     pub fn from_url(url: &str) -> Self {
-        // Text files should not be parsed for links
-        if url.ends_with(".txt") {
+        if url.contains("/_sources/") {
+            // Sphinx serves document sources as `.rst.txt` or `.md.txt`
+            if url.ends_with(".md.txt") || url.ends_with(".md") {
+                ::log::debug!("Classifying as Markdown (_sources): {}", url);
+                ParserType::Markdown
+            } else {
+                ::log::debug!("Classifying as reStructuredText (_sources): {}", url);
+                ParserType::Rst
+            }
+        } else if url.ends_with(".md") || url.ends_with(".markdown") {
+            ::log::debug!("Classifying as Markdown: {}", url);
+            ParserType::Markdown
+        } else if url.ends_with(".rst") {
+            ::log::debug!("Classifying as reStructuredText: {}", url);
+            ParserType::Rst
+        } else if url.ends_with(".txt") {
+            // Text files should not be parsed for links
             ::log::debug!("Classifying as Text: {}", url);
             ParserType::Text
+        } else if url.ends_with(".json") || url.ends_with(".jsonld") {
+            ::log::debug!("Classifying as JSON: {}", url);
+            ParserType::Json
         } else if url.ends_with(".yaml") || url.ends_with(".yml") {
-            ::log::debug!("Classifying as Text (YAML): {}", url);
-            ParserType::Text
+            ::log::debug!("Classifying as YAML: {}", url);
+            ParserType::Yaml
+        } else if url.ends_with(".toml") {
+            ::log::debug!("Classifying as TOML: {}", url);
+            ParserType::Toml
+        } else if url.ends_with(".csv") || url.ends_with(".tsv") {
+            ::log::debug!("Classifying as CSV: {}", url);
+            ParserType::Csv
+        } else if url.ends_with(".docx") {
+            ::log::debug!("Classifying as DOCX: {}", url);
+            ParserType::Docx
+        } else if url.ends_with(".xlsx") {
+            ::log::debug!("Classifying as XLSX: {}", url);
+            ParserType::Xlsx
+        } else if url.ends_with(".pptx") {
+            ::log::debug!("Classifying as PPTX: {}", url);
+            ParserType::Pptx
+        } else if url.ends_with(".odt") || url.ends_with(".ods") || url.ends_with(".odp") {
+            ::log::debug!("Classifying as OpenDocument: {}", url);
+            ParserType::OpenDocument
+        } else if url.ends_with(".epub") {
+            ::log::debug!("Classifying as EPUB: {}", url);
+            ParserType::Epub
         } else if url.ends_with(".pdf") {
             ::log::debug!("Classifying as PDF: {}", url);
             ParserType::Pdf
-        } else if url.contains("/_sources/") {
-            // Special rule from UrlFilter.should_parse_links
-            ::log::debug!("Classifying as Text (_sources): {}", url);
-            ParserType::Text
+        } else if url.ends_with(".zip")
+            || url.ends_with(".tar")
+            || url.ends_with(".tgz")
+            || url.ends_with(".gz")
+        {
+            // Archives are downloaded and expanded into their files
+            ::log::debug!("Classifying as Other (archive): {}", url);
+            ParserType::Other
         } else if url.ends_with(".jpg")
             || url.ends_with(".jpeg")
             || url.ends_with(".png")
@@ -51,31 +151,118 @@ impl ParserType {
         }
     }
 
+    /// Determines the parser type from a Content-Type header value
+    ///
+    /// Returns `None` for generic types such as `application/octet-stream` and
+    /// `text/plain` that don't identify the format.
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        content_type::parser_for_mime_type(&content_type::mime_type(content_type))
+    }
+
+    /// Determines the parser type from the leading bytes of the content
+    pub fn sniff(bytes: &[u8]) -> Option<Self> {
+        content_type::sniff_bytes(bytes)
+    }
+
     /// Returns if the parser should extract links
     pub fn should_extract_links(&self) -> bool {
-        matches!(self, ParserType::Html)
+        matches!(
+            self,
+            ParserType::Html | ParserType::Markdown | ParserType::Rst
+        )
     }
 }
 
+/// A section heading found in a document
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Heading {
+    /// Heading level, from 1 (top level) to 6
+    pub level: u8,
+    /// Heading text
+    pub text: String,
+}
+
 /// Result of parsing content
 pub struct ParseResult {
+    /// Document title (if available)
+    pub title: Option<String>,
+    /// Section headings in document order
+    pub headings: Vec<Heading>,
     /// Extracted text content
     pub content: String,
     /// Extracted links (if applicable)
     pub links: Vec<String>,
+    /// Typed links with kind, anchor text and rel attributes (if applicable)
+    pub link_details: Vec<Link>,
+    /// Tables with their headers and rows (if applicable)
+    pub tables: Vec<Table>,
+    /// Code blocks kept verbatim, with their language and heading (if applicable)
+    pub code_blocks: Vec<CodeBlock>,
+    /// Images, video and audio referenced by the document (if applicable)
+    pub media: Vec<MediaItem>,
+    /// Schema.org objects from JSON-LD and microdata (if applicable)
+    pub structured_data: Vec<SchemaObject>,
+    /// Breadcrumb trail leading to the document, outermost section first
+    pub section_path: Vec<String>,
+    /// Language of the document as a lower-cased BCP 47 tag (if known)
+    pub language: Option<String>,
+    /// Metadata gathered while decoding and parsing the content
+    pub metadata: PageMetadata,
 }
 
 impl ParseResult {
     /// Creates a new parse result with the given content and links
     pub fn new(content: String, links: Vec<String>) -> Self {
-        Self { content, links }
+        Self {
+            title: None,
+            headings: Vec::new(),
+            content,
+            links,
+            link_details: Vec::new(),
+            tables: Vec::new(),
+            code_blocks: Vec::new(),
+            media: Vec::new(),
+            structured_data: Vec::new(),
+            section_path: Vec::new(),
+            language: None,
+            metadata: PageMetadata::default(),
+        }
     }
 
     /// Creates a new parse result with content only (no links)
     pub fn content_only(content: String) -> Self {
-        Self {
-            content,
-            links: Vec::new(),
+        Self::new(content, Vec::new())
+    }
+
+    /// Records a detected language on the result
+    pub fn set_language(&mut self, language: language::DetectedLanguage) {
+        self.language = Some(language.tag);
+        self.metadata.language_source = Some(language.source);
+    }
+
+    /// Resolves relative typed links and media URLs against the URL the content was fetched from
+    pub fn resolve_links(&mut self, page_url: &str) {
+        let Ok(base) = url::Url::parse(page_url) else {
+            return;
+        };
+
+        let media_urls = self.media.iter_mut().flat_map(|item| {
+            item.url
+                .iter_mut()
+                .chain(item.poster.iter_mut())
+                .chain(item.sources.iter_mut().map(|source| &mut source.url))
+        });
+        for url in self
+            .link_details
+            .iter_mut()
+            .map(|link| &mut link.url)
+            .chain(media_urls)
+        {
+            if url::Url::parse(url).is_err()
+                && let Ok(resolved) = base.join(url)
+            {
+                *url = resolved.to_string();
+            }
         }
     }
 }
@@ -86,9 +273,23 @@ pub struct Parser;
 impl Parser {
     /// Parse content based on the parser type
     pub fn parse(content: &str, parser_type: ParserType) -> ParseResult {
-        match parser_type {
+        let result = match parser_type {
             ParserType::Html => html::parse(content),
             ParserType::Text => text::parse(content),
+            ParserType::Markdown => markdown::parse(content),
+            ParserType::Rst => rst::parse(content),
+            ParserType::Json | ParserType::Yaml | ParserType::Toml | ParserType::Csv => {
+                Self::parse_structured(
+                    content,
+                    parser_type,
+                    &ParserOptions::with_text_options(text::TextParserOptions::default()),
+                )
+            }
+            ParserType::Docx
+            | ParserType::Xlsx
+            | ParserType::Pptx
+            | ParserType::OpenDocument
+            | ParserType::Epub => Self::package_as_text(parser_type),
             ParserType::Pdf => {
                 // Placeholder for PDF parsing (not implemented yet)
                 ParseResult::content_only("PDF parsing not implemented yet".to_string())
@@ -97,7 +298,8 @@ impl Parser {
                 // Default handling for unknown formats - just treat as plain text
                 text::parse(content)
             }
-        }
+        };
+        Self::with_detected_language(result, parser_type)
     }
 
     /// Parse content with specific text parser options
@@ -106,9 +308,55 @@ impl Parser {
         parser_type: ParserType,
         text_options: &text::TextParserOptions,
     ) -> ParseResult {
-        match parser_type {
-            ParserType::Html => html::parse(content),
+        Self::parse_with_options(
+            content,
+            parser_type,
+            &ParserOptions::with_text_options(*text_options),
+        )
+    }
+
+    /// Parse content with specific parser options
+    pub fn parse_with_options(
+        content: &str,
+        parser_type: ParserType,
+        options: &ParserOptions,
+    ) -> ParseResult {
+        Self::parse_document(content, parser_type, None, options)
+    }
+
+    /// Parse content fetched from `url` with specific parser options, resolving links against it
+    pub fn parse_with_url_and_options(
+        content: &str,
+        parser_type: ParserType,
+        url: &str,
+        options: &ParserOptions,
+    ) -> ParseResult {
+        Self::parse_document(content, parser_type, Some(url), options)
+    }
+
+    /// Shared implementation for parsing with options, with the URL of the content if known
+    fn parse_document(
+        content: &str,
+        parser_type: ParserType,
+        url: Option<&str>,
+        options: &ParserOptions,
+    ) -> ParseResult {
+        let text_options = &options.text;
+        let mut result = match parser_type {
+            ParserType::Html => html::parse_with_options(content, url, options),
             ParserType::Text => text::parse_with_options(content, text_options),
+            ParserType::Markdown => {
+                markdown::parse_with_mode(content, text_options, options.markdown_mode)
+            }
+            ParserType::Rst => rst::parse_with_options(content, text_options),
+            ParserType::Json | ParserType::Yaml | ParserType::Toml | ParserType::Csv => {
+                Self::parse_structured(content, parser_type, options)
+            }
+            ParserType::Docx
+            | ParserType::Xlsx
+            | ParserType::Pptx
+            | ParserType::OpenDocument
+            | ParserType::Epub => Self::package_as_text(parser_type),
             ParserType::Pdf => {
                 // Placeholder for PDF parsing (not implemented yet)
                 ParseResult::content_only("PDF parsing not implemented yet".to_string())
@@ -117,13 +365,116 @@ impl Parser {
                 // Default handling for unknown formats - just treat as plain text
                 text::parse_with_options(content, text_options)
             }
+        };
+
+        if !options.extract_links {
+            result.links.clear();
+            result.link_details.clear();
+        }
+        if parser_type != ParserType::Html
+            && let Some(url) = url
+        {
+            result.resolve_links(url);
+        }
+        Self::with_detected_language(result, parser_type)
+    }
+
+    /// Office documents and e-books are ZIP packages, which can't be parsed once decoded as text
+    fn package_as_text(parser_type: ParserType) -> ParseResult {
+        ::log::debug!("{:?} documents must be parsed from bytes", parser_type);
+        ParseResult::content_only(String::new())
+    }
+
+    /// Detects the language of prose documents from their text when the markup doesn't declare it
+    fn with_detected_language(mut result: ParseResult, parser_type: ParserType) -> ParseResult {
+        let is_prose = matches!(
+            parser_type,
+            ParserType::Text
+                | ParserType::Markdown
+                | ParserType::Rst
+                | ParserType::Docx
+                | ParserType::Xlsx
+                | ParserType::Pptx
+                | ParserType::OpenDocument
+                | ParserType::Epub
+                | ParserType::Other
+        );
+        if is_prose
+            && result.language.is_none()
+            && let Some(detected) = language::detect_from_text(&result.content)
+        {
+            result.set_language(detected);
+        }
+        result
+    }
+
+    /// Parse structured data, falling back to plain text if it is malformed
+    fn parse_structured(
+        content: &str,
+        parser_type: ParserType,
+        options: &ParserOptions,
+    ) -> ParseResult {
+        let structured_options = structured::StructuredParserOptions {
+            extract_links: options.extract_links,
+        };
+        match structured::parse(content, parser_type, &structured_options) {
+            Ok(result) => result,
+            Err(e) => {
+                ::log::debug!("Parsing as {:?} failed, using text: {}", parser_type, e);
+                text::parse_with_options(content, &options.text)
+            }
         }
     }
 
+    /// Parse raw bytes, detecting and recording their character encoding
+    pub fn parse_bytes(
+        bytes: &[u8],
+        parser_type: ParserType,
+        content_type: Option<&str>,
+    ) -> ParseResult {
+        Self::parse_bytes_with_text_options(
+            bytes,
+            parser_type,
+            content_type,
+            &text::TextParserOptions::default(),
+        )
+    }
+
+    /// Parse raw bytes with specific text parser options
+    pub fn parse_bytes_with_text_options(
+        bytes: &[u8],
+        parser_type: ParserType,
+        content_type: Option<&str>,
+        text_options: &text::TextParserOptions,
+    ) -> ParseResult {
+        Self::parse_bytes_with_options(
+            bytes,
+            parser_type,
+            content_type,
+            &ParserOptions::with_text_options(*text_options),
+        )
+    }
+
+    /// Parse raw bytes with specific parser options
+    pub fn parse_bytes_with_options(
+        bytes: &[u8],
+        parser_type: ParserType,
+        content_type: Option<&str>,
+        options: &ParserOptions,
+    ) -> ParseResult {
+        BuiltinParser(parser_type).parse_bytes(bytes, content_type, None, options)
+    }
+
     /// Determine parser type from URL and then parse content
     pub fn parse_from_url(content: &str, url: &str) -> ParseResult {
-        let parser_type = ParserType::from_url(url);
-        Self::parse(content, parser_type)
+        match ParserType::from_url(url) {
+            ParserType::Html => html::parse_with_url(content, url),
+            parser_type => {
+                let mut result = Self::parse(content, parser_type);
+                result.resolve_links(url);
+                result
+            }
+        }
     }
 
     /// Determine parser type from URL and then parse content with text options
@@ -132,7 +483,19 @@ impl Parser {
         url: &str,
         text_options: &text::TextParserOptions,
     ) -> ParseResult {
-        let parser_type = ParserType::from_url(url);
-        Self::parse_with_text_options(content, parser_type, text_options)
+        Self::parse_from_url_with_options(
+            content,
+            url,
+            &ParserOptions::with_text_options(*text_options),
+        )
+    }
+
+    /// Determine parser type from URL and then parse content with parser options
+    pub fn parse_from_url_with_options(
+        content: &str,
+        url: &str,
+        options: &ParserOptions,
+    ) -> ParseResult {
+        Self::parse_document(content, ParserType::from_url(url), Some(url), options)
     }
 }
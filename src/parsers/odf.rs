@@ -0,0 +1,233 @@
+use crate::parsers::office::{
+    DocumentBuilder, OfficeError, Package, attr, children, collapse_whitespace, is,
+    table_from_cells,
+};
+use crate::parsers::{ParseResult, ParserOptions};
+use roxmltree::Node;
+
+/// Content part of an OpenDocument package
+const CONTENT_PART: &str = "content.xml";
+
+/// Most times a non-empty row or cell is repeated
+const MAX_REPEAT: usize = 1000;
+
+/// Parses an OpenDocument text, spreadsheet or presentation (`.odt`, `.ods`, `.odp`)
+///
+/// Text documents are read like Word documents. Each spreadsheet table becomes a
+/// heading with the sheet name followed by a table whose first row is the header
+/// row; presentation pages are read frame by frame. Notes, annotations and
+/// tracked changes are left out.
+pub(crate) fn parse(
+    package: &mut Package,
+    options: &ParserOptions,
+) -> Result<ParseResult, OfficeError> {
+    let xml = package.read_required(CONTENT_PART)?;
+    let doc = roxmltree::Document::parse(&xml)?;
+    let body = doc
+        .descendants()
+        .find(|n| is(*n, "body"))
+        .ok_or("Document has no body")?;
+
+    let mut reader = Reader {
+        builder: DocumentBuilder::with_title(package.title("meta.xml")?),
+        spreadsheet: false,
+    };
+    for content in body.children().filter(Node::is_element) {
+        reader.spreadsheet = is(content, "spreadsheet");
+        reader.blocks(content);
+    }
+    Ok(reader.builder.finish(options))
+}
+
+/// Reads OpenDocument content into a document builder
+struct Reader {
+    builder: DocumentBuilder,
+    /// Whether tables are spreadsheets, which have a header row and a name
+    spreadsheet: bool,
+}
+
+impl Reader {
+    /// Reads the block-level content of a container in order
+    fn blocks(&mut self, container: Node) {
+        for node in container.children().filter(Node::is_element) {
+            match node.tag_name().name() {
+                "h" => {
+                    let level = attr(node, "outline-level")
+                        .and_then(|l| l.parse().ok())
+                        .unwrap_or(1);
+                    let text = self.inline_text(node);
+                    self.builder.heading(level, &text);
+                }
+                "p" => {
+                    let text = self.inline_text(node);
+                    self.builder.paragraph(&text);
+                    // Frames anchored in a paragraph hold their own blocks
+                    let frames = node.descendants().filter(|n| {
+                        is(*n, "frame") && n.ancestors().skip(1).find(|a| is(*a, "p")) == Some(node)
+                    });
+                    for frame in frames {
+                        self.frame(frame);
+                    }
+                }
+                "table" => self.table(node),
+                "frame" => self.frame(node),
+                // Containers of ordinary blocks
+                "list" | "list-item" | "list-header" | "section" | "page" | "g"
+                | "custom-shape" | "text-box" => self.blocks(node),
+                _ => {}
+            }
+        }
+    }
+
+    /// Reads a drawing frame, as a heading if it is a presentation title
+    fn frame(&mut self, frame: Node) {
+        if attr(frame, "class").is_some_and(|c| c == "title") {
+            let text: Vec<String> = frame
+                .descendants()
+                .filter(|n| is(*n, "p"))
+                .map(|p| self.inline_text(p))
+                .collect();
+            self.builder.heading(1, &text.join(" "));
+        } else {
+            self.blocks(frame);
+        }
+    }
+
+    /// Collects the text of a paragraph or heading, recording hyperlinks
+    fn inline_text(&mut self, node: Node) -> String {
+        let mut text = String::new();
+        for child in node.children() {
+            if child.is_text() {
+                text.push_str(child.text().unwrap_or_default());
+                continue;
+            }
+            match child.tag_name().name() {
+                "s" => {
+                    let count = attr(child, "c").and_then(|c| c.parse().ok()).unwrap_or(1);
+                    text.push_str(&" ".repeat(count));
+                }
+                "tab" => text.push('\t'),
+                "line-break" => text.push('\n'),
+                "a" => {
+                    let link_text = self.inline_text(child);
+                    if let Some(href) = attr(child, "href") {
+                        self.builder.link(href, &link_text, "text:a");
+                    }
+                    text.push_str(&link_text);
+                }
+                // Notes, comments and anchored frames aren't running text
+                "note" | "annotation" | "frame" | "tracked-changes" => {}
+                _ => text.push_str(&self.inline_text(child)),
+            }
+        }
+        text
+    }
+
+    /// Reads a table, expanding repeated rows and columns
+    fn table(&mut self, table: Node) {
+        let mut rows = Vec::new();
+        let mut header_rows = 0;
+        self.table_rows(table, &mut rows, &mut header_rows, false);
+
+        let header_rows = if header_rows == 0 && self.spreadsheet {
+            1
+        } else {
+            header_rows
+        };
+        if self.spreadsheet
+            && let Some(name) = attr(table, "name")
+        {
+            self.builder.heading(1, name);
+        }
+        self.builder
+            .table(table_from_cells(None, &rows, header_rows));
+    }
+
+    /// Collects the rows of a table or row group
+    fn table_rows(
+        &mut self,
+        container: Node,
+        rows: &mut Vec<Vec<(String, usize, usize)>>,
+        header_rows: &mut usize,
+        in_header: bool,
+    ) {
+        for node in container.children().filter(Node::is_element) {
+            match node.tag_name().name() {
+                "table-row" => {
+                    let cells = self.table_row(node);
+                    let repeat = if cells.is_empty() {
+                        // Spreadsheets pad sheets with thousands of empty rows
+                        1
+                    } else {
+                        repeat_count(node, "number-rows-repeated")
+                    };
+                    for _ in 0..repeat {
+                        rows.push(cells.clone());
+                        if in_header {
+                            *header_rows += 1;
+                        }
+                    }
+                }
+                "table-header-rows" => self.table_rows(node, rows, header_rows, true),
+                "table-rows" | "table-row-group" => {
+                    self.table_rows(node, rows, header_rows, in_header)
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Reads the cells of a row as (text, column span, row span)
+    ///
+    /// Covered cells are skipped since the spanning cell fills their positions.
+    /// Trailing empty cells are dropped.
+    fn table_row(&mut self, row: Node) -> Vec<(String, usize, usize)> {
+        let mut cells: Vec<(String, usize, usize, usize)> = Vec::new();
+        for cell in children(row, "table-cell") {
+            let span = |name| {
+                attr(cell, name)
+                    .and_then(|v| v.parse::<usize>().ok())
+                    .unwrap_or(1)
+                    .max(1)
+            };
+            let paragraphs: Vec<String> = cell
+                .descendants()
+                .filter(|n| is(*n, "p") || is(*n, "h"))
+                .map(|p| self.inline_text(p))
+                .collect();
+            let text = collapse_whitespace(&paragraphs.join(" "));
+            let repeat = repeat_count(cell, "number-columns-repeated");
+            cells.push((
+                text,
+                span("number-columns-spanned"),
+                span("number-rows-spanned"),
+                repeat,
+            ));
+        }
+
+        while cells.last().is_some_and(|(text, ..)| text.is_empty()) {
+            cells.pop();
+        }
+
+        let mut expanded = Vec::new();
+        for (text, colspan, rowspan, repeat) in cells {
+            let repeat = if text.is_empty() {
+                repeat
+            } else {
+                repeat.min(MAX_REPEAT)
+            };
+            for _ in 0..repeat {
+                expanded.push((text.clone(), colspan, rowspan));
+            }
+        }
+        expanded
+    }
+}
+
+/// Reads a repetition attribute, capped so padding can't blow up the table
+fn repeat_count(node: Node, name: &str) -> usize {
+    attr(node, name)
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(1)
+        .clamp(1, MAX_REPEAT)
+}
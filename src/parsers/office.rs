@@ -0,0 +1,386 @@
+use crate::parsers::tables::MAX_COLSPAN;
+use crate::parsers::text;
+use crate::parsers::{Heading, Link, LinkKind, ParseResult, ParserOptions, ParserType, Table};
+use crate::parsers::{docx, odf, pptx, xlsx};
+use roxmltree::Node;
+use std::collections::HashMap;
+use std::error::Error;
+use std::io::{Cursor, Read};
+use zip::ZipArchive;
+
+/// Largest package part read, so a small archive can't expand to fill memory
+const MAX_PART_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Namespace of relationship ID attributes (`r:id`)
+const RELATIONSHIPS_NAMESPACE: &str =
+    "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+
+/// Error type for office document parsing
+pub type OfficeError = Box<dyn Error + Send + Sync>;
+
+/// Parses a DOCX, XLSX, PPTX or OpenDocument file
+///
+/// Text is extracted in reading order, with headings, tables and hyperlinks.
+/// Returns an error if the bytes aren't a package of the given type.
+pub fn parse(
+    bytes: &[u8],
+    parser_type: ParserType,
+    options: &ParserOptions,
+) -> Result<ParseResult, OfficeError> {
+    let mut package = Package::open(bytes)?;
+    let mut result = match parser_type {
+        ParserType::Docx => docx::parse(&mut package, options)?,
+        ParserType::Xlsx => xlsx::parse(&mut package, options)?,
+        ParserType::Pptx => pptx::parse(&mut package, options)?,
+        ParserType::OpenDocument => odf::parse(&mut package, options)?,
+        _ => return Err(format!("{:?} is not an office document type", parser_type).into()),
+    };
+
+    if !options.extract_links {
+        result.links.clear();
+        result.link_details.clear();
+    }
+    Ok(result)
+}
+
+/// Identifies the office format of a ZIP archive from the parts it contains
+pub fn sniff(bytes: &[u8]) -> Option<ParserType> {
+    let mut package = Package::open(bytes).ok()?;
+    if package.has("word/document.xml") {
+        Some(ParserType::Docx)
+    } else if package.has("xl/workbook.xml") {
+        Some(ParserType::Xlsx)
+    } else if package.has("ppt/presentation.xml") {
+        Some(ParserType::Pptx)
+    } else {
+        let mimetype = package.read("mimetype").ok()??;
+        mimetype
+            .trim()
+            .starts_with("application/vnd.oasis.opendocument.")
+            .then_some(ParserType::OpenDocument)
+    }
+}
+
+/// A ZIP-based document package (OOXML or OpenDocument)
+pub(crate) struct Package<'a> {
+    archive: ZipArchive<Cursor<&'a [u8]>>,
+}
+
+/// A relationship from an OOXML part to another part or an external URL
+#[derive(Debug, Clone)]
+pub(crate) struct Relationship {
+    /// Part name within the package, or the URL for external targets
+    pub target: String,
+    /// Whether the target is outside the package (e.g. a hyperlink)
+    pub external: bool,
+}
+
+impl<'a> Package<'a> {
+    /// Opens a package from its bytes
+    pub fn open(bytes: &'a [u8]) -> Result<Self, OfficeError> {
+        Ok(Self {
+            archive: ZipArchive::new(Cursor::new(bytes))?,
+        })
+    }
+
+    /// Returns whether the package contains a part
+    pub fn has(&self, name: &str) -> bool {
+        self.archive.index_for_name(name).is_some()
+    }
+
+    /// Reads a part as text, or `None` if the package doesn't contain it
+    pub fn read(&mut self, name: &str) -> Result<Option<String>, OfficeError> {
+        let file = match self.archive.by_name(name) {
+            Ok(file) => file,
+            Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut text = String::new();
+        file.take(MAX_PART_SIZE).read_to_string(&mut text)?;
+        Ok(Some(text))
+    }
+
+    /// Reads a part that must be present
+    pub fn read_required(&mut self, name: &str) -> Result<String, OfficeError> {
+        self.read(name)?
+            .ok_or_else(|| format!("Package has no {} part", name).into())
+    }
+
+    /// Reads the relationships of an OOXML part, keyed by relationship ID
+    ///
+    /// Internal targets are resolved to part names relative to the package root.
+    pub fn relationships(
+        &mut self,
+        part: &str,
+    ) -> Result<HashMap<String, Relationship>, OfficeError> {
+        let (dir, file) = part.rsplit_once('/').unwrap_or(("", part));
+        let rels_name = if dir.is_empty() {
+            format!("_rels/{}.rels", file)
+        } else {
+            format!("{}/_rels/{}.rels", dir, file)
+        };
+        let Some(xml) = self.read(&rels_name)? else {
+            return Ok(HashMap::new());
+        };
+
+        let doc = roxmltree::Document::parse(&xml)?;
+        let mut relationships = HashMap::new();
+        for node in doc.descendants().filter(|n| is(*n, "Relationship")) {
+            let (Some(id), Some(target)) = (attr(node, "Id"), attr(node, "Target")) else {
+                continue;
+            };
+            let external = attr(node, "TargetMode") == Some("External");
+            let target = if external {
+                target.to_string()
+            } else {
+                resolve_part(dir, target)
+            };
+            relationships.insert(id.to_string(), Relationship { target, external });
+        }
+        Ok(relationships)
+    }
+
+    /// Reads the `dc:title` from OOXML core properties or OpenDocument metadata
+    pub fn title(&mut self, part: &str) -> Result<Option<String>, OfficeError> {
+        let Some(xml) = self.read(part)? else {
+            return Ok(None);
+        };
+        let doc = roxmltree::Document::parse(&xml)?;
+        Ok(doc
+            .descendants()
+            .find(|n| is(*n, "title"))
+            .map(|n| collapse_whitespace(&node_text(n)))
+            .filter(|t| !t.is_empty()))
+    }
+}
+
+/// Resolves a relationship target against the directory of its source part
+pub(crate) fn resolve_part(dir: &str, target: &str) -> String {
+    if let Some(absolute) = target.strip_prefix('/') {
+        return absolute.to_string();
+    }
+
+    let mut segments: Vec<&str> = dir.split('/').filter(|s| !s.is_empty()).collect();
+    for segment in target.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
+
+/// Collects the pieces of a document in reading order
+#[derive(Default)]
+pub(crate) struct DocumentBuilder {
+    blocks: Vec<String>,
+    headings: Vec<Heading>,
+    links: Vec<Link>,
+    tables: Vec<Table>,
+    /// Title from the document properties, if any
+    pub title: Option<String>,
+}
+
+impl DocumentBuilder {
+    /// Creates a builder with the title from the document properties, if any
+    pub fn with_title(title: Option<String>) -> Self {
+        Self {
+            title,
+            ..Self::default()
+        }
+    }
+
+    /// Adds a paragraph of text
+    pub fn paragraph(&mut self, text: &str) {
+        let text = text.trim();
+        if !text.is_empty() {
+            self.blocks.push(text.to_string());
+        }
+    }
+
+    /// Adds a heading, which is also part of the text
+    pub fn heading(&mut self, level: u8, text: &str) {
+        let text = collapse_whitespace(text);
+        if text.is_empty() {
+            return;
+        }
+        self.blocks.push(text.clone());
+        self.headings.push(Heading { level, text });
+    }
+
+    /// Adds a table, whose rows are also part of the text
+    pub fn table(&mut self, table: Table) {
+        if table.rows.is_empty() && table.headers.is_empty() {
+            return;
+        }
+        self.blocks.push(table.to_text());
+        self.tables.push(table);
+    }
+
+    /// Adds a hyperlink
+    pub fn link(&mut self, href: &str, text: &str, element: &str) {
+        let href = href.trim();
+        if href.is_empty() {
+            return;
+        }
+        let text = collapse_whitespace(text);
+        self.links.push(Link {
+            url: href.to_string(),
+            href: href.to_string(),
+            kind: LinkKind::Anchor,
+            text: Some(text).filter(|t| !t.is_empty()),
+            rel: Vec::new(),
+            nofollow: false,
+            element: element.to_string(),
+            hreflang: None,
+        });
+    }
+
+    /// Builds the parse result, normalizing the text with the text options
+    pub fn finish(self, options: &ParserOptions) -> ParseResult {
+        let content = text::parse_with_options(&self.blocks.join("\n\n"), &options.text).content;
+        let links = self.links.iter().map(|l| l.href.clone()).collect();
+
+        let mut result = ParseResult::new(content, links);
+        result.title = self
+            .title
+            .or_else(|| self.headings.first().map(|h| h.text.clone()));
+        result.headings = self.headings;
+        result.link_details = self.links;
+        result.tables = self.tables;
+        result
+    }
+}
+
+/// Lays out spanned cells as a table, repeating each cell over the positions it covers
+///
+/// Rows are lists of (text, column span, row span). Positions already covered by
+/// a cell spanning down from an earlier row are skipped.
+pub(crate) fn table_from_cells(
+    caption: Option<String>,
+    rows: &[Vec<(String, usize, usize)>],
+    header_rows: usize,
+) -> Table {
+    let mut grid: Vec<Vec<Option<String>>> = vec![Vec::new(); rows.len()];
+    for (r, row) in rows.iter().enumerate() {
+        let mut column = 0;
+        for (text, colspan, rowspan) in row {
+            while grid[r].get(column).is_some_and(Option::is_some) {
+                column += 1;
+            }
+            let colspan = (*colspan).clamp(1, MAX_COLSPAN);
+            let rowspan = (*rowspan).clamp(1, rows.len() - r);
+            for grid_row in grid.iter_mut().skip(r).take(rowspan) {
+                if grid_row.len() < column + colspan {
+                    grid_row.resize(column + colspan, None);
+                }
+                for slot in &mut grid_row[column..column + colspan] {
+                    *slot = Some(text.clone());
+                }
+            }
+            column += colspan;
+        }
+    }
+
+    // Spreadsheets pad rows with empty cells; drop empty trailing columns
+    let columns = grid
+        .iter()
+        .filter_map(|row| {
+            row.iter()
+                .rposition(|c| c.as_deref().is_some_and(|c| !c.is_empty()))
+        })
+        .max()
+        .map_or(0, |last| last + 1);
+    let mut grid: Vec<Vec<String>> = grid
+        .into_iter()
+        .map(|row| {
+            let mut row: Vec<String> = row.into_iter().map(Option::unwrap_or_default).collect();
+            row.resize(columns, String::new());
+            row
+        })
+        .collect();
+
+    let header_rows = header_rows.min(grid.len());
+    let headers = if header_rows == 0 {
+        Vec::new()
+    } else {
+        (0..columns)
+            .map(|column| {
+                let mut parts: Vec<&str> = Vec::new();
+                for row in &grid[..header_rows] {
+                    let text = row[column].as_str();
+                    if !text.is_empty() && !parts.contains(&text) {
+                        parts.push(text);
+                    }
+                }
+                parts.join(" / ")
+            })
+            .collect()
+    };
+
+    let rows = grid
+        .split_off(header_rows)
+        .into_iter()
+        .filter(|row| row.iter().any(|cell| !cell.is_empty()))
+        .collect();
+
+    Table {
+        caption,
+        headers,
+        rows,
+    }
+}
+
+/// Returns whether a node is an element with the given local name
+pub(crate) fn is(node: Node, name: &str) -> bool {
+    node.is_element() && node.tag_name().name() == name
+}
+
+/// Returns the child elements of a node with the given local name
+pub(crate) fn children<'a, 'input>(
+    node: Node<'a, 'input>,
+    name: &'static str,
+) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.children().filter(move |n| is(*n, name))
+}
+
+/// Returns the first child element with the given local name
+pub(crate) fn child<'a, 'input>(
+    node: Node<'a, 'input>,
+    name: &'static str,
+) -> Option<Node<'a, 'input>> {
+    children(node, name).next()
+}
+
+/// Reads an attribute by local name, whatever its namespace
+pub(crate) fn attr<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.attributes()
+        .find(|a| a.name() == name)
+        .map(|a| a.value())
+}
+
+/// Reads the `r:id` relationship ID of an OOXML element
+///
+/// Unlike `attr`, this tells `r:id` apart from a plain `id` on the same element.
+pub(crate) fn relationship_id<'a>(node: Node<'a, '_>) -> Option<&'a str> {
+    node.attributes()
+        .find(|a| a.name() == "id" && a.namespace() == Some(RELATIONSHIPS_NAMESPACE))
+        .map(|a| a.value())
+}
+
+/// Concatenates the text below a node
+pub(crate) fn node_text(node: Node) -> String {
+    node.descendants()
+        .filter(|n| n.is_text())
+        .filter_map(|n| n.text())
+        .collect()
+}
+
+/// Collapses runs of whitespace into single spaces
+pub(crate) fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
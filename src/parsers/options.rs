@@ -0,0 +1,128 @@
+use crate::parsers::text::TextParserOptions;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Markdown dialect used by the Markdown parser
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MarkdownMode {
+    /// GitHub-flavored Markdown: tables, footnotes, strikethrough, task lists and front matter
+    #[default]
+    Gfm,
+    /// Strict CommonMark without extensions
+    CommonMark,
+}
+
+/// How EPUB e-books are turned into pages
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EpubMode {
+    /// One document with the chapters in reading order
+    #[default]
+    Combined,
+    /// One page per chapter, addressed as `book.epub!/path/to/chapter.xhtml`
+    Chapters,
+}
+
+/// Options controlling how content is parsed, applied to every parser type
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ParserOptions {
+    /// How extracted text is normalized
+    pub text: TextParserOptions,
+
+    /// CSS selector for the main content of HTML pages (the whole `<body>` if unset)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_selector: Option<String>,
+
+    /// CSS selectors for HTML elements left out of the text (e.g. `nav`, `footer`)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub exclude_selectors: Vec<String>,
+
+    /// Markdown dialect
+    pub markdown_mode: MarkdownMode,
+
+    /// Whether EPUB e-books become one page or a page per chapter
+    pub epub_mode: EpubMode,
+
+    /// Whether to extract links; pages parsed without links lead nowhere
+    pub extract_links: bool,
+
+    /// Whether pages keep the source they were parsed from (e.g. for mirroring)
+    pub keep_source: bool,
+}
+
+impl Default for ParserOptions {
+    /// Crawls keep paragraph breaks, unlike `TextParserOptions::default()`
+    fn default() -> Self {
+        Self {
+            text: TextParserOptions {
+                preserve_paragraphs: true,
+                ..TextParserOptions::default()
+            },
+            content_selector: None,
+            exclude_selectors: Vec::new(),
+            markdown_mode: MarkdownMode::default(),
+            epub_mode: EpubMode::default(),
+            extract_links: true,
+            keep_source: false,
+        }
+    }
+}
+
+impl ParserOptions {
+    /// Creates parser options with the given text options and defaults otherwise
+    pub fn with_text_options(text: TextParserOptions) -> Self {
+        Self {
+            text,
+            ..Self::default()
+        }
+    }
+}
+
+/// Parser options for URLs matching a pattern, replacing the crawl-wide options
+///
+/// Options not given in the override take their defaults, not the crawl-wide values.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParserOptionsOverride {
+    /// Regex pattern matched against the URL
+    pub url_pattern: String,
+
+    /// Options for matching URLs
+    #[serde(default)]
+    pub options: ParserOptions,
+}
+
+/// Picks the parser options for each URL from crawl-wide options and overrides
+#[derive(Debug)]
+pub struct ParserOptionsSelector {
+    default: ParserOptions,
+    overrides: Vec<(Regex, ParserOptions)>,
+}
+
+impl ParserOptionsSelector {
+    /// Creates a selector, compiling the override patterns
+    pub fn new(
+        default: ParserOptions,
+        overrides: &[ParserOptionsOverride],
+    ) -> Result<Self, regex::Error> {
+        let mut compiled = Vec::with_capacity(overrides.len());
+        for o in overrides {
+            compiled.push((Regex::new(&o.url_pattern)?, o.options.clone()));
+        }
+
+        Ok(Self {
+            default,
+            overrides: compiled,
+        })
+    }
+
+    /// Returns the options of the first override matching the URL, or the crawl-wide options
+    pub fn for_url(&self, url: &str) -> &ParserOptions {
+        self.overrides
+            .iter()
+            .find(|(pattern, _)| pattern.is_match(url))
+            .map(|(_, options)| options)
+            .unwrap_or(&self.default)
+    }
+}
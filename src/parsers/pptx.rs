@@ -0,0 +1,158 @@
+use crate::parsers::office::{
+    DocumentBuilder, OfficeError, Package, Relationship, attr, child, children,
+    collapse_whitespace, is, relationship_id, table_from_cells,
+};
+use crate::parsers::{ParseResult, ParserOptions};
+use roxmltree::Node;
+use std::collections::HashMap;
+
+/// Presentation part of a PresentationML package
+const PRESENTATION_PART: &str = "ppt/presentation.xml";
+
+/// Parses a PowerPoint presentation (`.pptx`)
+///
+/// Slides are read in presentation order, and the shapes on each slide in
+/// drawing order. Title placeholders become headings; tables keep their cells.
+/// Speaker notes and slide layouts are left out.
+pub(crate) fn parse(
+    package: &mut Package,
+    options: &ParserOptions,
+) -> Result<ParseResult, OfficeError> {
+    let presentation_xml = package.read_required(PRESENTATION_PART)?;
+    let relationships = package.relationships(PRESENTATION_PART)?;
+
+    let mut builder = DocumentBuilder::with_title(package.title("docProps/core.xml")?);
+
+    let presentation = roxmltree::Document::parse(&presentation_xml)?;
+    let slide_parts: Vec<String> = presentation
+        .descendants()
+        .filter(|n| is(*n, "sldId"))
+        .filter_map(|n| relationship_id(n).and_then(|id| relationships.get(id)))
+        .filter(|r| !r.external)
+        .map(|r| r.target.clone())
+        .collect();
+
+    for part in slide_parts {
+        let Some(xml) = package.read(&part)? else {
+            ::log::debug!("Presentation references missing slide part {}", part);
+            continue;
+        };
+        let mut reader = SlideReader {
+            relationships: package.relationships(&part)?,
+            builder: &mut builder,
+        };
+        let doc = roxmltree::Document::parse(&xml)?;
+        if let Some(tree) = doc.descendants().find(|n| is(*n, "spTree")) {
+            reader.shapes(tree);
+        }
+    }
+
+    Ok(builder.finish(options))
+}
+
+/// Reads the shapes of one slide into a document builder
+struct SlideReader<'a> {
+    relationships: HashMap<String, Relationship>,
+    builder: &'a mut DocumentBuilder,
+}
+
+impl SlideReader<'_> {
+    /// Reads the shapes of a shape tree or group in drawing order
+    fn shapes(&mut self, tree: Node) {
+        for node in tree.children().filter(Node::is_element) {
+            match node.tag_name().name() {
+                "sp" => self.shape(node),
+                "grpSp" => self.shapes(node),
+                "graphicFrame" => {
+                    if let Some(tbl) = node.descendants().find(|n| is(*n, "tbl")) {
+                        self.table(tbl);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Reads a text shape, as a heading if it is a title placeholder
+    fn shape(&mut self, sp: Node) {
+        let Some(body) = child(sp, "txBody") else {
+            return;
+        };
+        let is_title = sp
+            .descendants()
+            .find(|n| is(*n, "ph"))
+            .and_then(|ph| attr(ph, "type"))
+            .is_some_and(|t| t == "title" || t == "ctrTitle");
+
+        let paragraphs = self.paragraphs(body);
+        if is_title {
+            self.builder.heading(1, &paragraphs.join(" "));
+        } else {
+            for paragraph in paragraphs {
+                self.builder.paragraph(&paragraph);
+            }
+        }
+    }
+
+    /// Reads the paragraphs of a text body, recording hyperlinks
+    fn paragraphs(&mut self, body: Node) -> Vec<String> {
+        let mut paragraphs = Vec::new();
+        for p in children(body, "p") {
+            let mut text = String::new();
+            for run in p.children().filter(Node::is_element) {
+                match run.tag_name().name() {
+                    "r" | "fld" => {
+                        let run_text = child(run, "t").and_then(|t| t.text()).unwrap_or_default();
+                        if let Some(target) = child(run, "rPr")
+                            .and_then(|pr| child(pr, "hlinkClick"))
+                            .and_then(relationship_id)
+                            .and_then(|id| self.relationships.get(id))
+                            .filter(|r| r.external)
+                        {
+                            self.builder.link(&target.target, run_text, "a:hlinkClick");
+                        }
+                        text.push_str(run_text);
+                    }
+                    "br" => text.push('\n'),
+                    _ => {}
+                }
+            }
+            paragraphs.push(text);
+        }
+        paragraphs
+    }
+
+    /// Reads a table, skipping cells covered by merged neighbours
+    fn table(&mut self, tbl: Node) {
+        let first_row_header = child(tbl, "tblPr")
+            .and_then(|pr| attr(pr, "firstRow"))
+            .is_some_and(|v| v == "1" || v == "true");
+
+        let mut rows = Vec::new();
+        for tr in children(tbl, "tr") {
+            let mut cells = Vec::new();
+            for tc in children(tr, "tc") {
+                if attr(tc, "hMerge").is_some() || attr(tc, "vMerge").is_some() {
+                    continue;
+                }
+                let span = |name| {
+                    attr(tc, name)
+                        .and_then(|v| v.parse::<usize>().ok())
+                        .unwrap_or(1)
+                };
+                let text = child(tc, "txBody")
+                    .map(|body| self.paragraphs(body).join(" "))
+                    .unwrap_or_default();
+                cells.push((
+                    collapse_whitespace(&text),
+                    span("gridSpan"),
+                    span("rowSpan"),
+                ));
+            }
+            rows.push(cells);
+        }
+
+        self.builder
+            .table(table_from_cells(None, &rows, usize::from(first_row_header)));
+    }
+}
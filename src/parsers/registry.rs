@@ -0,0 +1,219 @@
+use crate::parsers::{
+    ParseResult, Parser, ParserOptions, ParserType, content_type, encoding, epub, office,
+};
+use regex::Regex;
+use std::sync::Arc;
+
+/// A parser for one content format
+///
+/// Implement this to crawl formats the built-in parsers don't know about, and
+/// register it in a `ParserRegistry`. Closures taking the content, its URL and
+/// the parser options implement it too.
+pub trait ContentParser: Send + Sync {
+    /// Parses decoded text content fetched from `url` (if known)
+    fn parse(&self, content: &str, url: Option<&str>, options: &ParserOptions) -> ParseResult;
+
+    /// Parses raw bytes as served with `content_type`
+    ///
+    /// By default the bytes are decoded to UTF-8 and passed to `parse`, recording
+    /// the encoding in the result metadata. Binary formats override this.
+    fn parse_bytes(
+        &self,
+        bytes: &[u8],
+        content_type: Option<&str>,
+        url: Option<&str>,
+        options: &ParserOptions,
+    ) -> ParseResult {
+        decode_and_parse(self, bytes, content_type, url, options)
+    }
+}
+
+/// Decodes bytes to UTF-8 and parses them as text, recording the encoding
+fn decode_and_parse<P: ContentParser + ?Sized>(
+    parser: &P,
+    bytes: &[u8],
+    content_type: Option<&str>,
+    url: Option<&str>,
+    options: &ParserOptions,
+) -> ParseResult {
+    let decoded = encoding::decode(bytes, content_type);
+    ::log::debug!(
+        "Decoded content as {} ({:?})",
+        decoded.encoding,
+        decoded.source
+    );
+
+    let mut result = parser.parse(&decoded.text, url, options);
+    result.metadata.content_type = content_type.map(|s| s.to_string());
+    result.metadata.encoding = Some(decoded.encoding.to_string());
+    result.metadata.encoding_source = Some(decoded.source);
+    result
+}
+
+impl<F> ContentParser for F
+where
+    F: Fn(&str, Option<&str>, &ParserOptions) -> ParseResult + Send + Sync,
+{
+    fn parse(&self, content: &str, url: Option<&str>, options: &ParserOptions) -> ParseResult {
+        self(content, url, options)
+    }
+}
+
+/// One of the built-in parsers, as a `ContentParser`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BuiltinParser(pub ParserType);
+
+impl ContentParser for BuiltinParser {
+    fn parse(&self, content: &str, url: Option<&str>, options: &ParserOptions) -> ParseResult {
+        match url {
+            Some(url) => Parser::parse_with_url_and_options(content, self.0, url, options),
+            None => Parser::parse_with_options(content, self.0, options),
+        }
+    }
+
+    /// Office documents and e-books are parsed from their ZIP package rather than decoded text
+    fn parse_bytes(
+        &self,
+        bytes: &[u8],
+        content_type: Option<&str>,
+        url: Option<&str>,
+        options: &ParserOptions,
+    ) -> ParseResult {
+        let parsed = match self.0 {
+            ParserType::Docx | ParserType::Xlsx | ParserType::Pptx | ParserType::OpenDocument => {
+                office::parse(bytes, self.0, options)
+            }
+            ParserType::Epub => epub::parse(bytes, url, options),
+            _ => return decode_and_parse(self, bytes, content_type, url, options),
+        };
+
+        let mut result = parsed.unwrap_or_else(|e| {
+            ::log::warn!("Failed to parse {:?} document: {}", self.0, e);
+            ParseResult::content_only(String::new())
+        });
+        if let Some(url) = url {
+            result.resolve_links(url);
+        }
+        result.metadata.content_type = content_type.map(|s| s.to_string());
+        result
+    }
+}
+
+/// Which resources a registered parser handles
+#[derive(Debug, Clone)]
+pub enum ParserMatcher {
+    /// Resources whose Content-Type matches a pattern; `*` acts as a wildcard
+    MimeType(Regex),
+    /// Resources whose URL path ends with an extension (without the dot)
+    Extension(String),
+    /// Resources whose URL matches a regex
+    UrlPattern(Regex),
+    /// Resources the built-in detection assigns to a parser type, replacing that parser
+    ParserType(ParserType),
+}
+
+impl ParserMatcher {
+    /// Matches a MIME type such as `application/x-wiki` or `application/*+xml`
+    pub fn mime_type(pattern: &str) -> Self {
+        // Every character is escaped except `*`, so the pattern always compiles
+        Self::MimeType(content_type::mime_glob(pattern).expect("Escaped MIME pattern"))
+    }
+
+    /// Matches a file extension such as `wiki` or `.wiki`, ignoring case
+    pub fn extension(extension: &str) -> Self {
+        Self::Extension(extension.trim_start_matches('.').to_ascii_lowercase())
+    }
+
+    /// Matches URLs against a regex
+    pub fn url_pattern(pattern: &str) -> Result<Self, regex::Error> {
+        Ok(Self::UrlPattern(Regex::new(pattern)?))
+    }
+
+    /// Returns whether a resource matches
+    fn matches(&self, url: &str, mime_type: Option<&str>, parser_type: ParserType) -> bool {
+        match self {
+            Self::MimeType(regex) => mime_type.is_some_and(|mime| regex.is_match(mime)),
+            Self::Extension(extension) => {
+                let path = url::Url::parse(url)
+                    .map(|u| u.path().to_string())
+                    .unwrap_or_else(|_| url.split(['?', '#']).next().unwrap_or_default().into());
+                path.to_ascii_lowercase()
+                    .rsplit_once('.')
+                    .is_some_and(|(_, ext)| ext == extension)
+            }
+            Self::UrlPattern(regex) => regex.is_match(url),
+            Self::ParserType(t) => *t == parser_type,
+        }
+    }
+}
+
+/// User-defined parsers, checked before the built-in ones
+///
+/// Parsers are matched in registration order, and the first match handles the
+/// resource. Resources no parser matches use the built-in parser for their type.
+#[derive(Clone, Default)]
+pub struct ParserRegistry {
+    parsers: Vec<(ParserMatcher, Arc<dyn ContentParser>)>,
+}
+
+impl ParserRegistry {
+    /// Creates an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a parser for the resources a matcher accepts
+    pub fn register(&mut self, matcher: ParserMatcher, parser: impl ContentParser + 'static) {
+        self.parsers.push((matcher, Arc::new(parser)));
+    }
+
+    /// Registers a parser, returning the registry for chaining
+    pub fn with_parser(
+        mut self,
+        matcher: ParserMatcher,
+        parser: impl ContentParser + 'static,
+    ) -> Self {
+        self.register(matcher, parser);
+        self
+    }
+
+    /// Returns whether no parsers are registered
+    pub fn is_empty(&self) -> bool {
+        self.parsers.is_empty()
+    }
+
+    /// Finds the registered parser for a resource
+    ///
+    /// `parser_type` is the type built-in detection picked for the resource.
+    pub fn find(
+        &self,
+        url: &str,
+        content_type: Option<&str>,
+        parser_type: ParserType,
+    ) -> Option<Arc<dyn ContentParser>> {
+        let mime_type = content_type.map(content_type::mime_type);
+        self.parsers
+            .iter()
+            .find(|(matcher, _)| matcher.matches(url, mime_type.as_deref(), parser_type))
+            .map(|(_, parser)| Arc::clone(parser))
+    }
+
+    /// Returns the registered parser for a resource, or the built-in one for its type
+    pub fn resolve(
+        &self,
+        url: &str,
+        content_type: Option<&str>,
+        parser_type: ParserType,
+    ) -> Arc<dyn ContentParser> {
+        self.find(url, content_type, parser_type)
+            .unwrap_or_else(|| Arc::new(BuiltinParser(parser_type)))
+    }
+}
+
+impl std::fmt::Debug for ParserRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.parsers.iter().map(|(matcher, _)| matcher))
+            .finish()
+    }
+}
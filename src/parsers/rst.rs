@@ -0,0 +1,609 @@
+use crate::parsers::text::{self, TextParserOptions};
+use crate::parsers::{CodeBlock, Heading, Link, LinkKind, MediaItem, MediaKind, ParseResult};
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+/// Characters that may be used to adorn section titles and transitions
+const ADORNMENT_CHARS: &str = "!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~";
+
+/// Directives whose body is readable text
+const TEXT_DIRECTIVES: &[&str] = &[
+    "admonition",
+    "attention",
+    "caution",
+    "centered",
+    "container",
+    "danger",
+    "deprecated",
+    "error",
+    "figure",
+    "glossary",
+    "hint",
+    "hlist",
+    "important",
+    "list-table",
+    "note",
+    "only",
+    "rubric",
+    "seealso",
+    "sidebar",
+    "tab",
+    "table",
+    "tabs",
+    "tip",
+    "topic",
+    "versionadded",
+    "versionchanged",
+    "warning",
+];
+
+/// Directives whose argument is an image path or other non-text value
+const NON_TEXT_ARGUMENT_DIRECTIVES: &[&str] = &[
+    "container",
+    "figure",
+    "list-table",
+    "only",
+    "tab",
+    "table",
+    "tabs",
+];
+
+/// Directives whose body is source code
+const CODE_DIRECTIVES: &[&str] = &["code", "code-block", "doctest", "sourcecode", "testcode"];
+
+static ROLE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r":([A-Za-z0-9_.+:-]+):`([^`]+)`").unwrap());
+static LITERAL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"``(.+?)``").unwrap());
+static EMBEDDED_LINK: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"`([^`<]*?)\s*<([^`>]+)>`__?").unwrap());
+static NAMED_REFERENCE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"`([^`]+)`__?").unwrap());
+static WORD_REFERENCE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(^|[\s(])([A-Za-z0-9][A-Za-z0-9.-]*)__?($|[\s.,;:!?)])").unwrap()
+});
+static INTERPRETED: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"`([^`]+)`").unwrap());
+static FOOTNOTE_REFERENCE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\s?\[(#[\w-]*|\*|\d+|[A-Za-z][\w.-]*)\]_").unwrap());
+static SUBSTITUTION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\|([^|\s][^|]*?)\|(__?)?").unwrap());
+static STRONG: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\*\*(\S(?:.*?\S)?)\*\*").unwrap());
+static EMPHASIS: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\*(\S(?:[^*]*?\S)?)\*").unwrap());
+static LIST_MARKER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?:[-*+•‣⁃]|#\.|\d+[.)]|\(?[A-Za-z0-9]\)|[A-Za-z]\.)\s+").unwrap()
+});
+static FIELD: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^:([^:`]+):(?:\s+(.*))?$").unwrap());
+
+/// Parses reStructuredText source with default text options
+///
+/// Section titles, paragraphs, lists and admonitions are kept as plain text.
+/// Comments, substitution definitions and non-text directives (`toctree`,
+/// `image`, `raw`, ...) are dropped, and inline markup is reduced to its text.
+pub fn parse(rst: &str) -> ParseResult {
+    parse_with_options(rst, &TextParserOptions::default())
+}
+
+/// Parses reStructuredText source with specific text options
+pub fn parse_with_options(rst: &str, options: &TextParserOptions) -> ParseResult {
+    let lines: Vec<String> = rst.lines().map(|l| l.replace('\t', "    ")).collect();
+
+    let mut state = RstState::default();
+    parse_block(&lines, &mut state);
+
+    // Named references may be defined anywhere in the document
+    let mut link_details = Vec::new();
+    for reference in state.references {
+        let url = match reference.target {
+            Target::Url(url) => url,
+            Target::Name(name) => match state.targets.get(&normalize_name(&name)) {
+                Some(url) => url.clone(),
+                None => continue,
+            },
+        };
+        link_details.push(Link {
+            url: url.clone(),
+            href: url,
+            kind: LinkKind::Anchor,
+            text: Some(reference.text).filter(|t| !t.is_empty()),
+            rel: Vec::new(),
+            nofollow: false,
+            element: "a".to_string(),
+            hreflang: None,
+        });
+    }
+
+    let content = text::parse_with_options(&state.blocks.join("\n\n"), options).content;
+    let links = link_details.iter().map(|l| l.href.clone()).collect();
+
+    let mut result = ParseResult::new(content, links);
+    result.title = state.headings.first().map(|h| h.text.clone());
+    result.headings = state.headings;
+    result.link_details = link_details;
+    result.code_blocks = state.code_blocks;
+    result.media = state.media;
+    result
+}
+
+/// Where a reference points to
+enum Target {
+    /// An embedded URL or document path
+    Url(String),
+    /// A named hyperlink target defined elsewhere in the document
+    Name(String),
+}
+
+/// A reference found in inline markup
+struct Reference {
+    text: String,
+    target: Target,
+}
+
+/// State accumulated while walking the document
+#[derive(Default)]
+struct RstState {
+    blocks: Vec<String>,
+    headings: Vec<Heading>,
+    /// Section adornment styles (character, has overline) in order of first use
+    styles: Vec<(char, bool)>,
+    /// Named hyperlink targets (`.. _name: url`)
+    targets: HashMap<String, String>,
+    references: Vec<Reference>,
+    code_blocks: Vec<CodeBlock>,
+    /// Images from `image` and `figure` directives
+    media: Vec<MediaItem>,
+    /// Default language of literal blocks, set by the `highlight` directive
+    highlight_language: Option<String>,
+}
+
+impl RstState {
+    /// Returns the section level for an adornment style, registering new styles
+    fn level_for(&mut self, style: (char, bool)) -> u8 {
+        let index = match self.styles.iter().position(|s| *s == style) {
+            Some(index) => index,
+            None => {
+                self.styles.push(style);
+                self.styles.len() - 1
+            }
+        };
+        (index + 1).min(6) as u8
+    }
+
+    /// Adds a section heading
+    fn push_heading(&mut self, title: &str, style: (char, bool)) {
+        let level = self.level_for(style);
+        let text = self.inline(title);
+        if !text.is_empty() {
+            self.headings.push(Heading {
+                level,
+                text: text.clone(),
+            });
+            self.blocks.push(text);
+        }
+    }
+
+    /// Adds a paragraph of inline text
+    fn push_paragraph(&mut self, lines: &[&str]) {
+        let text = self.inline(&lines.join("\n"));
+        if !text.trim().is_empty() {
+            self.blocks.push(text.trim().to_string());
+        }
+    }
+
+    /// Adds a literal block, keeping its text as is
+    fn push_literal(&mut self, lines: &[String], language: Option<&str>) {
+        let text = dedent(lines).join("\n");
+        if !text.trim().is_empty() {
+            self.blocks.push(text.trim_end().to_string());
+            let heading = self.headings.last().map(|h| h.text.as_str());
+            self.code_blocks
+                .push(CodeBlock::new(&text, language, heading));
+        }
+    }
+
+    /// Reduces inline markup to text, recording references
+    fn inline(&mut self, text: &str) -> String {
+        let mut references = Vec::new();
+
+        let text = ROLE.replace_all(text, |c: &regex::Captures| {
+            let (title, target) = split_embedded_target(&c[2]);
+            let title = title.trim_start_matches(['~', '!']);
+            let role = c[1].rsplit(':').next().unwrap_or_default();
+            if role == "doc"
+                && let Some(target) = target.or(Some(title))
+            {
+                references.push(Reference {
+                    text: title.to_string(),
+                    target: Target::Url(doc_path(target)),
+                });
+            }
+            // Show the last component of `~module.Class.method` style targets
+            if c[2].starts_with('~') {
+                title.rsplit('.').next().unwrap_or(title).to_string()
+            } else {
+                title.to_string()
+            }
+        });
+        let text = LITERAL.replace_all(&text, "$1");
+        let text = EMBEDDED_LINK.replace_all(&text, |c: &regex::Captures| {
+            let title = if c[1].is_empty() { &c[2] } else { &c[1] };
+            let target = c[2].trim();
+            references.push(Reference {
+                text: title.to_string(),
+                target: match target.strip_suffix('_') {
+                    Some(name) => Target::Name(name.to_string()),
+                    None => Target::Url(target.to_string()),
+                },
+            });
+            title.to_string()
+        });
+        let text = NAMED_REFERENCE.replace_all(&text, |c: &regex::Captures| {
+            references.push(Reference {
+                text: c[1].to_string(),
+                target: Target::Name(c[1].to_string()),
+            });
+            c[1].to_string()
+        });
+        let text = WORD_REFERENCE.replace_all(&text, |c: &regex::Captures| {
+            references.push(Reference {
+                text: c[2].to_string(),
+                target: Target::Name(c[2].to_string()),
+            });
+            format!("{}{}{}", &c[1], &c[2], &c[3])
+        });
+        let text = INTERPRETED.replace_all(&text, "$1");
+        let text = FOOTNOTE_REFERENCE.replace_all(&text, "");
+        let text = SUBSTITUTION.replace_all(&text, "$1");
+        let text = STRONG.replace_all(&text, "$1");
+        let text = EMPHASIS.replace_all(&text, "$1");
+        let text = text.replace("\\", "");
+
+        self.references.extend(references);
+        text
+    }
+}
+
+/// Walks a block of lines, appending text to the state
+fn parse_block(lines: &[String], state: &mut RstState) {
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut literal_next = false;
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i].as_str();
+        let trimmed = line.trim();
+
+        if trimmed.is_empty() {
+            flush_paragraph(&mut paragraph, &mut literal_next, state);
+            i += 1;
+            continue;
+        }
+
+        // An indented block after a paragraph ending in `::` is a literal block
+        if literal_next && paragraph.is_empty() && indentation(line) > 0 {
+            let end = indented_block_end(lines, i, 0);
+            let language = state.highlight_language.clone();
+            state.push_literal(&lines[i..end], language.as_deref());
+            literal_next = false;
+            i = end;
+            continue;
+        }
+        literal_next = false;
+
+        // Section title with overline and underline
+        if paragraph.is_empty()
+            && is_adornment(line)
+            && i + 2 < lines.len()
+            && !lines[i + 1].trim().is_empty()
+            && is_adornment(&lines[i + 2])
+            && lines[i + 2].trim().starts_with(&trimmed[..1])
+        {
+            let style = (trimmed.chars().next().unwrap(), true);
+            state.push_heading(lines[i + 1].trim(), style);
+            i += 3;
+            continue;
+        }
+
+        // Section title with underline only
+        if paragraph.is_empty()
+            && indentation(line) == 0
+            && !is_adornment(line)
+            && i + 1 < lines.len()
+            && is_adornment(&lines[i + 1])
+            && lines[i + 1].trim().chars().count() >= trimmed.chars().count().min(4)
+        {
+            let style = (lines[i + 1].trim().chars().next().unwrap(), false);
+            state.push_heading(trimmed, style);
+            i += 2;
+            continue;
+        }
+
+        // Transitions and table borders
+        if is_adornment(line) || is_table_border(trimmed) {
+            flush_paragraph(&mut paragraph, &mut literal_next, state);
+            i += 1;
+            continue;
+        }
+
+        // Explicit markup: directives, comments, targets and substitution definitions
+        if trimmed == ".." || trimmed.starts_with(".. ") {
+            flush_paragraph(&mut paragraph, &mut literal_next, state);
+            i = parse_explicit_markup(lines, i, state);
+            continue;
+        }
+
+        // Field lists (`:orphan:`, `:author: Name`) keep only their values
+        if let Some(c) = FIELD.captures(trimmed) {
+            flush_paragraph(&mut paragraph, &mut literal_next, state);
+            if let Some(value) = c.get(2).map(|m| m.as_str().trim())
+                && !value.is_empty()
+                && !c[1].contains(' ')
+                && !value.chars().all(|c| c.is_ascii_digit())
+            {
+                state.push_paragraph(&[value]);
+            }
+            i += 1;
+            continue;
+        }
+
+        // Each list item starts a new paragraph
+        if let Some(m) = LIST_MARKER.find(trimmed) {
+            flush_paragraph(&mut paragraph, &mut literal_next, state);
+            paragraph.push(&trimmed[m.end()..]);
+            i += 1;
+            continue;
+        }
+
+        // Grid table rows and line blocks
+        if trimmed.starts_with('|') {
+            let cells = trimmed
+                .split('|')
+                .map(str::trim)
+                .filter(|c| !c.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ");
+            if !cells.is_empty() {
+                state.push_paragraph(&[&cells]);
+            }
+            i += 1;
+            continue;
+        }
+
+        paragraph.push(trimmed);
+        i += 1;
+    }
+
+    flush_paragraph(&mut paragraph, &mut literal_next, state);
+}
+
+/// Flushes the pending paragraph, noting whether it introduces a literal block
+fn flush_paragraph(paragraph: &mut Vec<&str>, literal_next: &mut bool, state: &mut RstState) {
+    if paragraph.is_empty() {
+        return;
+    }
+
+    let last = paragraph.len() - 1;
+    let last_line = paragraph[last];
+    if let Some(stripped) = last_line.strip_suffix("::") {
+        *literal_next = true;
+        // `Paragraph::` becomes `Paragraph:`, a lone `::` disappears
+        let stripped = stripped.trim_end();
+        let replacement = if stripped.is_empty() {
+            String::new()
+        } else {
+            format!("{}:", stripped)
+        };
+        let mut lines: Vec<&str> = paragraph[..last].to_vec();
+        lines.push(&replacement);
+        state.push_paragraph(&lines);
+    } else {
+        state.push_paragraph(paragraph);
+    }
+    paragraph.clear();
+}
+
+/// Handles a line starting with `..`, returning the index of the next line to process
+fn parse_explicit_markup(lines: &[String], start: usize, state: &mut RstState) -> usize {
+    let line = lines[start].trim();
+    let base_indent = indentation(&lines[start]);
+    let end = indented_block_end(lines, start + 1, base_indent);
+    let body = &lines[start + 1..end];
+    let rest = line.strip_prefix("..").unwrap_or_default().trim();
+
+    // Hyperlink targets: `.. _name: https://example.com`
+    if let Some(target) = rest.strip_prefix('_') {
+        if let Some((name, url)) = split_target(target) {
+            let url = std::iter::once(url)
+                .chain(body.iter().map(|l| l.trim()))
+                .collect::<String>();
+            if !url.is_empty() {
+                state.targets.insert(normalize_name(name), url);
+            }
+        }
+        return end;
+    }
+
+    // Substitution definitions: `.. |name| replace:: text`
+    if rest.starts_with('|') {
+        return end;
+    }
+
+    // Directives: `.. name:: arguments`
+    if let Some((name, argument)) = rest.split_once("::") {
+        let name = name.trim().to_ascii_lowercase();
+        let name = name.rsplit(':').next().unwrap_or_default();
+        let argument = argument.trim();
+
+        // Directive options come first in the body
+        let content_start = body
+            .iter()
+            .position(|l| !FIELD.is_match(l.trim()))
+            .unwrap_or(body.len());
+        let content = &body[content_start..];
+
+        if matches!(name, "image" | "figure") && !argument.is_empty() {
+            state.media.push(directive_image(
+                argument,
+                &body[..content_start],
+                content,
+                name,
+            ));
+        }
+
+        if CODE_DIRECTIVES.contains(&name) {
+            // Doctest directives take a group name rather than a language
+            let language = match name {
+                "doctest" | "testcode" => Some("python".to_string()),
+                _ if !argument.is_empty() => Some(argument.to_string()),
+                _ => state.highlight_language.clone(),
+            };
+            state.push_literal(content, language.as_deref());
+        } else if name == "highlight" {
+            state.highlight_language = Some(argument.to_string()).filter(|a| !a.is_empty());
+        } else if TEXT_DIRECTIVES.contains(&name) {
+            if !argument.is_empty() && !NON_TEXT_ARGUMENT_DIRECTIVES.contains(&name) {
+                state.push_paragraph(&[argument]);
+            }
+            parse_block(&dedent(content), state);
+        } else {
+            ::log::trace!("Skipping reStructuredText directive: {}", name);
+        }
+        return end;
+    }
+
+    // Anything else is a comment
+    end
+}
+
+/// Builds an image from an `image` or `figure` directive and its options
+///
+/// A figure's caption is the first paragraph of its content.
+fn directive_image(path: &str, options: &[String], content: &[String], name: &str) -> MediaItem {
+    let mut image = MediaItem::new(MediaKind::Image, Some(path.to_string()));
+    for option in options {
+        let Some(captures) = FIELD.captures(option.trim()) else {
+            continue;
+        };
+        let value = captures
+            .get(2)
+            .map(|v| v.as_str().trim())
+            .unwrap_or_default();
+        match captures[1].trim() {
+            "alt" => image.alt = Some(value.to_string()),
+            "width" => image.width = value.trim_end_matches("px").parse().ok(),
+            "height" => image.height = value.trim_end_matches("px").parse().ok(),
+            _ => {}
+        }
+    }
+
+    if name == "figure" {
+        let caption = content
+            .iter()
+            .map(|l| l.trim())
+            .skip_while(|l| l.is_empty())
+            .take_while(|l| !l.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        image.caption = Some(caption).filter(|c| !c.is_empty());
+    }
+    image
+}
+
+/// Splits `name: url` in a hyperlink target, allowing escaped or quoted colons
+fn split_target(target: &str) -> Option<(&str, &str)> {
+    if let Some(rest) = target.strip_prefix('`') {
+        let (name, url) = rest.split_once("`:")?;
+        return Some((name, url.trim()));
+    }
+    let (name, url) = target.split_once(':')?;
+    Some((name, url.trim()))
+}
+
+/// Returns the index after the block indented deeper than `base_indent`
+fn indented_block_end(lines: &[String], start: usize, base_indent: usize) -> usize {
+    let mut end = start;
+    let mut i = start;
+    while i < lines.len() {
+        if lines[i].trim().is_empty() {
+            i += 1;
+            continue;
+        }
+        if indentation(&lines[i]) <= base_indent {
+            break;
+        }
+        i += 1;
+        end = i;
+    }
+    end
+}
+
+/// Removes the common leading indentation from a block of lines
+fn dedent(lines: &[String]) -> Vec<String> {
+    let indent = lines
+        .iter()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| indentation(l))
+        .min()
+        .unwrap_or(0);
+    lines
+        .iter()
+        .map(|l| l.get(indent..).unwrap_or_default().to_string())
+        .collect()
+}
+
+/// Number of leading spaces
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// Returns whether a line consists of a single repeated adornment character
+fn is_adornment(line: &str) -> bool {
+    let trimmed = line.trim_end();
+    let mut chars = trimmed.chars();
+    match chars.next() {
+        Some(first) if ADORNMENT_CHARS.contains(first) && indentation(line) == 0 => {
+            trimmed.chars().count() >= 3 && chars.all(|c| c == first)
+        }
+        _ => false,
+    }
+}
+
+/// Returns whether a line is a grid or simple table border
+fn is_table_border(trimmed: &str) -> bool {
+    (trimmed.starts_with('+')
+        && trimmed.ends_with('+')
+        && trimmed.len() > 2
+        && trimmed.chars().all(|c| matches!(c, '+' | '-' | '=' | ':')))
+        || (trimmed.contains("  ")
+            && trimmed.chars().all(|c| matches!(c, '=' | '-' | ' '))
+            && trimmed.split_whitespace().count() > 1)
+}
+
+/// Splits `Title <target>` into its parts
+fn split_embedded_target(text: &str) -> (&str, Option<&str>) {
+    if let Some(stripped) = text.strip_suffix('>')
+        && let Some((title, target)) = stripped.rsplit_once('<')
+    {
+        return (title.trim(), Some(target.trim()));
+    }
+    (text.trim(), None)
+}
+
+/// Maps a `:doc:` target to the path of its source file
+fn doc_path(target: &str) -> String {
+    let has_extension = target
+        .rsplit('/')
+        .next()
+        .is_some_and(|name| name.contains('.'));
+    if has_extension {
+        target.to_string()
+    } else {
+        format!("{}.rst", target)
+    }
+}
+
+/// Normalizes a reference name (case-insensitive, whitespace-insensitive)
+fn normalize_name(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
@@ -0,0 +1,269 @@
+use crate::parsers::{content_type, links};
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use url::Url;
+
+/// Where a schema.org object was found in the page
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SchemaSource {
+    /// A `<script type="application/ld+json">` block
+    JsonLd,
+    /// `itemscope`/`itemprop` microdata attributes
+    Microdata,
+}
+
+/// A schema.org object (Article, FAQPage, BreadcrumbList, Product, ...) found in a page
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SchemaObject {
+    /// Schema.org type names without the vocabulary prefix (e.g. `Article`)
+    pub types: Vec<String>,
+
+    /// Where the object was found
+    pub source: SchemaSource,
+
+    /// The object itself, as JSON-LD
+    pub data: Value,
+}
+
+impl SchemaObject {
+    /// Returns whether the object has the given schema.org type
+    pub fn is_type(&self, name: &str) -> bool {
+        self.types.iter().any(|t| t.eq_ignore_ascii_case(name))
+    }
+}
+
+/// Extracts schema.org objects from JSON-LD scripts and microdata
+///
+/// JSON-LD arrays and `@graph` containers are flattened into their top-level
+/// objects. Microdata URL properties are resolved against the document base.
+pub fn extract_schema_objects(doc: &Html, page_url: Option<&str>) -> Vec<SchemaObject> {
+    let mut objects = extract_json_ld(doc);
+    objects.extend(extract_microdata(doc, page_url));
+    objects
+}
+
+/// Returns the names in the page's first `BreadcrumbList`, ordered by position
+pub fn section_path(objects: &[SchemaObject]) -> Vec<String> {
+    let Some(list) = objects.iter().find(|o| o.is_type("BreadcrumbList")) else {
+        return Vec::new();
+    };
+
+    let mut items: Vec<(f64, String)> = as_array(&list.data["itemListElement"])
+        .into_iter()
+        .filter_map(|item| {
+            let name = item
+                .get("name")
+                .or_else(|| item.get("item").and_then(|i| i.get("name")))
+                .and_then(text_value)?;
+            let position = item
+                .get("position")
+                .and_then(text_value)
+                .and_then(|p| p.parse().ok())
+                .unwrap_or(f64::MAX);
+            Some((position, name))
+        })
+        .collect();
+
+    // Positions are optional and may be out of order in the markup
+    items.sort_by(|a, b| a.0.total_cmp(&b.0));
+    items.into_iter().map(|(_, name)| name).collect()
+}
+
+/// Parses `<script type="application/ld+json">` blocks
+fn extract_json_ld(doc: &Html) -> Vec<SchemaObject> {
+    let selector = Selector::parse("script[type]").unwrap();
+    let mut objects = Vec::new();
+
+    for script in doc.select(&selector) {
+        let script_type = script.value().attr("type").unwrap_or_default();
+        if content_type::mime_type(script_type) != "application/ld+json" {
+            continue;
+        }
+
+        let json = script.text().collect::<String>();
+        match serde_json::from_str::<Value>(json.trim()) {
+            Ok(value) => collect_json_ld(value, &mut objects),
+            Err(e) => ::log::debug!("Skipping malformed JSON-LD: {}", e),
+        }
+    }
+
+    objects
+}
+
+/// Flattens a JSON-LD value into typed top-level objects
+fn collect_json_ld(value: Value, objects: &mut Vec<SchemaObject>) {
+    match value {
+        Value::Array(items) => {
+            for item in items {
+                collect_json_ld(item, objects);
+            }
+        }
+        Value::Object(mut map) => {
+            if let Some(graph) = map.remove("@graph") {
+                collect_json_ld(graph, objects);
+            }
+            let types: Vec<String> = as_array(map.get("@type").unwrap_or(&Value::Null))
+                .into_iter()
+                .filter_map(Value::as_str)
+                .map(short_type_name)
+                .collect();
+            if !types.is_empty() {
+                objects.push(SchemaObject {
+                    types,
+                    source: SchemaSource::JsonLd,
+                    data: Value::Object(map),
+                });
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Parses top-level microdata items (`itemscope` elements that aren't properties)
+fn extract_microdata(doc: &Html, page_url: Option<&str>) -> Vec<SchemaObject> {
+    let selector = Selector::parse("[itemscope]:not([itemprop])").unwrap();
+    let base = links::document_base_url(doc, page_url);
+
+    doc.select(&selector)
+        .filter_map(|element| {
+            let data = microdata_item(element, base.as_ref());
+            let types = as_array(data.get("@type").unwrap_or(&Value::Null))
+                .into_iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect::<Vec<_>>();
+            (!types.is_empty()).then_some(SchemaObject {
+                types,
+                source: SchemaSource::Microdata,
+                data,
+            })
+        })
+        .collect()
+}
+
+/// Builds a JSON-LD style object from a microdata item
+fn microdata_item(element: ElementRef, base: Option<&Url>) -> Value {
+    let mut map = Map::new();
+
+    let types: Vec<Value> = element
+        .value()
+        .attr("itemtype")
+        .unwrap_or_default()
+        .split_whitespace()
+        .map(|t| Value::String(short_type_name(t)))
+        .collect();
+    match types.len() {
+        0 => {}
+        1 => {
+            map.insert("@type".to_string(), types[0].clone());
+        }
+        _ => {
+            map.insert("@type".to_string(), Value::Array(types));
+        }
+    }
+    if let Some(id) = element.value().attr("itemid") {
+        map.insert("@id".to_string(), Value::String(links::resolve(base, id)));
+    }
+
+    collect_properties(element, base, &mut map);
+    Value::Object(map)
+}
+
+/// Adds `itemprop` values below `element` to `map`, without entering nested items
+fn collect_properties(element: ElementRef, base: Option<&Url>, map: &mut Map<String, Value>) {
+    for child in element.children().filter_map(ElementRef::wrap) {
+        let nested_item = child.value().attr("itemscope").is_some();
+
+        if let Some(names) = child.value().attr("itemprop") {
+            let value = if nested_item {
+                microdata_item(child, base)
+            } else {
+                property_value(child, base)
+            };
+            for name in names.split_whitespace() {
+                insert_property(map, name, value.clone());
+            }
+        }
+
+        if !nested_item {
+            collect_properties(child, base, map);
+        }
+    }
+}
+
+/// Adds a property, turning repeated properties into arrays
+fn insert_property(map: &mut Map<String, Value>, name: &str, value: Value) {
+    match map.get_mut(name) {
+        Some(Value::Array(values)) => values.push(value),
+        Some(existing) => {
+            let first = existing.take();
+            *existing = Value::Array(vec![first, value]);
+        }
+        None => {
+            map.insert(name.to_string(), value);
+        }
+    }
+}
+
+/// Returns the value of a microdata property element
+fn property_value(element: ElementRef, base: Option<&Url>) -> Value {
+    let el = element.value();
+    let url_attr = match el.name() {
+        "audio" | "embed" | "iframe" | "img" | "source" | "track" | "video" => Some("src"),
+        "a" | "area" | "link" => Some("href"),
+        "object" => Some("data"),
+        _ => None,
+    };
+
+    let value = if let Some(attr) = url_attr {
+        el.attr(attr).map(|href| links::resolve(base, href.trim()))
+    } else {
+        match el.name() {
+            "meta" => el.attr("content").map(str::to_string),
+            "data" | "meter" => el.attr("value").map(str::to_string),
+            "time" => el.attr("datetime").map(str::to_string),
+            _ => None,
+        }
+    };
+
+    Value::String(value.unwrap_or_else(|| {
+        element
+            .text()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+    }))
+}
+
+/// Strips the vocabulary from a type (`https://schema.org/Article`, `schema:Article`)
+fn short_type_name(name: &str) -> String {
+    name.trim()
+        .trim_end_matches('/')
+        .rsplit(['/', ':', '#'])
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Treats a single value as a one-element array
+fn as_array(value: &Value) -> Vec<&Value> {
+    match value {
+        Value::Array(items) => items.iter().collect(),
+        Value::Null => Vec::new(),
+        other => vec![other],
+    }
+}
+
+/// Returns a string or number value as trimmed, non-empty text
+fn text_value(value: &Value) -> Option<String> {
+    let text = match value {
+        Value::String(s) => s.trim().to_string(),
+        Value::Number(n) => n.to_string(),
+        _ => return None,
+    };
+    (!text.is_empty()).then_some(text)
+}
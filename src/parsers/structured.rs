@@ -0,0 +1,279 @@
+use crate::parsers::{Link, LinkKind, ParseResult, ParserType};
+use serde_json::{Map, Number, Value};
+use std::error::Error;
+
+/// Top-level keys used as the document title, in order of preference
+const TITLE_PATHS: [&[&str]; 3] = [&["title"], &["info", "title"], &["name"]];
+
+/// Configuration options for structured data parsing
+#[derive(Debug, Clone, Copy)]
+pub struct StructuredParserOptions {
+    /// Whether to extract absolute `http(s)` URLs found in string values as links
+    pub extract_links: bool,
+}
+
+impl Default for StructuredParserOptions {
+    fn default() -> Self {
+        Self {
+            extract_links: true,
+        }
+    }
+}
+
+/// Parses JSON, YAML, TOML or CSV content
+///
+/// Documents are flattened into one `key.path: value` line per scalar, CSV files into
+/// one line per row. The parsed value is stored as JSON in the result metadata.
+/// Returns an error if the content isn't valid for the format.
+pub fn parse(
+    content: &str,
+    parser_type: ParserType,
+    options: &StructuredParserOptions,
+) -> Result<ParseResult, Box<dyn Error + Send + Sync>> {
+    let (value, content) = match parser_type {
+        ParserType::Json => {
+            let value: Value = serde_json::from_str(content)?;
+            let text = flatten(&value);
+            (value, text)
+        }
+        ParserType::Yaml => {
+            let value = parse_yaml(content)?;
+            let text = flatten(&value);
+            (value, text)
+        }
+        ParserType::Toml => {
+            let value = toml_to_json(toml::Value::Table(content.parse::<toml::Table>()?));
+            let text = flatten(&value);
+            (value, text)
+        }
+        ParserType::Csv => parse_csv(content)?,
+        _ => return Err(format!("{:?} is not a structured data format", parser_type).into()),
+    };
+
+    let mut link_details = Vec::new();
+    if options.extract_links {
+        collect_links(&value, &mut String::new(), &mut link_details);
+    }
+    let links = link_details.iter().map(|l| l.href.clone()).collect();
+
+    let mut result = ParseResult::new(content, links);
+    result.title = TITLE_PATHS.iter().find_map(|path| {
+        path.iter()
+            .try_fold(&value, |v, key| v.get(key))
+            .and_then(Value::as_str)
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+    });
+    result.link_details = link_details;
+    result.metadata.parsed_value = Some(value);
+    Ok(result)
+}
+
+/// Parses YAML, turning a stream of several documents into an array
+fn parse_yaml(content: &str) -> Result<Value, serde_norway::Error> {
+    let mut documents = Vec::new();
+    for document in serde_norway::Deserializer::from_str(content) {
+        let value = <serde_norway::Value as serde::Deserialize>::deserialize(document)?;
+        documents.push(yaml_to_json(value));
+    }
+
+    Ok(match documents.len() {
+        0 => Value::Null,
+        1 => documents.remove(0),
+        _ => Value::Array(documents),
+    })
+}
+
+/// Parses CSV (or TSV) with a header row into an array of objects and row text
+fn parse_csv(content: &str) -> Result<(Value, String), csv::Error> {
+    let first_line = content.lines().next().unwrap_or_default();
+    let delimiter = if first_line.contains('\t') && !first_line.contains(',') {
+        b'\t'
+    } else {
+        b','
+    };
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_reader(content.as_bytes());
+
+    let headers: Vec<String> = reader
+        .headers()?
+        .iter()
+        .map(|h| h.trim().to_string())
+        .collect();
+    let mut lines = vec![headers.join(" | ")];
+    let mut rows = Vec::new();
+
+    for record in reader.records() {
+        let record = record?;
+        if record.iter().all(|field| field.trim().is_empty()) {
+            continue;
+        }
+
+        let mut row = Map::new();
+        for (i, field) in record.iter().enumerate() {
+            let key = headers
+                .get(i)
+                .filter(|h| !h.is_empty())
+                .cloned()
+                .unwrap_or_else(|| format!("column_{}", i + 1));
+            row.insert(key, Value::String(field.trim().to_string()));
+        }
+        lines.push(record.iter().map(str::trim).collect::<Vec<_>>().join(" | "));
+        rows.push(Value::Object(row));
+    }
+
+    Ok((Value::Array(rows), lines.join("\n")))
+}
+
+/// Flattens a value into `key.path: value` lines
+fn flatten(value: &Value) -> String {
+    let mut lines = Vec::new();
+    flatten_into(value, &mut String::new(), &mut lines);
+    lines.join("\n")
+}
+
+fn flatten_into(value: &Value, path: &mut String, lines: &mut Vec<String>) {
+    let scalar = match value {
+        Value::Null => return,
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) if s.trim().is_empty() => return,
+        // Multi-line strings (descriptions) are kept on one line
+        Value::String(s) => s.split_whitespace().collect::<Vec<_>>().join(" "),
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                let len = path.len();
+                path.push_str(&format!("[{}]", i));
+                flatten_into(item, path, lines);
+                path.truncate(len);
+            }
+            return;
+        }
+        Value::Object(map) => {
+            for (key, item) in map {
+                let len = path.len();
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(key);
+                flatten_into(item, path, lines);
+                path.truncate(len);
+            }
+            return;
+        }
+    };
+
+    if path.is_empty() {
+        lines.push(scalar);
+    } else {
+        lines.push(format!("{}: {}", path, scalar));
+    }
+}
+
+/// Collects string values that are absolute `http(s)` URLs
+fn collect_links(value: &Value, path: &mut String, links: &mut Vec<Link>) {
+    match value {
+        Value::String(s) => {
+            let href = s.trim();
+            let is_url = !href.contains(char::is_whitespace)
+                && url::Url::parse(href).is_ok_and(|u| matches!(u.scheme(), "http" | "https"));
+            if is_url {
+                let field = path
+                    .rsplit(['.', '['])
+                    .next()
+                    .unwrap_or_default()
+                    .trim_end_matches(']');
+                links.push(Link {
+                    url: href.to_string(),
+                    href: href.to_string(),
+                    kind: LinkKind::Data,
+                    text: (!path.is_empty()).then(|| path.clone()),
+                    rel: Vec::new(),
+                    nofollow: false,
+                    element: field.to_string(),
+                    hreflang: None,
+                });
+            }
+        }
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                let len = path.len();
+                path.push_str(&format!("[{}]", i));
+                collect_links(item, path, links);
+                path.truncate(len);
+            }
+        }
+        Value::Object(map) => {
+            for (key, item) in map {
+                let len = path.len();
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(key);
+                collect_links(item, path, links);
+                path.truncate(len);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Converts a YAML value to JSON, stringifying non-string keys (e.g. HTTP status codes)
+fn yaml_to_json(value: serde_norway::Value) -> Value {
+    match value {
+        serde_norway::Value::Null => Value::Null,
+        serde_norway::Value::Bool(b) => Value::Bool(b),
+        serde_norway::Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                Value::from(i)
+            } else if let Some(u) = n.as_u64() {
+                Value::from(u)
+            } else {
+                n.as_f64()
+                    .and_then(Number::from_f64)
+                    .map_or(Value::Null, Value::Number)
+            }
+        }
+        serde_norway::Value::String(s) => Value::String(s),
+        serde_norway::Value::Sequence(items) => {
+            Value::Array(items.into_iter().map(yaml_to_json).collect())
+        }
+        serde_norway::Value::Mapping(mapping) => Value::Object(
+            mapping
+                .into_iter()
+                .map(|(key, value)| (yaml_key(key), yaml_to_json(value)))
+                .collect(),
+        ),
+        serde_norway::Value::Tagged(tagged) => yaml_to_json(tagged.value),
+    }
+}
+
+/// Renders a YAML mapping key as a string
+fn yaml_key(key: serde_norway::Value) -> String {
+    match yaml_to_json(key) {
+        Value::String(s) => s,
+        Value::Null => "null".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Converts a TOML value to JSON, rendering dates and times as strings
+fn toml_to_json(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(i) => Value::from(i),
+        toml::Value::Float(f) => Number::from_f64(f).map_or(Value::Null, Value::Number),
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(dt) => Value::String(dt.to_string()),
+        toml::Value::Array(items) => Value::Array(items.into_iter().map(toml_to_json).collect()),
+        toml::Value::Table(table) => Value::Object(
+            table
+                .into_iter()
+                .map(|(key, value)| (key, toml_to_json(value)))
+                .collect(),
+        ),
+    }
+}
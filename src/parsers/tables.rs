@@ -0,0 +1,280 @@
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+
+/// Largest `colspan`/`rowspan` honored, as in the HTML table processing model
+pub(crate) const MAX_COLSPAN: usize = 1000;
+const MAX_ROWSPAN: usize = 65534;
+
+/// A table extracted from an HTML document
+///
+/// Cells spanning several columns or rows are repeated in each position they
+/// cover, so every row has one value per column.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Table {
+    /// Text of the `<caption>` element
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>,
+
+    /// Column headers (empty if the table has no header row)
+    #[serde(default)]
+    pub headers: Vec<String>,
+
+    /// Body rows, each with one cell per column
+    #[serde(default)]
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    /// Number of columns in the table
+    pub fn column_count(&self) -> usize {
+        self.rows
+            .iter()
+            .map(Vec::len)
+            .chain(std::iter::once(self.headers.len()))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Renders the table as a GitHub-flavored Markdown table
+    ///
+    /// Tables without headers use their first row as the header row.
+    pub fn to_markdown(&self) -> String {
+        let columns = self.column_count();
+        if columns == 0 {
+            return String::new();
+        }
+
+        let (headers, rows) = if self.headers.is_empty() {
+            (&self.rows[0], &self.rows[1..])
+        } else {
+            (&self.headers, &self.rows[..])
+        };
+
+        let mut lines = Vec::new();
+        if let Some(caption) = &self.caption {
+            lines.push(format!("**{}**", caption));
+            lines.push(String::new());
+        }
+        lines.push(markdown_row(headers, columns));
+        lines.push(format!("|{}", " --- |".repeat(columns)));
+        for row in rows {
+            lines.push(markdown_row(row, columns));
+        }
+        lines.join("\n")
+    }
+
+    /// Renders the table as plain text, one line per row
+    ///
+    /// With headers, each cell is labelled with its column (`Name: value; Type: string`)
+    /// so rows read on their own; without headers, cells are separated by ` | `.
+    pub fn to_text(&self) -> String {
+        let mut lines = Vec::new();
+        if let Some(caption) = &self.caption {
+            lines.push(caption.clone());
+        }
+
+        for row in &self.rows {
+            let line = if self.headers.is_empty() {
+                row.join(" | ")
+            } else {
+                row.iter()
+                    .enumerate()
+                    .filter(|(_, cell)| !cell.is_empty())
+                    .map(
+                        |(i, cell)| match self.headers.get(i).filter(|h| !h.is_empty()) {
+                            Some(header) => format!("{}: {}", header, cell),
+                            None => cell.clone(),
+                        },
+                    )
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            };
+            lines.push(line);
+        }
+        lines.join("\n")
+    }
+}
+
+/// Extracts all data tables from a document, in document order
+///
+/// Tables marked `role="presentation"` (layout tables) and tables without any
+/// rows are skipped. Nested tables are extracted separately.
+pub fn extract_tables(doc: &Html) -> Vec<Table> {
+    let selector = Selector::parse("table").unwrap();
+    doc.select(&selector)
+        .filter(|table| {
+            !matches!(
+                table.value().attr("role"),
+                Some("presentation") | Some("none")
+            )
+        })
+        .filter_map(extract_table)
+        .collect()
+}
+
+/// Extracts a single table element
+fn extract_table(table: ElementRef) -> Option<Table> {
+    let mut caption = None;
+    let mut rows: Vec<(ElementRef, bool)> = Vec::new();
+
+    for child in table.children().filter_map(ElementRef::wrap) {
+        match child.value().name() {
+            "caption" => caption = Some(cell_text(child)).filter(|c| !c.is_empty()),
+            "tr" => rows.push((child, false)),
+            section @ ("thead" | "tbody" | "tfoot") => {
+                for row in child.children().filter_map(ElementRef::wrap) {
+                    if row.value().name() == "tr" {
+                        rows.push((row, section == "thead"));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let grid = build_grid(&rows);
+    if grid.is_empty() {
+        return None;
+    }
+
+    // Header rows are the `<thead>` rows, or leading rows made only of `<th>` cells
+    let header_count = if rows.iter().any(|(_, in_head)| *in_head) {
+        rows.iter().take_while(|(_, in_head)| *in_head).count()
+    } else {
+        rows.iter()
+            .take_while(|(row, _)| is_header_row(*row))
+            .count()
+    };
+    let header_count = header_count.min(grid.len());
+
+    let columns = grid.iter().map(Vec::len).max().unwrap_or(0);
+    let headers = (0..columns)
+        .map(|column| {
+            let mut parts: Vec<&str> = Vec::new();
+            for row in &grid[..header_count] {
+                let text = row.get(column).map(String::as_str).unwrap_or_default();
+                if !text.is_empty() && !parts.contains(&text) {
+                    parts.push(text);
+                }
+            }
+            parts.join(" / ")
+        })
+        .collect::<Vec<_>>();
+    let headers = if headers.iter().all(String::is_empty) {
+        Vec::new()
+    } else {
+        headers
+    };
+
+    let rows = grid
+        .into_iter()
+        .skip(header_count)
+        .map(|mut row| {
+            row.resize(columns, String::new());
+            row
+        })
+        .filter(|row| row.iter().any(|cell| !cell.is_empty()))
+        .collect::<Vec<_>>();
+
+    if headers.is_empty() && rows.is_empty() {
+        return None;
+    }
+
+    Some(Table {
+        caption,
+        headers,
+        rows,
+    })
+}
+
+/// Lays out cells in a grid, repeating cells that span several columns or rows
+fn build_grid(rows: &[(ElementRef, bool)]) -> Vec<Vec<String>> {
+    let mut grid: Vec<Vec<Option<String>>> = vec![Vec::new(); rows.len()];
+
+    for (r, (row, _)) in rows.iter().enumerate() {
+        let mut column = 0;
+        for cell in row.children().filter_map(ElementRef::wrap) {
+            if !matches!(cell.value().name(), "td" | "th") {
+                continue;
+            }
+
+            // Skip positions already filled by cells spanning down from earlier rows
+            while grid[r].get(column).is_some_and(Option::is_some) {
+                column += 1;
+            }
+
+            let colspan = span(cell, "colspan", MAX_COLSPAN);
+            let rowspan = span(cell, "rowspan", MAX_ROWSPAN).min(rows.len() - r);
+            let text = cell_text(cell);
+
+            for grid_row in grid.iter_mut().skip(r).take(rowspan) {
+                if grid_row.len() < column + colspan {
+                    grid_row.resize(column + colspan, None);
+                }
+                for slot in &mut grid_row[column..column + colspan] {
+                    *slot = Some(text.clone());
+                }
+            }
+            column += colspan;
+        }
+    }
+
+    grid.into_iter()
+        .map(|row| row.into_iter().map(Option::unwrap_or_default).collect())
+        .collect()
+}
+
+/// Reads a `colspan` or `rowspan` attribute, treating invalid values as 1
+fn span(cell: ElementRef, attr: &str, max: usize) -> usize {
+    cell.value()
+        .attr(attr)
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(1)
+        .min(max)
+}
+
+/// Returns whether every cell in a row is a `<th>`
+fn is_header_row(row: ElementRef) -> bool {
+    let mut cells = row
+        .children()
+        .filter_map(ElementRef::wrap)
+        .filter(|c| matches!(c.value().name(), "td" | "th"))
+        .peekable();
+    cells.peek().is_some() && cells.all(|c| c.value().name() == "th")
+}
+
+/// Collapses the text of a cell, leaving out nested tables
+fn cell_text(cell: ElementRef) -> String {
+    let mut parts = Vec::new();
+    collect_text(cell, &mut parts);
+    parts
+        .join(" ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn collect_text<'a>(element: ElementRef<'a>, parts: &mut Vec<&'a str>) {
+    for child in element.children() {
+        if let Some(text) = child.value().as_text() {
+            parts.push(text);
+        } else if let Some(child) = ElementRef::wrap(child)
+            && !matches!(child.value().name(), "table" | "script" | "style")
+        {
+            collect_text(child, parts);
+        }
+    }
+}
+
+/// Renders one Markdown table row, escaping pipes
+fn markdown_row(cells: &[String], columns: usize) -> String {
+    let mut line = String::from("|");
+    for i in 0..columns {
+        let cell = cells.get(i).map(String::as_str).unwrap_or_default();
+        line.push(' ');
+        line.push_str(&cell.replace('|', "\\|"));
+        line.push_str(" |");
+    }
+    line
+}
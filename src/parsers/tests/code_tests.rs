@@ -0,0 +1,116 @@
+use crate::parsers::code::{self, CodeBlock};
+use crate::parsers::{Parser, ParserType, html, markdown, rst};
+use scraper::Html;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html_code_blocks() {
+        let html_content = r##"<html><body>
+            <h2>Install <a class="headerlink" href="#install">¶</a></h2>
+            <pre><code class="language-rust">fn main() {
+    println!("hi");
+}
+</code></pre>
+            <h2>Configure</h2>
+            <div class="highlight-python notranslate"><div class="highlight"><pre><span class="k">import</span> os
+<span class="n">os</span>.getcwd()</pre></div></div>
+            <pre>no language</pre>
+            <p>Inline <code>code</code> is not a block.</p>
+            </body></html>"##;
+
+        let result = Parser::parse(html_content, ParserType::Html);
+
+        assert_eq!(
+            result.code_blocks,
+            vec![
+                CodeBlock {
+                    code: "fn main() {\n    println!(\"hi\");\n}".to_string(),
+                    language: Some("rust".to_string()),
+                    heading: Some("Install".to_string()),
+                },
+                CodeBlock {
+                    code: "import os\nos.getcwd()".to_string(),
+                    language: Some("python".to_string()),
+                    heading: Some("Configure".to_string()),
+                },
+                CodeBlock {
+                    code: "no language".to_string(),
+                    language: None,
+                    heading: Some("Configure".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_html_line_numbers_and_data_lang() {
+        let html_content = r#"<body class="language-en">
+            <pre data-lang="Shell"><span class="lineno">1 </span>cargo build<br><span class="lineno">2 </span>cargo test</pre>
+            </body>"#;
+
+        let doc = Html::parse_document(html_content);
+        let blocks = code::extract_code_blocks(&doc);
+
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].code, "cargo build\ncargo test");
+        assert_eq!(blocks[0].language.as_deref(), Some("shell"));
+        assert_eq!(blocks[0].heading, None);
+
+        // Page-level classes aren't mistaken for the block language
+        let doc = Html::parse_document(r#"<body class="language-en"><pre>x = 1</pre></body>"#);
+        assert_eq!(code::extract_code_blocks(&doc)[0].language, None);
+    }
+
+    #[test]
+    fn test_markdown_code_blocks() {
+        let source =
+            "# Usage\n\n```rust,ignore\nlet x = 1;\n\n  let y = 2;\n```\n\n    indented();\n";
+
+        let result = markdown::parse(source);
+
+        assert_eq!(result.code_blocks.len(), 2);
+        assert_eq!(result.code_blocks[0].code, "let x = 1;\n\n  let y = 2;");
+        assert_eq!(result.code_blocks[0].language.as_deref(), Some("rust"));
+        assert_eq!(result.code_blocks[0].heading.as_deref(), Some("Usage"));
+        assert_eq!(result.code_blocks[1].code, "indented();");
+        assert_eq!(result.code_blocks[1].language, None);
+    }
+
+    #[test]
+    fn test_rst_code_blocks() {
+        let source = "Setup\n=====\n\n.. highlight:: bash\n\nRun::\n\n    make install\n\n\
+                      .. code-block:: python\n   :linenos:\n\n   def f():\n       return 1\n";
+
+        let result = rst::parse(source);
+
+        assert_eq!(
+            result.code_blocks,
+            vec![
+                CodeBlock {
+                    code: "make install".to_string(),
+                    language: Some("bash".to_string()),
+                    heading: Some("Setup".to_string()),
+                },
+                CodeBlock {
+                    code: "def f():\n    return 1".to_string(),
+                    language: Some("python".to_string()),
+                    heading: Some("Setup".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_normalize_language() {
+        assert_eq!(code::normalize_language("{.Rust}").as_deref(), Some("rust"));
+        assert_eq!(
+            code::normalize_language("python title=\"x.py\"").as_deref(),
+            Some("python")
+        );
+        assert_eq!(code::normalize_language("  "), None);
+        assert!(html::parse("<pre>   </pre>").code_blocks.is_empty());
+    }
+}
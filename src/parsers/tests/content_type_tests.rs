@@ -0,0 +1,163 @@
+use crate::parsers::{ContentTypeDetector, ParserMapping, ParserType};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_content_type() {
+        assert_eq!(
+            ParserType::from_content_type("text/html; charset=utf-8"),
+            Some(ParserType::Html)
+        );
+        assert_eq!(
+            ParserType::from_content_type("application/pdf"),
+            Some(ParserType::Pdf)
+        );
+        assert_eq!(
+            ParserType::from_content_type("image/png"),
+            Some(ParserType::Other)
+        );
+
+        // Generic types don't identify the format
+        assert_eq!(
+            ParserType::from_content_type("application/octet-stream"),
+            None
+        );
+        assert_eq!(ParserType::from_content_type("text/plain"), None);
+    }
+
+    #[test]
+    fn test_sniff() {
+        assert_eq!(ParserType::sniff(b"%PDF-1.7\n..."), Some(ParserType::Pdf));
+        assert_eq!(
+            ParserType::sniff(b"\xef\xbb\xbf  <!DOCTYPE html><html></html>"),
+            Some(ParserType::Html)
+        );
+        assert_eq!(
+            ParserType::sniff(b"\x89PNG\r\n\x1a\n\x00\x00"),
+            Some(ParserType::Other)
+        );
+        assert_eq!(ParserType::sniff(b"name: value\n"), None);
+    }
+
+    #[test]
+    fn test_header_beats_url() {
+        let detector = ContentTypeDetector::default();
+
+        // A PDF served from a URL without an extension
+        let result = detector.detect(
+            "https://example.com/download?id=5",
+            Some("application/pdf"),
+            None,
+        );
+        assert_eq!(result, ParserType::Pdf);
+
+        // Plain text served from an extensionless URL
+        let result = detector.detect("https://example.com/api/spec", Some("text/plain"), None);
+        assert_eq!(result, ParserType::Text);
+    }
+
+    #[test]
+    fn test_sniff_beats_url() {
+        let detector = ContentTypeDetector::default();
+        let result = detector.detect(
+            "https://example.com/download?id=5",
+            Some("application/octet-stream"),
+            Some(b"%PDF-1.4"),
+        );
+        assert_eq!(result, ParserType::Pdf);
+
+        // Nothing conclusive - fall back to the URL
+        let result = detector.detect("https://example.com/notes.txt", None, Some(b"hello"));
+        assert_eq!(result, ParserType::Text);
+        assert_eq!(
+            detector.detect_explicit("https://example.com/notes.txt", None, Some(b"hello")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_configured_mappings() {
+        let detector = ContentTypeDetector::new(&[
+            ParserMapping {
+                mime_type: Some("application/*+json".to_string()),
+                url_pattern: None,
+                parser: ParserType::Text,
+            },
+            ParserMapping {
+                mime_type: None,
+                url_pattern: Some(r"/raw/".to_string()),
+                parser: ParserType::Text,
+            },
+        ])
+        .unwrap();
+
+        assert_eq!(
+            detector.detect(
+                "https://example.com/api",
+                Some("application/vnd.api+json"),
+                None
+            ),
+            ParserType::Text
+        );
+
+        // Mappings take precedence over the Content-Type header
+        assert_eq!(
+            detector.detect("https://example.com/raw/page", Some("text/html"), None),
+            ParserType::Text
+        );
+    }
+
+    #[test]
+    fn test_detect_from_url() {
+        let detector = ContentTypeDetector::default();
+        assert_eq!(
+            detector.detect_from_url("https://example.com/guide.html?v=2#intro"),
+            Some(ParserType::Html)
+        );
+        assert_eq!(
+            detector.detect_from_url("https://example.com/report.pdf"),
+            Some(ParserType::Pdf)
+        );
+        // Extension-less URLs could be anything until the browser loads them
+        assert_eq!(detector.detect_from_url("https://example.com/guide"), None);
+        // Archives are downloaded so they can be expanded
+        assert_eq!(
+            detector.detect_from_url("https://example.com/docs.tar.gz"),
+            Some(ParserType::Other)
+        );
+
+        let detector = ContentTypeDetector::new(&[
+            ParserMapping {
+                mime_type: None,
+                url_pattern: Some(r"/raw/".to_string()),
+                parser: ParserType::Text,
+            },
+            ParserMapping {
+                mime_type: Some("text/html".to_string()),
+                url_pattern: Some(r"/legacy/".to_string()),
+                parser: ParserType::Text,
+            },
+        ])
+        .unwrap();
+        assert_eq!(
+            detector.detect_from_url("https://example.com/raw/page.html"),
+            Some(ParserType::Text)
+        );
+        // A mapping on the Content-Type needs the response
+        assert_eq!(
+            detector.detect_from_url("https://example.com/legacy/page.pdf"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_mapping_deserialization() {
+        let mapping: ParserMapping =
+            serde_json::from_str(r#"{"mime_type": "application/x-wiki", "parser": "text"}"#)
+                .unwrap();
+        assert_eq!(mapping.parser, ParserType::Text);
+        assert!(mapping.url_pattern.is_none());
+    }
+}
@@ -0,0 +1,94 @@
+use crate::parsers::encoding::{self, EncodingSource};
+use crate::parsers::{Parser, ParserType};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bom() {
+        let decoded = encoding::decode(b"\xef\xbb\xbfHello", Some("text/plain; charset=latin1"));
+        assert_eq!(decoded.text, "Hello");
+        assert_eq!(decoded.encoding, "UTF-8");
+        assert_eq!(decoded.source, EncodingSource::Bom);
+
+        // UTF-16LE with a BOM
+        let decoded = encoding::decode(b"\xff\xfeH\x00i\x00", None);
+        assert_eq!(decoded.text, "Hi");
+        assert_eq!(decoded.source, EncodingSource::Bom);
+    }
+
+    #[test]
+    fn test_content_type_charset() {
+        // "Příliš" in windows-1250
+        let bytes = b"P\xf8\xedli\x9a";
+        let decoded = encoding::decode(bytes, Some("text/plain; charset=\"windows-1250\""));
+        assert_eq!(decoded.text, "Příliš");
+        assert_eq!(decoded.encoding, "windows-1250");
+        assert_eq!(decoded.source, EncodingSource::ContentType);
+    }
+
+    #[test]
+    fn test_meta_charset() {
+        // "日本語" in Shift_JIS
+        let mut bytes = b"<html><head><meta charset=\"shift_jis\"></head><body>".to_vec();
+        bytes.extend_from_slice(b"\x93\xfa\x96\x7b\x8c\xea");
+        bytes.extend_from_slice(b"</body></html>");
+
+        let decoded = encoding::decode(&bytes, Some("text/html"));
+        assert!(decoded.text.contains("日本語"));
+        assert_eq!(decoded.encoding, "Shift_JIS");
+        assert_eq!(decoded.source, EncodingSource::MetaTag);
+
+        // The http-equiv form is recognised too
+        let bytes = b"<meta http-equiv=\"Content-Type\" content=\"text/html; charset=ISO-8859-2\">";
+        let (encoding, source) = encoding::detect(bytes, None);
+        assert_eq!(encoding.name(), "ISO-8859-2");
+        assert_eq!(source, EncodingSource::MetaTag);
+    }
+
+    #[test]
+    fn test_xml_declaration() {
+        let bytes = b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?><doc>caf\xe9</doc>";
+        let decoded = encoding::decode(bytes, None);
+        assert_eq!(
+            decoded.text,
+            "<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?><doc>café</doc>"
+        );
+        assert_eq!(decoded.source, EncodingSource::XmlDeclaration);
+    }
+
+    #[test]
+    fn test_statistical_detection() {
+        // Plain UTF-8 without any declaration
+        let decoded = encoding::decode("Grüße aus Köln".as_bytes(), None);
+        assert_eq!(decoded.text, "Grüße aus Köln");
+        assert_eq!(decoded.encoding, "UTF-8");
+        assert_eq!(decoded.source, EncodingSource::Detected);
+
+        // Undeclared Shift_JIS
+        let bytes = b"\x82\xb1\x82\xea\x82\xcd\x93\xfa\x96\x7b\x8c\xea\x82\xcc\x95\xb6\x8f\xcd\x82\xc5\x82\xb7\x81\x42";
+        let decoded = encoding::decode(bytes, None);
+        assert_eq!(decoded.text, "これは日本語の文章です。");
+        assert_eq!(decoded.encoding, "Shift_JIS");
+    }
+
+    #[test]
+    fn test_parse_bytes_records_encoding() {
+        let result = Parser::parse_bytes(
+            b"Za\xbf\xf3\xb3\xe6 g\xea\xb6l\xb1 ja\xbc\xf1",
+            ParserType::Text,
+            Some("text/plain; charset=iso-8859-2"),
+        );
+        assert_eq!(result.content, "Zażółć gęślą jaźń");
+        assert_eq!(result.metadata.encoding.as_deref(), Some("ISO-8859-2"));
+        assert_eq!(
+            result.metadata.encoding_source,
+            Some(EncodingSource::ContentType)
+        );
+        assert_eq!(
+            result.metadata.content_type.as_deref(),
+            Some("text/plain; charset=iso-8859-2")
+        );
+    }
+}
@@ -0,0 +1,153 @@
+use crate::parsers::epub;
+use crate::parsers::{EpubMode, Parser, ParserOptions, ParserType};
+use std::io::{Cursor, Write};
+use zip::ZipWriter;
+use zip::write::SimpleFileOptions;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOOK_URL: &str = "https://example.org/manual.epub";
+
+    fn chapter(title: &str, body: &str) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<html xmlns="http://www.w3.org/1999/xhtml"><head><title>{title}</title></head><body>{body}</body></html>"#
+        )
+    }
+
+    /// Builds an EPUB 3 book whose spine order differs from its manifest order
+    fn book() -> Vec<u8> {
+        let container = r#"<container xmlns="urn:oasis:names:tc:opendocument:xmlns:container" version="1.0">
+            <rootfiles><rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/></rootfiles>
+        </container>"#;
+        let opf = r#"<package xmlns="http://www.idpf.org/2007/opf" version="3.0">
+            <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+                <dc:title>Operator Manual</dc:title>
+                <dc:creator>Ada Lovelace</dc:creator>
+                <dc:creator>Charles Babbage</dc:creator>
+                <dc:language>en-GB</dc:language>
+            </metadata>
+            <manifest>
+                <item id="nav" href="nav.xhtml" media-type="application/xhtml+xml" properties="nav"/>
+                <item id="ch2" href="text/ch2.xhtml" media-type="application/xhtml+xml"/>
+                <item id="ch1" href="text/ch1.xhtml" media-type="application/xhtml+xml"/>
+                <item id="cover" href="cover.png" media-type="image/png"/>
+            </manifest>
+            <spine><itemref idref="ch1"/><itemref idref="cover"/><itemref idref="ch2"/></spine>
+        </package>"#;
+        let nav = chapter(
+            "Contents",
+            r#"<nav epub:type="toc" xmlns:epub="http://www.idpf.org/2007/ops"><ol>
+                <li><a href="text/ch1.xhtml">1. Installing</a></li>
+                <li><a href="text/ch1.xhtml#verify">Verifying</a></li>
+                <li><a href="text/ch2.xhtml">2. Running</a></li>
+            </ol></nav>"#,
+        );
+        let ch1 = chapter(
+            "Operator Manual",
+            r#"<h1>Installing</h1><p>Unpack it, then see <a href="ch2.xhtml#start">starting</a>.</p>
+               <h2 id="verify">Verify</h2><p>Check the <a href="https://example.org/sums">checksums</a>.</p>"#,
+        );
+        let ch2 = chapter(
+            "Operator Manual",
+            r#"<h1 id="start">Running</h1><p>Start the service.</p>"#,
+        );
+
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in [
+            ("mimetype", "application/epub+zip"),
+            ("META-INF/container.xml", container),
+            ("OEBPS/content.opf", opf),
+            ("OEBPS/nav.xhtml", nav.as_str()),
+            ("OEBPS/text/ch1.xhtml", ch1.as_str()),
+            ("OEBPS/text/ch2.xhtml", ch2.as_str()),
+        ] {
+            zip.start_file(name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_detection() {
+        let bytes = book();
+        assert_eq!(ParserType::sniff(&bytes), Some(ParserType::Epub));
+        assert_eq!(
+            ParserType::from_content_type("application/epub+zip"),
+            Some(ParserType::Epub)
+        );
+    }
+
+    #[test]
+    fn test_combined() {
+        let bytes = book();
+        let result = epub::parse(&bytes, Some(BOOK_URL), &ParserOptions::default()).unwrap();
+
+        assert_eq!(result.title.as_deref(), Some("Operator Manual"));
+        assert_eq!(
+            result.metadata.authors,
+            vec!["Ada Lovelace", "Charles Babbage"]
+        );
+        assert_eq!(result.language.as_deref(), Some("en-gb"));
+
+        // Spine order, not manifest order
+        let install = result.content.find("Unpack it").unwrap();
+        let run = result.content.find("Start the service").unwrap();
+        assert!(install < run);
+
+        // Links between chapters point into the book itself
+        let urls: Vec<&str> = result.link_details.iter().map(|l| l.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            vec![
+                "https://example.org/manual.epub#start",
+                "https://example.org/sums"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_chapters() {
+        let bytes = book();
+        let pages = epub::parse_book(&bytes, Some(BOOK_URL), &ParserOptions::default())
+            .unwrap()
+            .into_pages(BOOK_URL);
+
+        let urls: Vec<&str> = pages.iter().map(|p| p.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            vec![
+                "https://example.org/manual.epub!/OEBPS/text/ch1.xhtml",
+                "https://example.org/manual.epub!/OEBPS/text/ch2.xhtml",
+            ]
+        );
+        // Titles come from the table of contents, not the repeated <title>
+        assert_eq!(pages[0].title.as_deref(), Some("1. Installing"));
+        assert_eq!(pages[1].title.as_deref(), Some("2. Running"));
+        assert_eq!(pages[1].section_path, vec!["Operator Manual"]);
+        assert_eq!(pages[1].metadata.authors.len(), 2);
+        assert_eq!(pages[1].metadata.archive_url.as_deref(), Some(BOOK_URL));
+        assert_eq!(
+            pages[0].link_details[0].url,
+            "https://example.org/manual.epub!/OEBPS/text/ch2.xhtml#start"
+        );
+    }
+
+    #[test]
+    fn test_parse_bytes() {
+        let options = ParserOptions {
+            epub_mode: EpubMode::Chapters,
+            ..ParserOptions::default()
+        };
+        // Parsing bytes always yields one document; the crawler splits chapters
+        let result = Parser::parse_bytes_with_options(&book(), ParserType::Epub, None, &options);
+        assert!(result.content.contains("Unpack it"));
+        assert!(result.content.contains("Start the service"));
+
+        let result =
+            Parser::parse_bytes_with_options(b"PK\x03\x04broken", ParserType::Epub, None, &options);
+        assert!(result.content.is_empty());
+    }
+}
@@ -0,0 +1,31 @@
+use crate::parsers::html;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html_meta_description_and_robots() {
+        let html_content = r#"<html><head>
+            <title>Guide</title>
+            <meta name="Description" content="  How to install
+                the tool ">
+            <meta name="robots" content="NoIndex, follow">
+            <meta name="robots" content="noindex, noarchive">
+            </head><body><p>Text</p></body></html>"#;
+
+        let result = html::parse(html_content);
+        assert_eq!(
+            result.metadata.description.as_deref(),
+            Some("How to install the tool")
+        );
+        assert_eq!(
+            result.metadata.robots,
+            vec!["noindex", "follow", "noarchive"]
+        );
+
+        let result = html::parse(r#"<meta name="description" content=" ">"#);
+        assert_eq!(result.metadata.description, None);
+        assert!(result.metadata.robots.is_empty());
+    }
+}
@@ -85,8 +85,26 @@ mod tests {
         let types = [
             ("file.txt", ParserType::Text),
             ("file.html", ParserType::Html),
-            ("file.yml", ParserType::Text),
+            ("file.yml", ParserType::Yaml),
+            ("openapi.json", ParserType::Json),
+            ("Cargo.toml", ParserType::Toml),
+            ("data/export.csv", ParserType::Csv),
             ("file.pdf", ParserType::Pdf),
+            ("report.docx", ParserType::Docx),
+            ("budget.xlsx", ParserType::Xlsx),
+            ("slides.pptx", ParserType::Pptx),
+            ("notes.odt", ParserType::OpenDocument),
+            ("manual.epub", ParserType::Epub),
+            ("README.md", ParserType::Markdown),
+            ("docs/index.rst", ParserType::Rst),
+            (
+                "https://docs.example.org/_sources/intro.rst.txt",
+                ParserType::Rst,
+            ),
+            (
+                "https://docs.example.org/_sources/guide.md.txt",
+                ParserType::Markdown,
+            ),
             ("file.jpg", ParserType::Other), // Image should be "other"
             ("https://example.org/page", ParserType::Html), // URL without extension should be HTML
         ];
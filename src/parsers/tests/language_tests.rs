@@ -0,0 +1,106 @@
+use crate::parsers::language::{self, LanguageSource};
+use crate::parsers::{Parser, ParserType, html, links};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html_lang_attribute() {
+        let result = html::parse(r#"<html lang="pt_BR"><body><p>Olá</p></body></html>"#);
+
+        assert_eq!(result.language.as_deref(), Some("pt-br"));
+        assert_eq!(
+            result.metadata.language_source,
+            Some(LanguageSource::HtmlLang)
+        );
+    }
+
+    #[test]
+    fn test_content_language_meta() {
+        let html_content = r#"<html><head>
+            <meta http-equiv="Content-Language" content="de, en">
+            </head><body>Hallo</body></html>"#;
+
+        let result = html::parse(html_content);
+
+        assert_eq!(result.language.as_deref(), Some("de"));
+        assert_eq!(
+            result.metadata.language_source,
+            Some(LanguageSource::ContentLanguage)
+        );
+    }
+
+    #[test]
+    fn test_self_referencing_hreflang() {
+        let html_content = r#"<html><head>
+            <link rel="alternate" hreflang="en" href="/en/guide">
+            <link rel="alternate" hreflang="fr" href="/fr/guide">
+            <link rel="alternate" hreflang="x-default" href="/guide">
+            </head><body>Guide</body></html>"#;
+
+        let result = html::parse_with_url(html_content, "https://example.com/fr/guide#intro");
+
+        assert_eq!(result.language.as_deref(), Some("fr"));
+        assert_eq!(
+            result.metadata.language_source,
+            Some(LanguageSource::Hreflang)
+        );
+
+        let alternates = language::hreflang_alternates(&result.link_details);
+        assert_eq!(
+            alternates,
+            vec![
+                ("en".to_string(), "https://example.com/en/guide"),
+                ("fr".to_string(), "https://example.com/fr/guide")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_detected_from_content() {
+        let text = "La documentation explique comment installer le logiciel et configurer \
+                    le serveur avant de lancer la première analyse du site.";
+
+        let result = Parser::parse(text, ParserType::Text);
+
+        assert_eq!(result.language.as_deref(), Some("fr"));
+        assert_eq!(
+            result.metadata.language_source,
+            Some(LanguageSource::Detected)
+        );
+
+        // Markup takes precedence over the content
+        let html_content = format!(r#"<html lang="en"><body>{}</body></html>"#, text);
+        assert_eq!(html::parse(&html_content).language.as_deref(), Some("en"));
+    }
+
+    #[test]
+    fn test_short_text_is_not_guessed() {
+        assert!(language::detect_from_text("OK").is_none());
+        assert!(
+            Parser::parse("{\"a\": 1}", ParserType::Json)
+                .language
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_language_matching() {
+        assert!(language::matches("en-GB", "en"));
+        assert!(language::matches("en", "EN"));
+        assert!(!language::matches("en", "en-gb"));
+        assert!(!language::matches("eng", "en"));
+        assert!(!language::matches("x-default", "x-default"));
+    }
+
+    #[test]
+    fn test_alternates_require_hreflang() {
+        let links = links::extract_links(
+            r#"<link rel="alternate" type="application/rss+xml" href="/feed.xml">"#,
+            Some("https://example.com/"),
+        );
+
+        assert!(language::hreflang_alternates(&links).is_empty());
+    }
+}
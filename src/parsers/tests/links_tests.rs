@@ -0,0 +1,89 @@
+use crate::parsers::links::{self, LinkKind};
+use crate::parsers::{Parser, html};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_kinds() {
+        let html_content = r#"<html><head>
+            <link rel="next" href="/docs/page-3">
+            <link rel="prev" href="/docs/page-1">
+            <link rel="alternate" hreflang="fr" href="/fr/docs/page-2">
+            <link rel="stylesheet" href="/style.css">
+            <meta http-equiv="refresh" content="5; URL='/docs/moved'">
+            </head><body>
+            <a href="intro.html">Intro <b>page</b></a>
+            <map><area href="/map/a" alt="Region A"></map>
+            <iframe src="/embed/frame.html"></iframe>
+            </body></html>"#;
+
+        let result = links::extract_links(html_content, Some("https://example.com/docs/page-2"));
+        let find = |kind: LinkKind| result.iter().find(|l| l.kind == kind).unwrap();
+
+        assert_eq!(find(LinkKind::Next).url, "https://example.com/docs/page-3");
+        assert_eq!(find(LinkKind::Prev).url, "https://example.com/docs/page-1");
+        assert_eq!(find(LinkKind::Alternate).hreflang.as_deref(), Some("fr"));
+        assert_eq!(
+            find(LinkKind::Resource).url,
+            "https://example.com/style.css"
+        );
+        assert_eq!(
+            find(LinkKind::MetaRefresh).url,
+            "https://example.com/docs/moved"
+        );
+        assert_eq!(
+            find(LinkKind::Anchor).url,
+            "https://example.com/docs/intro.html"
+        );
+        assert_eq!(find(LinkKind::Anchor).text.as_deref(), Some("Intro page"));
+        assert_eq!(find(LinkKind::Area).text.as_deref(), Some("Region A"));
+        assert_eq!(find(LinkKind::Iframe).element, "iframe");
+    }
+
+    #[test]
+    fn test_base_href_resolution() {
+        let html_content = r#"<html><head><base href="/static/v2/"></head>
+            <body><a href="guide.html">Guide</a><frame src="nav.html"></body></html>"#;
+
+        let result =
+            links::extract_links(html_content, Some("https://example.com/docs/index.html"));
+        assert_eq!(result[0].url, "https://example.com/static/v2/guide.html");
+        assert_eq!(result[0].href, "guide.html");
+    }
+
+    #[test]
+    fn test_nofollow() {
+        let html_content = r#"<body>
+            <a href="/a" rel="nofollow noopener">A</a>
+            <a href="/b">B</a>
+            </body>"#;
+        let result = links::extract_links(html_content, Some("https://example.com/"));
+        assert!(result[0].nofollow);
+        assert_eq!(result[0].rel, vec!["nofollow", "noopener"]);
+        assert!(!result[1].nofollow);
+
+        // A robots meta tag marks every link on the page as nofollow
+        let html_content = r#"<head><meta name="robots" content="noindex, nofollow"></head>
+            <body><a href="/a">A</a></body>"#;
+        let result = links::extract_links(html_content, Some("https://example.com/"));
+        assert!(result[0].nofollow);
+    }
+
+    #[test]
+    fn test_unresolved_without_page_url() {
+        // Without a page URL, links keep their raw value
+        let result = html::parse("<body><a href=\"guide.html\">Guide</a></body>");
+        assert_eq!(result.links, vec!["guide.html"]);
+        assert_eq!(result.link_details[0].url, "guide.html");
+    }
+
+    #[test]
+    fn test_parse_from_url_resolves_link_details() {
+        let html_content = "<body><a href=\"../other\">Other</a></body>";
+        let result = Parser::parse_from_url(html_content, "https://example.com/docs/page/");
+        assert_eq!(result.links, vec!["../other"]);
+        assert_eq!(result.link_details[0].url, "https://example.com/docs/other");
+    }
+}
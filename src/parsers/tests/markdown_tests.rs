@@ -0,0 +1,82 @@
+use crate::parsers::{LinkKind, Parser, ParserType, markdown};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown_strips_markup() {
+        let source = "# Getting Started\n\nInstall the **crawler** with `cargo install`.\n\n\
+                      - First *item*\n- Second item\n\n```rust\nfn main() {}\n```\n";
+
+        let result = markdown::parse(source);
+
+        assert!(result.content.contains("Getting Started"));
+        assert!(
+            result
+                .content
+                .contains("Install the crawler with cargo install.")
+        );
+        assert!(result.content.contains("First item"));
+        assert!(result.content.contains("fn main() {}"));
+        assert!(!result.content.contains("**"));
+        assert!(!result.content.contains("```"));
+        assert!(!result.content.contains("# "));
+    }
+
+    #[test]
+    fn test_markdown_headings_and_title() {
+        let source = "Intro text\n\n# Guide\n\n## Setup\n\n### Options\n";
+
+        let result = markdown::parse(source);
+
+        assert_eq!(result.title.as_deref(), Some("Guide"));
+        let levels: Vec<(u8, &str)> = result
+            .headings
+            .iter()
+            .map(|h| (h.level, h.text.as_str()))
+            .collect();
+        assert_eq!(levels, vec![(1, "Guide"), (2, "Setup"), (3, "Options")]);
+    }
+
+    #[test]
+    fn test_markdown_front_matter_title() {
+        let source =
+            "---\ntitle: \"Release Notes\"\ndate: 2024-01-01\n---\n\n# Version 2\n\nChanges.\n";
+
+        let result = markdown::parse(source);
+
+        assert_eq!(result.title.as_deref(), Some("Release Notes"));
+        assert!(!result.content.contains("date:"));
+        assert!(result.content.contains("Changes."));
+    }
+
+    #[test]
+    fn test_markdown_links() {
+        let source = "See [the guide](guide.md) and ![logo](logo.png) or <https://example.com>.\n";
+
+        let result = Parser::parse_from_url(source, "https://example.com/docs/README.md");
+
+        assert_eq!(result.links, vec!["guide.md", "https://example.com"]);
+        assert_eq!(result.link_details[0].kind, LinkKind::Anchor);
+        assert_eq!(result.link_details[0].text.as_deref(), Some("the guide"));
+        assert_eq!(
+            result.link_details[0].url,
+            "https://example.com/docs/guide.md"
+        );
+        assert!(!result.content.contains("logo"));
+    }
+
+    #[test]
+    fn test_markdown_from_bytes() {
+        let result = Parser::parse_bytes(
+            b"# Title\n\nBody text",
+            ParserType::Markdown,
+            Some("text/markdown; charset=utf-8"),
+        );
+
+        assert_eq!(result.title.as_deref(), Some("Title"));
+        assert!(result.content.contains("Body text"));
+        assert_eq!(result.metadata.encoding.as_deref(), Some("UTF-8"));
+    }
+}
@@ -0,0 +1,164 @@
+use crate::parsers::media::{MediaKind, MediaSource};
+use crate::parsers::{Parser, html, markdown, rst};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html_images() {
+        let html_content = r#"<html><body>
+            <figure>
+              <picture>
+                <source srcset="/img/hero.avif" type="image/avif">
+                <source srcset="/img/hero-small.webp 480w, /img/hero.webp 960w" media="(min-width: 600px)">
+                <img src="/img/hero.jpg" alt=" Team photo " title="Our team" width="960" height="540px">
+              </picture>
+              <figcaption>The whole
+                team in 2024</figcaption>
+            </figure>
+            <img src="spacer.gif" alt="">
+            <img src="data:image/gif;base64,R0lGOD" data-src="lazy.png" data-srcset="lazy.png 1x, lazy@2x.png 2x">
+            </body></html>"#;
+
+        let media = html::parse_with_url(html_content, "https://example.com/about/").media;
+        assert_eq!(media.len(), 3);
+
+        let hero = &media[0];
+        assert_eq!(hero.kind, MediaKind::Image);
+        assert_eq!(
+            hero.url.as_deref(),
+            Some("https://example.com/img/hero.jpg")
+        );
+        assert_eq!(hero.alt.as_deref(), Some("Team photo"));
+        assert_eq!(hero.title.as_deref(), Some("Our team"));
+        assert_eq!((hero.width, hero.height), (Some(960), Some(540)));
+        assert_eq!(hero.caption.as_deref(), Some("The whole team in 2024"));
+        assert_eq!(
+            hero.sources,
+            vec![
+                MediaSource {
+                    url: "https://example.com/img/hero.avif".to_string(),
+                    descriptor: None,
+                    mime_type: Some("image/avif".to_string()),
+                    media: None,
+                },
+                MediaSource {
+                    url: "https://example.com/img/hero-small.webp".to_string(),
+                    descriptor: Some("480w".to_string()),
+                    mime_type: None,
+                    media: Some("(min-width: 600px)".to_string()),
+                },
+                MediaSource {
+                    url: "https://example.com/img/hero.webp".to_string(),
+                    descriptor: Some("960w".to_string()),
+                    mime_type: None,
+                    media: Some("(min-width: 600px)".to_string()),
+                },
+            ]
+        );
+        assert!(!hero.is_missing_alt());
+
+        // An empty alt marks a decorative image, which is not the same as a missing alt
+        assert_eq!(media[1].alt.as_deref(), Some(""));
+        assert!(!media[1].is_missing_alt());
+
+        let lazy = &media[2];
+        assert_eq!(
+            lazy.url.as_deref(),
+            Some("https://example.com/about/lazy.png")
+        );
+        assert!(lazy.is_missing_alt());
+        let descriptors: Vec<_> = lazy
+            .sources
+            .iter()
+            .map(|s| (s.url.as_str(), s.descriptor.as_deref()))
+            .collect();
+        assert_eq!(
+            descriptors,
+            vec![
+                ("https://example.com/about/lazy.png", Some("1x")),
+                ("https://example.com/about/lazy@2x.png", Some("2x")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_html_video_and_audio() {
+        let html_content = r#"<html><head><base href="https://cdn.example.com/"></head><body>
+            <video poster="poster.jpg" width="640" title="Demo">
+              <source src="demo.webm" type="video/webm">
+              <source src="demo.mp4" type="video/mp4">
+            </video>
+            <audio src="episode.mp3" aria-label="Episode 1"></audio>
+            </body></html>"#;
+
+        let media = html::parse_with_url(html_content, "https://example.com/").media;
+        assert_eq!(media.len(), 2);
+
+        let video = &media[0];
+        assert_eq!(video.kind, MediaKind::Video);
+        assert_eq!(
+            video.url.as_deref(),
+            Some("https://cdn.example.com/demo.webm")
+        );
+        assert_eq!(
+            video.poster.as_deref(),
+            Some("https://cdn.example.com/poster.jpg")
+        );
+        assert_eq!(video.width, Some(640));
+        assert_eq!(video.title.as_deref(), Some("Demo"));
+        assert_eq!(video.sources.len(), 2);
+        assert_eq!(video.sources[1].mime_type.as_deref(), Some("video/mp4"));
+        assert!(!video.is_missing_alt());
+
+        let audio = &media[1];
+        assert_eq!(audio.kind, MediaKind::Audio);
+        assert_eq!(
+            audio.url.as_deref(),
+            Some("https://cdn.example.com/episode.mp3")
+        );
+        assert_eq!(audio.title.as_deref(), Some("Episode 1"));
+    }
+
+    #[test]
+    fn test_markdown_images() {
+        let markdown_content =
+            "# Guide\n\nSee ![the *architecture* diagram](img/arch.png \"Architecture\") below.\n";
+
+        let result = markdown::parse(markdown_content);
+        assert_eq!(result.media.len(), 1);
+        assert_eq!(result.media[0].url.as_deref(), Some("img/arch.png"));
+        assert_eq!(
+            result.media[0].alt.as_deref(),
+            Some("the architecture diagram")
+        );
+        assert_eq!(result.media[0].title.as_deref(), Some("Architecture"));
+        // Alt text stays out of the readable content
+        assert!(!result.content.contains("diagram"));
+
+        let result = Parser::parse_from_url(markdown_content, "https://example.com/docs/guide.md");
+        assert_eq!(
+            result.media[0].url.as_deref(),
+            Some("https://example.com/docs/img/arch.png")
+        );
+    }
+
+    #[test]
+    fn test_rst_images() {
+        let rst_content = "Guide\n=====\n\n.. image:: logo.png\n   :alt: Project logo\n   :width: 200px\n\n.. figure:: arch.svg\n   :height: 300\n\n   The architecture\n   at a glance.\n\n   More legend text.\n";
+
+        let media = rst::parse(rst_content).media;
+        assert_eq!(media.len(), 2);
+        assert_eq!(media[0].url.as_deref(), Some("logo.png"));
+        assert_eq!(media[0].alt.as_deref(), Some("Project logo"));
+        assert_eq!(media[0].width, Some(200));
+        assert_eq!(media[1].url.as_deref(), Some("arch.svg"));
+        assert_eq!(media[1].height, Some(300));
+        assert_eq!(
+            media[1].caption.as_deref(),
+            Some("The architecture at a glance.")
+        );
+        assert!(media[1].is_missing_alt());
+    }
+}
@@ -1,3 +1,19 @@
+mod code_tests;
+mod content_type_tests;
+mod encoding_tests;
+mod epub_tests;
+mod html_tests;
 mod integration_tests;
+mod language_tests;
+mod links_tests;
+mod markdown_tests;
+mod media_tests;
+mod office_tests;
+mod options_tests;
+mod registry_tests;
+mod rst_tests;
+mod schema_org_tests;
+mod structured_tests;
+mod tables_tests;
 mod text_parser_tests;
 mod text_parser_unit_tests;
@@ -0,0 +1,203 @@
+use crate::parsers::{Parser, ParserOptions, ParserType};
+use std::io::{Cursor, Write};
+use zip::ZipWriter;
+use zip::write::SimpleFileOptions;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a ZIP package from (part name, content) pairs
+    fn package(parts: &[(&str, &str)]) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in parts {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    fn parse(bytes: &[u8], parser_type: ParserType) -> crate::parsers::ParseResult {
+        Parser::parse_bytes_with_options(bytes, parser_type, None, &ParserOptions::default())
+    }
+
+    const W: &str = r#"xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships""#;
+
+    #[test]
+    fn test_docx() {
+        let document = format!(
+            r#"<w:document {W}><w:body>
+                <w:p><w:pPr><w:pStyle w:val="Kop1"/></w:pPr><w:r><w:t>Overview</w:t></w:r></w:p>
+                <w:p><w:r><w:t xml:space="preserve">Read the </w:t></w:r><w:hyperlink r:id="rId9"><w:r><w:t>guide</w:t></w:r></w:hyperlink><w:r><w:delText>old</w:delText></w:r><w:r><w:t>.</w:t></w:r></w:p>
+                <w:tbl>
+                    <w:tr><w:trPr><w:tblHeader/></w:trPr><w:tc><w:p><w:r><w:t>Name</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>Size</w:t></w:r></w:p></w:tc></w:tr>
+                    <w:tr><w:tc><w:tcPr><w:vMerge w:val="restart"/></w:tcPr><w:p><w:r><w:t>a</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>1</w:t></w:r></w:p></w:tc></w:tr>
+                    <w:tr><w:tc><w:tcPr><w:vMerge/></w:tcPr><w:p/></w:tc><w:tc><w:p><w:r><w:t>2</w:t></w:r></w:p></w:tc></w:tr>
+                </w:tbl>
+            </w:body></w:document>"#
+        );
+        // Localized style ID, recognized by its built-in name
+        let styles = format!(
+            r#"<w:styles {W}><w:style w:type="paragraph" w:styleId="Kop1"><w:name w:val="heading 1"/></w:style></w:styles>"#
+        );
+        let rels = r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+            <Relationship Id="rId9" Type="hyperlink" Target="https://example.org/guide" TargetMode="External"/>
+        </Relationships>"#;
+        let bytes = package(&[
+            ("word/document.xml", &document),
+            ("word/styles.xml", &styles),
+            ("word/_rels/document.xml.rels", rels),
+        ]);
+
+        assert_eq!(ParserType::sniff(&bytes), Some(ParserType::Docx));
+        let result = parse(&bytes, ParserType::Docx);
+        assert_eq!(result.title.as_deref(), Some("Overview"));
+        assert_eq!(result.headings.len(), 1);
+        assert_eq!(result.headings[0].level, 1);
+        assert!(result.content.contains("Read the guide."));
+        assert!(!result.content.contains("old"));
+        assert_eq!(result.links, vec!["https://example.org/guide"]);
+
+        assert_eq!(result.tables.len(), 1);
+        assert_eq!(result.tables[0].headers, vec!["Name", "Size"]);
+        assert_eq!(result.tables[0].rows, vec![vec!["a", "1"], vec!["a", "2"]]);
+    }
+
+    #[test]
+    fn test_xlsx() {
+        let workbook = r#"<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">
+            <sheets><sheet name="Prices" sheetId="1" r:id="rId1"/></sheets>
+        </workbook>"#;
+        let rels = r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+            <Relationship Id="rId1" Type="worksheet" Target="worksheets/sheet1.xml"/>
+            <Relationship Id="rId2" Type="sharedStrings" Target="sharedStrings.xml"/>
+        </Relationships>"#;
+        let shared = r#"<sst xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">
+            <si><t>Item</t></si><si><t>Price</t></si><si><r><t>Tea</t></r><r><t xml:space="preserve"> (green)</t></r></si>
+        </sst>"#;
+        let sheet = r#"<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">
+            <sheetData>
+                <row r="1"><c r="A1" t="s"><v>0</v></c><c r="B1" t="s"><v>1</v></c></row>
+                <row r="3"><c r="A3" t="s"><v>2</v></c><c r="C3" t="b"><v>1</v></c><c r="B3"><f>1+1</f><v>2.5</v></c></row>
+            </sheetData>
+        </worksheet>"#;
+        let bytes = package(&[
+            ("xl/workbook.xml", workbook),
+            ("xl/_rels/workbook.xml.rels", rels),
+            ("xl/sharedStrings.xml", shared),
+            ("xl/worksheets/sheet1.xml", sheet),
+        ]);
+
+        assert_eq!(ParserType::sniff(&bytes), Some(ParserType::Xlsx));
+        let result = parse(&bytes, ParserType::Xlsx);
+        assert_eq!(result.title, None);
+        assert_eq!(result.headings[0].text, "Prices");
+        assert_eq!(result.tables.len(), 1);
+        assert_eq!(result.tables[0].headers, vec!["Item", "Price", ""]);
+        assert_eq!(
+            result.tables[0].rows,
+            vec![vec!["Tea (green)", "2.5", "TRUE"]]
+        );
+    }
+
+    #[test]
+    fn test_pptx() {
+        let presentation = r#"<p:presentation xmlns:p="http://schemas.openxmlformats.org/presentationml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">
+            <p:sldIdLst><p:sldId id="257" r:id="rId3"/><p:sldId id="256" r:id="rId2"/></p:sldIdLst>
+        </p:presentation>"#;
+        let rels = r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+            <Relationship Id="rId2" Type="slide" Target="slides/slide1.xml"/>
+            <Relationship Id="rId3" Type="slide" Target="slides/slide2.xml"/>
+        </Relationships>"#;
+        let slide = |title: &str, body: &str| {
+            format!(
+                r#"<p:sld xmlns:p="http://schemas.openxmlformats.org/presentationml/2006/main" xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><p:cSld><p:spTree>
+                    <p:sp><p:nvSpPr><p:nvPr><p:ph type="title"/></p:nvPr></p:nvSpPr><p:txBody><a:p><a:r><a:t>{title}</a:t></a:r></a:p></p:txBody></p:sp>
+                    <p:sp><p:txBody><a:p>{body}</a:p></p:txBody></p:sp>
+                </p:spTree></p:cSld></p:sld>"#
+            )
+        };
+        let first = slide(
+            "Agenda",
+            r#"<a:r><a:rPr><a:hlinkClick r:id="rId1"/></a:rPr><a:t>Roadmap</a:t></a:r>"#,
+        );
+        let second = slide("Results", "<a:r><a:t>Up 5%</a:t></a:r>");
+        let slide_rels = r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+            <Relationship Id="rId1" Type="hyperlink" Target="https://example.org/roadmap" TargetMode="External"/>
+        </Relationships>"#;
+        let bytes = package(&[
+            ("ppt/presentation.xml", presentation),
+            ("ppt/_rels/presentation.xml.rels", rels),
+            ("ppt/slides/slide1.xml", &second),
+            ("ppt/slides/slide2.xml", &first),
+            ("ppt/slides/_rels/slide2.xml.rels", slide_rels),
+        ]);
+
+        assert_eq!(ParserType::sniff(&bytes), Some(ParserType::Pptx));
+        let result = parse(&bytes, ParserType::Pptx);
+        // Slides follow the presentation order, not the part names
+        let headings: Vec<&str> = result.headings.iter().map(|h| h.text.as_str()).collect();
+        assert_eq!(headings, vec!["Agenda", "Results"]);
+        assert_eq!(result.title.as_deref(), Some("Agenda"));
+        assert!(result.content.find("Roadmap") < result.content.find("Up 5%"));
+        assert_eq!(result.links, vec!["https://example.org/roadmap"]);
+    }
+
+    #[test]
+    fn test_odt() {
+        let content = r#"<office:document-content xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0" xmlns:table="urn:oasis:names:tc:opendocument:xmlns:table:1.0" xmlns:xlink="http://www.w3.org/1999/xlink">
+            <office:body><office:text>
+                <text:h text:outline-level="2">Setup</text:h>
+                <text:p>Run<text:s text:c="2"/>it, see <text:a xlink:href="https://example.org/docs">the docs</text:a>.<text:note><text:note-body><text:p>Footnote</text:p></text:note-body></text:note></text:p>
+                <text:list><text:list-item><text:p>First step</text:p></text:list-item></text:list>
+                <table:table table:name="Table1">
+                    <table:table-header-rows><table:table-row><table:table-cell><text:p>Key</text:p></table:table-cell><table:table-cell><text:p>Value</text:p></table:table-cell></table:table-row></table:table-header-rows>
+                    <table:table-row><table:table-cell table:number-columns-spanned="2"><text:p>both</text:p></table:table-cell><table:covered-table-cell/></table:table-row>
+                </table:table>
+            </office:text></office:body>
+        </office:document-content>"#;
+        let meta = r#"<office:document-meta xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:dc="http://purl.org/dc/elements/1.1/">
+            <office:meta><dc:title>Install notes</dc:title></office:meta>
+        </office:document-meta>"#;
+        let bytes = package(&[
+            ("mimetype", "application/vnd.oasis.opendocument.text"),
+            ("content.xml", content),
+            ("meta.xml", meta),
+        ]);
+
+        assert_eq!(ParserType::sniff(&bytes), Some(ParserType::OpenDocument));
+        let result = parse(&bytes, ParserType::OpenDocument);
+        assert_eq!(result.title.as_deref(), Some("Install notes"));
+        assert_eq!(result.headings[0].level, 2);
+        assert!(result.content.contains("Run it, see the docs."));
+        assert!(result.content.contains("First step"));
+        assert!(!result.content.contains("Footnote"));
+        assert_eq!(result.links, vec!["https://example.org/docs"]);
+        assert_eq!(result.tables[0].headers, vec!["Key", "Value"]);
+        assert_eq!(result.tables[0].rows, vec![vec!["both", "both"]]);
+    }
+
+    #[test]
+    fn test_detection_and_invalid_packages() {
+        assert_eq!(
+            ParserType::from_content_type(
+                "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+            ),
+            Some(ParserType::Docx)
+        );
+        assert_eq!(
+            ParserType::from_content_type("application/vnd.oasis.opendocument.spreadsheet"),
+            Some(ParserType::OpenDocument)
+        );
+
+        // Other ZIP archives are still just binary
+        let bytes = package(&[("readme.txt", "hello")]);
+        assert_eq!(ParserType::sniff(&bytes), Some(ParserType::Other));
+
+        // Broken packages parse to nothing rather than failing the crawl
+        let result = parse(b"PK\x03\x04not really a zip", ParserType::Docx);
+        assert!(result.content.is_empty());
+        let result = parse(&bytes, ParserType::Xlsx);
+        assert!(result.content.is_empty());
+    }
+}
@@ -0,0 +1,175 @@
+use crate::config::WebCrawlerConfig;
+use crate::parsers::text::TextParserOptions;
+use crate::parsers::{
+    MarkdownMode, Parser, ParserOptions, ParserOptionsOverride, ParserOptionsSelector, ParserType,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = r#"<html><body>
+        <nav><a href="/">Home</a> <a href="/docs/">Docs</a></nav>
+        <main>
+          <h1>Guide</h1>
+          <p>First   paragraph
+             spans lines.</p>
+          <div class="ad">Buy now</div>
+          <p>Second<br>paragraph.</p>
+        </main>
+        <footer>Copyright</footer>
+        </body></html>"#;
+
+    #[test]
+    fn test_html_text_options() {
+        // HTML ignored text options before; now paragraphs come from block elements
+        let options = ParserOptions::with_text_options(TextParserOptions {
+            preserve_paragraphs: true,
+            ..TextParserOptions::default()
+        });
+        let result = Parser::parse_with_options(PAGE, ParserType::Html, &options);
+        assert_eq!(
+            result.content,
+            "Home Docs\n\nGuide\n\nFirst paragraph spans lines.\n\nBuy now\n\nSecond paragraph.\n\nCopyright"
+        );
+
+        let options = ParserOptions::with_text_options(TextParserOptions {
+            preserve_paragraphs: true,
+            preserve_line_breaks: true,
+            ..TextParserOptions::default()
+        });
+        let result = Parser::parse_with_options(PAGE, ParserType::Html, &options);
+        assert!(result.content.contains("Second\nparagraph."));
+
+        // The defaults still collapse everything into one line
+        let result = Parser::parse(PAGE, ParserType::Html);
+        assert_eq!(
+            result.content,
+            "Home Docs Guide First paragraph spans lines. Buy now Second paragraph. Copyright"
+        );
+    }
+
+    #[test]
+    fn test_html_selectors() {
+        let options = ParserOptions {
+            content_selector: Some("main".to_string()),
+            exclude_selectors: vec![".ad".to_string(), "h1".to_string()],
+            ..ParserOptions::default()
+        };
+        let result = Parser::parse_with_options(PAGE, ParserType::Html, &options);
+        assert_eq!(
+            result.content,
+            "First paragraph spans lines.\n\nSecond paragraph."
+        );
+        // Selectors narrow the text only
+        assert_eq!(result.title, None);
+        assert_eq!(result.headings.len(), 1);
+        assert_eq!(result.links.len(), 2);
+
+        // Missing or invalid selectors fall back to the whole body
+        let options = ParserOptions {
+            content_selector: Some("article[".to_string()),
+            ..ParserOptions::default()
+        };
+        let result = Parser::parse_with_options(PAGE, ParserType::Html, &options);
+        assert!(result.content.starts_with("Home Docs"));
+    }
+
+    #[test]
+    fn test_extract_links_toggle() {
+        let options = ParserOptions {
+            extract_links: false,
+            ..ParserOptions::default()
+        };
+
+        let result = Parser::parse_with_options(PAGE, ParserType::Html, &options);
+        assert!(result.links.is_empty());
+        assert!(result.link_details.is_empty());
+
+        let json = r#"{"homepage": "https://example.com/"}"#;
+        let result = Parser::parse_with_options(json, ParserType::Json, &options);
+        assert!(result.link_details.is_empty());
+        assert_eq!(result.content, "homepage: https://example.com/");
+
+        let markdown = "See [the docs](https://example.com/docs).";
+        let result = Parser::parse_with_options(markdown, ParserType::Markdown, &options);
+        assert!(result.links.is_empty());
+    }
+
+    #[test]
+    fn test_markdown_mode() {
+        let markdown = "Some ~~old~~ text.";
+
+        let gfm =
+            Parser::parse_with_options(markdown, ParserType::Markdown, &ParserOptions::default());
+        assert_eq!(gfm.content, "Some old text.");
+
+        let options = ParserOptions {
+            markdown_mode: MarkdownMode::CommonMark,
+            ..ParserOptions::default()
+        };
+        let strict = Parser::parse_with_options(markdown, ParserType::Markdown, &options);
+        assert_eq!(strict.content, "Some ~~old~~ text.");
+    }
+
+    #[test]
+    fn test_options_for_url() {
+        let api_options = ParserOptions {
+            content_selector: Some("article".to_string()),
+            ..ParserOptions::default()
+        };
+        let selector = ParserOptionsSelector::new(
+            ParserOptions::default(),
+            &[ParserOptionsOverride {
+                url_pattern: "/docs/api/".to_string(),
+                options: api_options.clone(),
+            }],
+        )
+        .unwrap();
+
+        assert_eq!(
+            selector.for_url("https://example.com/docs/api/index.html"),
+            &api_options
+        );
+        assert_eq!(
+            selector.for_url("https://example.com/blog/"),
+            &ParserOptions::default()
+        );
+
+        let invalid = ParserOptionsOverride {
+            url_pattern: "(".to_string(),
+            options: ParserOptions::default(),
+        };
+        assert!(ParserOptionsSelector::new(ParserOptions::default(), &[invalid]).is_err());
+    }
+
+    #[test]
+    fn test_options_in_config() {
+        let config: WebCrawlerConfig = serde_json::from_str(
+            r#"{
+                "start_url": "https://example.com",
+                "parser_options": {
+                    "text": {"preserve_line_breaks": true},
+                    "exclude_selectors": ["nav"]
+                },
+                "parser_overrides": [
+                    {"url_pattern": "\\.md$", "options": {"markdown_mode": "common_mark"}}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert!(config.parser_options.text.preserve_line_breaks);
+        assert!(config.parser_options.text.normalize_whitespace);
+        assert_eq!(config.parser_options.exclude_selectors, vec!["nav"]);
+        assert!(config.parser_options.extract_links);
+        assert_eq!(
+            config.parser_overrides[0].options.markdown_mode,
+            MarkdownMode::CommonMark
+        );
+
+        // Crawls keep paragraphs unless configured otherwise
+        let config = WebCrawlerConfig::new("https://example.com");
+        assert!(config.parser_options.text.preserve_paragraphs);
+    }
+}
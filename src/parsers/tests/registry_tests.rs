@@ -0,0 +1,146 @@
+use crate::parsers::{
+    BuiltinParser, ContentParser, EncodingSource, ParseResult, Parser, ParserMatcher,
+    ParserOptions, ParserRegistry, ParserType,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses `key=value` lines from a made-up wiki export format
+    struct WikiParser;
+
+    impl ContentParser for WikiParser {
+        fn parse(
+            &self,
+            content: &str,
+            _url: Option<&str>,
+            _options: &ParserOptions,
+        ) -> ParseResult {
+            let mut result = ParseResult::content_only(
+                content
+                    .lines()
+                    .filter_map(|line| line.split_once('=').map(|(_, v)| v.trim()))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            );
+            result.title = Some("Wiki".to_string());
+            result
+        }
+    }
+
+    fn upper(content: &str, _url: Option<&str>, _options: &ParserOptions) -> ParseResult {
+        ParseResult::content_only(content.to_uppercase())
+    }
+
+    #[test]
+    fn test_matchers() {
+        let registry = ParserRegistry::new()
+            .with_parser(ParserMatcher::extension(".WIKI"), WikiParser)
+            .with_parser(ParserMatcher::mime_type("application/*+xml"), upper)
+            .with_parser(
+                ParserMatcher::url_pattern(r"^https://intranet\.example\.com/").unwrap(),
+                upper,
+            )
+            .with_parser(ParserMatcher::ParserType(ParserType::Csv), WikiParser);
+
+        let options = ParserOptions::default();
+        let wiki = registry
+            .find(
+                "https://example.com/page.wiki?rev=2",
+                None,
+                ParserType::Html,
+            )
+            .unwrap();
+        assert_eq!(
+            wiki.parse("a = one\nb = two", None, &options).content,
+            "one two"
+        );
+
+        let xml = registry
+            .find(
+                "https://example.com/feed",
+                Some("application/atom+xml; charset=utf-8"),
+                ParserType::Text,
+            )
+            .unwrap();
+        assert_eq!(xml.parse("<feed/>", None, &options).content, "<FEED/>");
+
+        assert!(
+            registry
+                .find("https://intranet.example.com/x", None, ParserType::Html)
+                .is_some()
+        );
+        assert!(
+            registry
+                .find("https://example.com/data.csv", None, ParserType::Csv)
+                .is_some()
+        );
+        assert!(
+            registry
+                .find(
+                    "https://example.com/wiki/",
+                    Some("text/html"),
+                    ParserType::Html
+                )
+                .is_none()
+        );
+        assert!(ParserMatcher::url_pattern("(").is_err());
+    }
+
+    #[test]
+    fn test_first_match_wins() {
+        let registry = ParserRegistry::new()
+            .with_parser(ParserMatcher::extension("md"), upper)
+            .with_parser(ParserMatcher::ParserType(ParserType::Markdown), WikiParser);
+
+        let parser = registry
+            .find("https://example.com/README.md", None, ParserType::Markdown)
+            .unwrap();
+        let result = parser.parse("# hi", None, &ParserOptions::default());
+        assert_eq!(result.content, "# HI");
+    }
+
+    #[test]
+    fn test_resolve_falls_back_to_builtin() {
+        let registry = ParserRegistry::new();
+        assert!(registry.is_empty());
+
+        let options = ParserOptions::default();
+        let parser = registry.resolve("https://example.com/notes.md", None, ParserType::Markdown);
+        let result = parser.parse(
+            "# Notes\n\n[Home](/)",
+            Some("https://example.com/docs/notes.md"),
+            &options,
+        );
+        assert_eq!(result.title.as_deref(), Some("Notes"));
+        assert_eq!(result.link_details[0].url, "https://example.com/");
+
+        let builtin = BuiltinParser(ParserType::Markdown).parse("# Notes", None, &options);
+        assert_eq!(
+            builtin.content,
+            Parser::parse_with_options("# Notes", ParserType::Markdown, &options).content
+        );
+    }
+
+    #[test]
+    fn test_default_parse_bytes_decodes() {
+        let bytes = b"title = caf\xe9";
+        let result = WikiParser.parse_bytes(
+            bytes,
+            Some("text/x-wiki; charset=iso-8859-1"),
+            None,
+            &ParserOptions::default(),
+        );
+        assert_eq!(result.content, "café");
+        assert_eq!(result.metadata.encoding.as_deref(), Some("windows-1252"));
+        assert_eq!(
+            result.metadata.encoding_source,
+            Some(EncodingSource::ContentType)
+        );
+        assert_eq!(
+            result.metadata.content_type.as_deref(),
+            Some("text/x-wiki; charset=iso-8859-1")
+        );
+    }
+}
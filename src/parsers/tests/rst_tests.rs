@@ -0,0 +1,98 @@
+use crate::parsers::{Parser, rst};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rst_section_levels() {
+        let source = "=========\nUser Guide\n=========\n\nIntro.\n\nInstallation\n============\n\n\
+                      Run it.\n\nFrom source\n-----------\n\nBuild it.\n\nUpgrading\n=========\n";
+
+        let result = rst::parse(source);
+
+        assert_eq!(result.title.as_deref(), Some("User Guide"));
+        let levels: Vec<(u8, &str)> = result
+            .headings
+            .iter()
+            .map(|h| (h.level, h.text.as_str()))
+            .collect();
+        assert_eq!(
+            levels,
+            vec![
+                (1, "User Guide"),
+                (2, "Installation"),
+                (3, "From source"),
+                (2, "Upgrading")
+            ]
+        );
+        assert!(!result.content.contains("==="));
+        assert!(!result.content.contains("---"));
+    }
+
+    #[test]
+    fn test_rst_inline_markup() {
+        let source = "Use **bold**, *emphasis* and ``literal`` text with :func:`os.path.join`.\n";
+
+        let result = rst::parse(source);
+
+        assert_eq!(
+            result.content,
+            "Use bold, emphasis and literal text with os.path.join."
+        );
+    }
+
+    #[test]
+    fn test_rst_directives() {
+        let source = ".. toctree::\n   :maxdepth: 2\n\n   install\n   usage\n\n\
+                      .. note::\n\n   Keep this text.\n\n\
+                      .. image:: logo.png\n   :alt: Logo\n\n\
+                      .. code-block:: python\n\n   print(\"hello\")\n\n\
+                      .. This is a comment\n\nThe end.\n";
+
+        let result = rst::parse(source);
+
+        assert!(result.content.contains("Keep this text."));
+        assert!(result.content.contains("print(\"hello\")"));
+        assert!(result.content.contains("The end."));
+        assert!(!result.content.contains("maxdepth"));
+        assert!(!result.content.contains("install"));
+        assert!(!result.content.contains("logo.png"));
+        assert!(!result.content.contains("comment"));
+    }
+
+    #[test]
+    fn test_rst_literal_block() {
+        let source = "Example::\n\n    $ crawltree https://example.com\n\nDone.\n";
+
+        let result = rst::parse(source);
+
+        assert!(result.content.contains("Example:"));
+        assert!(!result.content.contains("Example::"));
+        assert!(result.content.contains("$ crawltree https://example.com"));
+    }
+
+    #[test]
+    fn test_rst_links() {
+        let source = "Read `the docs <https://docs.example.com/>`_, the Python_ site \
+                      and :doc:`usage guide <guide/usage>`.\n\n\
+                      .. _Python: https://www.python.org/\n";
+
+        let result = Parser::parse_from_url(source, "https://example.com/_sources/index.rst.txt");
+
+        assert_eq!(
+            result.content,
+            "Read the docs, the Python site and usage guide."
+        );
+        let urls: Vec<&str> = result.link_details.iter().map(|l| l.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            vec![
+                "https://example.com/_sources/guide/usage.rst",
+                "https://docs.example.com/",
+                "https://www.python.org/"
+            ]
+        );
+        assert_eq!(result.link_details[1].text.as_deref(), Some("the docs"));
+    }
+}
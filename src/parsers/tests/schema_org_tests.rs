@@ -0,0 +1,124 @@
+use crate::parsers::schema_org::{self, SchemaSource};
+use crate::parsers::{Parser, ParserType, html};
+use scraper::Html;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_ld_graph() {
+        let html_content = r#"<html><head>
+            <script type="application/ld+json">
+            {
+              "@context": "https://schema.org",
+              "@graph": [
+                {"@type": "Article", "headline": "Release notes"},
+                {"@type": ["FAQPage", "WebPage"], "name": "FAQ"}
+              ]
+            }
+            </script>
+            <script type="application/ld+json">[{"@type": "schema:Product", "name": "Widget"}]</script>
+            <script type="application/ld+json">{ not valid </script>
+            </head><body></body></html>"#;
+
+        let doc = Html::parse_document(html_content);
+        let objects = schema_org::extract_schema_objects(&doc, None);
+
+        assert_eq!(objects.len(), 3);
+        assert!(objects[0].is_type("Article"));
+        assert_eq!(objects[0].data["headline"], "Release notes");
+        assert!(objects[1].is_type("FAQPage") && objects[1].is_type("WebPage"));
+        assert_eq!(objects[2].types, vec!["Product"]);
+        assert!(objects.iter().all(|o| o.source == SchemaSource::JsonLd));
+    }
+
+    #[test]
+    fn test_microdata() {
+        let html_content = r#"<html><body>
+            <div itemscope itemtype="https://schema.org/Product">
+              <h1 itemprop="name">Widget</h1>
+              <img itemprop="image" src="/img/widget.png">
+              <div itemprop="offers" itemscope itemtype="https://schema.org/Offer">
+                <meta itemprop="price" content="9.99">
+                <span itemprop="priceCurrency">USD</span>
+              </div>
+              <span itemprop="keywords">tools</span>
+              <span itemprop="keywords">hardware</span>
+            </div>
+            </body></html>"#;
+
+        let result = html::parse_with_url(html_content, "https://shop.example.com/widget");
+
+        assert_eq!(result.structured_data.len(), 1);
+        let product = &result.structured_data[0];
+        assert_eq!(product.source, SchemaSource::Microdata);
+        assert_eq!(product.types, vec!["Product"]);
+        assert_eq!(product.data["name"], "Widget");
+        assert_eq!(
+            product.data["image"],
+            "https://shop.example.com/img/widget.png"
+        );
+        assert_eq!(product.data["offers"]["@type"], "Offer");
+        assert_eq!(product.data["offers"]["price"], "9.99");
+        assert_eq!(product.data["offers"]["priceCurrency"], "USD");
+        assert_eq!(
+            product.data["keywords"],
+            serde_json::json!(["tools", "hardware"])
+        );
+        // Properties of the nested offer don't leak into the product
+        assert!(product.data.get("price").is_none());
+    }
+
+    #[test]
+    fn test_json_ld_breadcrumbs() {
+        let html_content = r#"<html><head>
+            <script type="application/ld+json">
+            {"@context": "https://schema.org", "@type": "BreadcrumbList", "itemListElement": [
+              {"@type": "ListItem", "position": 2, "name": "Guides", "item": "https://example.com/docs/guides"},
+              {"@type": "ListItem", "position": 1, "name": "Docs", "item": "https://example.com/docs"},
+              {"@type": "ListItem", "position": 3, "item": {"@id": "https://example.com/docs/guides/setup", "name": "Setup"}}
+            ]}
+            </script></head><body></body></html>"#;
+
+        let result = Parser::parse(html_content, ParserType::Html);
+
+        assert_eq!(result.section_path, vec!["Docs", "Guides", "Setup"]);
+    }
+
+    #[test]
+    fn test_microdata_breadcrumbs() {
+        let html_content = r#"<html><body>
+            <ol itemscope itemtype="https://schema.org/BreadcrumbList">
+              <li itemprop="itemListElement" itemscope itemtype="https://schema.org/ListItem">
+                <a itemprop="item" href="/books"><span itemprop="name">Books</span></a>
+                <meta itemprop="position" content="1">
+              </li>
+              <li itemprop="itemListElement" itemscope itemtype="https://schema.org/ListItem">
+                <a itemprop="item" href="/books/sf"><span itemprop="name">Science Fiction</span></a>
+                <meta itemprop="position" content="2">
+              </li>
+            </ol>
+            </body></html>"#;
+
+        let result = html::parse(html_content);
+
+        assert_eq!(result.section_path, vec!["Books", "Science Fiction"]);
+    }
+
+    #[test]
+    fn test_navigation_breadcrumbs() {
+        let html_content = r#"<html><body>
+            <nav aria-label="Breadcrumb"><ol>
+              <li><a href="/">Home</a></li><li>/</li>
+              <li><a href="/blog">Blog</a></li>
+              <li aria-current="page">Announcements</li>
+            </ol></nav>
+            </body></html>"#;
+
+        let result = html::parse(html_content);
+
+        assert_eq!(result.section_path, vec!["Home", "Blog", "Announcements"]);
+        assert!(result.structured_data.is_empty());
+    }
+}
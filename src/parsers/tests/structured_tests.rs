@@ -0,0 +1,120 @@
+use crate::parsers::structured::{self, StructuredParserOptions};
+use crate::parsers::{LinkKind, Parser, ParserType};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_key_paths() {
+        let json = r#"{
+            "openapi": "3.0.0",
+            "info": {"title": "Pet Store", "version": 1},
+            "tags": [{"name": "pets", "description": "Everything\n  about pets"}],
+            "deprecated": false,
+            "extra": null
+        }"#;
+
+        let result = Parser::parse(json, ParserType::Json);
+
+        assert_eq!(
+            result.content,
+            "openapi: 3.0.0\ninfo.title: Pet Store\ninfo.version: 1\ntags[0].name: pets\n\
+             tags[0].description: Everything about pets\ndeprecated: false"
+        );
+        assert_eq!(result.title.as_deref(), Some("Pet Store"));
+        assert_eq!(
+            result.metadata.parsed_value.unwrap()["info"]["version"],
+            serde_json::json!(1)
+        );
+    }
+
+    #[test]
+    fn test_yaml_openapi_spec() {
+        let yaml = "openapi: 3.0.0\ninfo:\n  title: Pet Store\n\
+                    servers:\n  - url: https://api.example.com/v1\n\
+                    paths:\n  /pets:\n    get:\n      responses:\n        200:\n          description: A list of pets\n";
+
+        let result = Parser::parse_from_url(yaml, "https://example.com/openapi.yaml");
+
+        assert!(
+            result
+                .content
+                .contains("servers[0].url: https://api.example.com/v1")
+        );
+        assert!(
+            result
+                .content
+                .contains("paths./pets.get.responses.200.description: A list of pets")
+        );
+        assert_eq!(result.links, vec!["https://api.example.com/v1"]);
+        assert_eq!(result.link_details[0].kind, LinkKind::Data);
+        assert_eq!(result.link_details[0].element, "url");
+        assert_eq!(
+            result.link_details[0].text.as_deref(),
+            Some("servers[0].url")
+        );
+    }
+
+    #[test]
+    fn test_yaml_multiple_documents() {
+        let yaml = "kind: Service\n---\nkind: Deployment\n";
+
+        let result = Parser::parse(yaml, ParserType::Yaml);
+
+        assert_eq!(result.content, "[0].kind: Service\n[1].kind: Deployment");
+    }
+
+    #[test]
+    fn test_toml() {
+        let toml = "[package]\nname = \"crawltree\"\nedition = \"2024\"\n\
+                    homepage = \"https://example.com/crawltree\"\n\n\
+                    [release]\ndate = 2024-05-01\n";
+
+        let result = Parser::parse(toml, ParserType::Toml);
+
+        assert!(result.content.contains("package.name: crawltree"));
+        assert!(result.content.contains("release.date: 2024-05-01"));
+        assert_eq!(result.links, vec!["https://example.com/crawltree"]);
+    }
+
+    #[test]
+    fn test_csv_rows() {
+        let csv = "name,url,stars\nalpha,https://example.com/alpha,12\nbeta,,3\n";
+
+        let result = Parser::parse(csv, ParserType::Csv);
+
+        assert_eq!(
+            result.content,
+            "name | url | stars\nalpha | https://example.com/alpha | 12\nbeta |  | 3"
+        );
+        let rows = result.metadata.parsed_value.unwrap();
+        assert_eq!(rows[1]["name"], "beta");
+        assert_eq!(result.link_details[0].text.as_deref(), Some("[0].url"));
+    }
+
+    #[test]
+    fn test_link_extraction_disabled() {
+        let options = StructuredParserOptions {
+            extract_links: false,
+        };
+
+        let result = structured::parse(
+            r#"{"homepage": "https://example.com"}"#,
+            ParserType::Json,
+            &options,
+        )
+        .unwrap();
+
+        assert!(result.links.is_empty());
+        assert!(result.link_details.is_empty());
+    }
+
+    #[test]
+    fn test_malformed_falls_back_to_text() {
+        let result = Parser::parse("{ not json", ParserType::Json);
+
+        assert_eq!(result.content, "{ not json");
+        assert!(result.metadata.parsed_value.is_none());
+    }
+}
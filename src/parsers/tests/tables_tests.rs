@@ -0,0 +1,113 @@
+use crate::parsers::tables::{self, Table};
+use crate::parsers::{Parser, ParserType};
+use scraper::Html;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parameter_table() {
+        let html_content = r#"<html><body>
+            <table>
+              <caption>Query parameters</caption>
+              <thead><tr><th>Name</th><th>Type</th><th>Description</th></tr></thead>
+              <tbody>
+                <tr><td><code>limit</code></td><td>integer</td><td>Maximum  number of <b>results</b></td></tr>
+                <tr><td>cursor</td><td>string</td><td></td></tr>
+              </tbody>
+            </table>
+            </body></html>"#;
+
+        let result = Parser::parse(html_content, ParserType::Html);
+
+        assert_eq!(
+            result.tables,
+            vec![Table {
+                caption: Some("Query parameters".to_string()),
+                headers: vec!["Name".into(), "Type".into(), "Description".into()],
+                rows: vec![
+                    vec![
+                        "limit".into(),
+                        "integer".into(),
+                        "Maximum number of results".into()
+                    ],
+                    vec!["cursor".into(), "string".into(), "".into()],
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_colspan_and_rowspan() {
+        let html_content = r#"<table>
+              <tr><th rowspan="2">Browser</th><th colspan="2">Support</th></tr>
+              <tr><th>Desktop</th><th>Mobile</th></tr>
+              <tr><td rowspan="2">Chrome</td><td colspan="2">Yes</td></tr>
+              <tr><td>120</td><td>121</td></tr>
+            </table>"#;
+
+        let doc = Html::parse_document(html_content);
+        let tables = tables::extract_tables(&doc);
+
+        assert_eq!(tables.len(), 1);
+        assert_eq!(
+            tables[0].headers,
+            vec!["Browser", "Support / Desktop", "Support / Mobile"]
+        );
+        assert_eq!(
+            tables[0].rows,
+            vec![vec!["Chrome", "Yes", "Yes"], vec!["Chrome", "120", "121"]]
+        );
+    }
+
+    #[test]
+    fn test_table_without_headers() {
+        let html_content = r#"<table><tr><td>a</td><td>b</td></tr><tr><td>c</td></tr></table>
+            <table role="presentation"><tr><td>layout</td></tr></table>"#;
+
+        let doc = Html::parse_document(html_content);
+        let tables = tables::extract_tables(&doc);
+
+        assert_eq!(tables.len(), 1);
+        assert!(tables[0].headers.is_empty());
+        assert_eq!(tables[0].rows, vec![vec!["a", "b"], vec!["c", ""]]);
+        assert_eq!(tables[0].to_text(), "a | b\nc | ");
+    }
+
+    #[test]
+    fn test_nested_tables() {
+        let html_content = r#"<table>
+              <tr><th>Outer</th></tr>
+              <tr><td>before <table><tr><td>inner</td></tr></table></td></tr>
+            </table>"#;
+
+        let doc = Html::parse_document(html_content);
+        let tables = tables::extract_tables(&doc);
+
+        assert_eq!(tables.len(), 2);
+        assert_eq!(tables[0].rows, vec![vec!["before"]]);
+        assert_eq!(tables[1].rows, vec![vec!["inner"]]);
+    }
+
+    #[test]
+    fn test_rendering() {
+        let table = Table {
+            caption: Some("Flags".to_string()),
+            headers: vec!["Flag".into(), "Meaning".into()],
+            rows: vec![
+                vec!["-v".into(), "verbose | chatty".into()],
+                vec!["-q".into(), "".into()],
+            ],
+        };
+
+        assert_eq!(
+            table.to_markdown(),
+            "**Flags**\n\n| Flag | Meaning |\n| --- | --- |\n| -v | verbose \\| chatty |\n| -q |  |"
+        );
+        assert_eq!(
+            table.to_text(),
+            "Flags\nFlag: -v; Meaning: verbose | chatty\nFlag: -q"
+        );
+    }
+}
@@ -1,7 +1,9 @@
 use crate::parsers::ParseResult;
+use serde::{Deserialize, Serialize};
 
 /// Configuration options for text parsing
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct TextParserOptions {
     /// Whether to preserve paragraph structure (insert double newlines between paragraphs)
     pub preserve_paragraphs: bool,
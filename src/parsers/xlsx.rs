@@ -0,0 +1,215 @@
+use crate::parsers::office::{
+    DocumentBuilder, OfficeError, Package, attr, child, children, is, node_text, relationship_id,
+    table_from_cells,
+};
+use crate::parsers::{ParseResult, ParserOptions};
+use roxmltree::Node;
+use std::collections::BTreeMap;
+
+/// Workbook part of a SpreadsheetML package
+const WORKBOOK_PART: &str = "xl/workbook.xml";
+
+/// Largest column index kept (Excel's own limit is 16384)
+const MAX_COLUMNS: u32 = 16384;
+
+/// Cell values of a sheet by row and column, both zero-based
+type SheetCells = BTreeMap<u32, BTreeMap<u32, String>>;
+
+/// Parses an Excel workbook (`.xlsx`)
+///
+/// Each sheet becomes a heading with its name followed by a table whose first
+/// row is the header row. Cell values are read as stored: shared and inline
+/// strings, numbers, booleans and cached formula results. Merged cells repeat
+/// their value over the merged range.
+pub(crate) fn parse(
+    package: &mut Package,
+    options: &ParserOptions,
+) -> Result<ParseResult, OfficeError> {
+    let workbook_xml = package.read_required(WORKBOOK_PART)?;
+    let relationships = package.relationships(WORKBOOK_PART)?;
+
+    let shared_strings_part = relationships
+        .values()
+        .find(|r| !r.external && r.target.ends_with("sharedStrings.xml"))
+        .map(|r| r.target.clone())
+        .unwrap_or_else(|| "xl/sharedStrings.xml".to_string());
+    let shared_strings = match package.read(&shared_strings_part)? {
+        Some(xml) => read_shared_strings(&xml)?,
+        None => Vec::new(),
+    };
+
+    let title = package.title("docProps/core.xml")?;
+    let mut builder = DocumentBuilder::with_title(title.clone());
+
+    let workbook = roxmltree::Document::parse(&workbook_xml)?;
+    for sheet in workbook.descendants().filter(|n| is(*n, "sheet")) {
+        let name = attr(sheet, "name").unwrap_or_default();
+        let Some(part) = relationship_id(sheet)
+            .and_then(|id| relationships.get(id))
+            .filter(|r| !r.external)
+            .map(|r| r.target.clone())
+        else {
+            continue;
+        };
+        let Some(xml) = package.read(&part)? else {
+            ::log::debug!("Workbook references missing sheet part {}", part);
+            continue;
+        };
+        let sheet_relationships = package.relationships(&part)?;
+
+        let doc = roxmltree::Document::parse(&xml)?;
+        let cells = read_cells(doc.root_element(), &shared_strings);
+
+        builder.heading(1, name);
+        let rows: Vec<Vec<(String, usize, usize)>> = cells
+            .values()
+            .map(|row| {
+                let mut cells = Vec::new();
+                for (&column, value) in row {
+                    cells.resize(column as usize, (String::new(), 1, 1));
+                    cells.push((value.clone(), 1, 1));
+                }
+                cells
+            })
+            .collect();
+        builder.table(table_from_cells(None, &rows, 1));
+
+        for hyperlink in doc.descendants().filter(|n| is(*n, "hyperlink")) {
+            let Some(target) = relationship_id(hyperlink)
+                .and_then(|id| sheet_relationships.get(id))
+                .filter(|r| r.external)
+            else {
+                continue;
+            };
+            let text = attr(hyperlink, "display")
+                .map(str::to_string)
+                .or_else(|| {
+                    let (row, column) = cell_position(attr(hyperlink, "ref")?)?;
+                    cells.get(&row)?.get(&column).cloned()
+                })
+                .unwrap_or_default();
+            builder.link(&target.target, &text, "hyperlink");
+        }
+    }
+
+    let mut result = builder.finish(options);
+    // Sheet names like `Sheet1` make poor titles
+    result.title = title;
+    Ok(result)
+}
+
+/// Reads the shared string table
+fn read_shared_strings(xml: &str) -> Result<Vec<String>, OfficeError> {
+    let doc = roxmltree::Document::parse(xml)?;
+    Ok(children(doc.root_element(), "si").map(rich_text).collect())
+}
+
+/// Reads the text of a string item, leaving out phonetic guides
+fn rich_text(item: Node) -> String {
+    item.descendants()
+        .filter(|n| is(*n, "t"))
+        .filter(|t| !t.ancestors().any(|a| is(a, "rPh")))
+        .map(node_text)
+        .collect()
+}
+
+/// Reads the non-empty cell values of a worksheet, spreading merged cells
+fn read_cells(worksheet: Node, shared_strings: &[String]) -> SheetCells {
+    let mut cells = SheetCells::new();
+    let Some(sheet_data) = child(worksheet, "sheetData") else {
+        return cells;
+    };
+
+    let mut next_row = 0;
+    for row in children(sheet_data, "row") {
+        let row_index = attr(row, "r")
+            .and_then(|r| r.parse::<u32>().ok())
+            .map_or(next_row, |r| r.saturating_sub(1));
+        next_row = row_index + 1;
+
+        let mut next_column = 0;
+        for cell in children(row, "c") {
+            let column = attr(cell, "r")
+                .and_then(cell_position)
+                .map_or(next_column, |(_, column)| column);
+            next_column = column + 1;
+            if column >= MAX_COLUMNS {
+                continue;
+            }
+
+            let value = cell_value(cell, shared_strings);
+            if !value.is_empty() {
+                cells.entry(row_index).or_default().insert(column, value);
+            }
+        }
+    }
+
+    // Merged ranges only store a value in their top-left cell
+    let last_row = cells.keys().next_back().copied().unwrap_or(0);
+    for merge in worksheet.descendants().filter(|n| is(*n, "mergeCell")) {
+        let Some((start, end)) = attr(merge, "ref").and_then(|r| r.split_once(':')) else {
+            continue;
+        };
+        let (Some((top, left)), Some((bottom, right))) = (cell_position(start), cell_position(end))
+        else {
+            continue;
+        };
+        let Some(value) = cells.get(&top).and_then(|row| row.get(&left)).cloned() else {
+            continue;
+        };
+        for row in top..=bottom.min(last_row) {
+            for column in left..=right.min(MAX_COLUMNS - 1) {
+                cells
+                    .entry(row)
+                    .or_default()
+                    .entry(column)
+                    .or_insert_with(|| value.clone());
+            }
+        }
+    }
+
+    cells
+}
+
+/// Returns the displayed value of a cell as text
+fn cell_value(cell: Node, shared_strings: &[String]) -> String {
+    let value = child(cell, "v").map(node_text).unwrap_or_default();
+    let value = match attr(cell, "t") {
+        Some("s") => value
+            .trim()
+            .parse::<usize>()
+            .ok()
+            .and_then(|i| shared_strings.get(i))
+            .cloned()
+            .unwrap_or_default(),
+        Some("inlineStr") => child(cell, "is").map(rich_text).unwrap_or_default(),
+        Some("b") => match value.trim() {
+            "1" => "TRUE".to_string(),
+            _ => "FALSE".to_string(),
+        },
+        _ => value,
+    };
+    value.trim().to_string()
+}
+
+/// Converts an `A1`-style reference to zero-based (row, column)
+fn cell_position(reference: &str) -> Option<(u32, u32)> {
+    let reference = reference.trim().replace('$', "");
+    let split = reference.find(|c: char| c.is_ascii_digit())?;
+    let (letters, digits) = reference.split_at(split);
+    if letters.is_empty() {
+        return None;
+    }
+
+    let mut column: u32 = 0;
+    for c in letters.chars() {
+        if !c.is_ascii_alphabetic() {
+            return None;
+        }
+        column = column
+            .checked_mul(26)?
+            .checked_add(c.to_ascii_uppercase() as u32 - 'A' as u32 + 1)?;
+    }
+    let row: u32 = digits.parse().ok()?;
+    Some((row.checked_sub(1)?, column - 1))
+}
@@ -1,3 +1,7 @@
+use crate::parsers::{
+    CodeBlock, EncodingSource, Heading, LanguageSource, Link, MediaItem, ParseResult, SchemaObject,
+    Table,
+};
 use serde::{Deserialize, Serialize};
 
 /// Represents a discovered page with its URL and content
@@ -14,6 +18,119 @@ pub struct PageData {
 
     /// Links discovered on the page (as strings)
     pub links: Vec<String>,
+
+    /// Section headings in document order
+    #[serde(default)]
+    pub headings: Vec<Heading>,
+
+    /// Typed links discovered on the page, resolved against the page URL
+    #[serde(default)]
+    pub link_details: Vec<Link>,
+
+    /// Tables on the page, with their headers and rows
+    #[serde(default)]
+    pub tables: Vec<Table>,
+
+    /// Code blocks on the page, kept verbatim with their language and heading
+    #[serde(default)]
+    pub code_blocks: Vec<CodeBlock>,
+
+    /// Images, video and audio referenced by the page; the media itself isn't fetched
+    #[serde(default)]
+    pub media: Vec<MediaItem>,
+
+    /// Schema.org objects (Article, Product, BreadcrumbList, ...) embedded in the page
+    #[serde(default)]
+    pub structured_data: Vec<SchemaObject>,
+
+    /// Breadcrumb trail leading to the page, outermost section first
+    #[serde(default)]
+    pub section_path: Vec<String>,
+
+    /// Language of the page as a lower-cased BCP 47 tag (e.g. `en`, `pt-br`)
+    #[serde(default)]
+    pub language: Option<String>,
+
+    /// Information about how the page was fetched and decoded
+    #[serde(default)]
+    pub metadata: PageMetadata,
+
+    /// Source the page was parsed from, if the crawl keeps it; never serialized
+    #[serde(skip)]
+    pub source: Option<Vec<u8>>,
+}
+
+/// Metadata about how a page was fetched and decoded
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PageMetadata {
+    /// Content-Type of the response (if known)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+
+    /// Character encoding the content was decoded from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
+
+    /// Where the character encoding was determined from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding_source: Option<EncodingSource>,
+
+    /// Where the language of the page was determined from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language_source: Option<LanguageSource>,
+
+    /// Parsed value of structured data (JSON, YAML, TOML or CSV), as JSON
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parsed_value: Option<serde_json::Value>,
+
+    /// Authors of the document (e.g. the creators of an e-book)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub authors: Vec<String>,
+
+    /// Summary of the page from `<meta name="description">`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Lower-cased directives from `<meta name="robots">` (`noindex`, `nofollow`, ...)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub robots: Vec<String>,
+
+    /// URL the browser ended up at, when loading the page redirected elsewhere
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub final_url: Option<String>,
+
+    /// Whether an incremental crawl found the page unchanged, in which case only its
+    /// URL, title, language and links are filled in
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub unchanged: bool,
+
+    /// URL of the archive the page was extracted from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archive_url: Option<String>,
+}
+
+/// A URL the crawler failed to turn into pages
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CrawlError {
+    /// URL that failed
+    pub url: String,
+
+    /// What went wrong
+    pub message: String,
+
+    /// When it failed, in seconds since the Unix epoch
+    pub occurred_at: u64,
+}
+
+impl CrawlError {
+    /// Create an error for a URL that failed now
+    pub fn new(url: &str, message: impl Into<String>) -> Self {
+        Self {
+            url: url.to_string(),
+            message: message.into(),
+            occurred_at: crate::utils::unix_timestamp(),
+        }
+    }
 }
 
 impl PageData {
@@ -24,6 +141,54 @@ impl PageData {
             title,
             content,
             links,
+            headings: Vec::new(),
+            link_details: Vec::new(),
+            tables: Vec::new(),
+            code_blocks: Vec::new(),
+            media: Vec::new(),
+            structured_data: Vec::new(),
+            section_path: Vec::new(),
+            language: None,
+            metadata: PageMetadata::default(),
+            source: None,
+        }
+    }
+
+    /// Returns the URL of each link on the page, with its details when the parser reports them
+    ///
+    /// Parsers without typed links only report the raw URLs, which come without details.
+    pub fn outgoing_links(&self) -> impl Iterator<Item = (&str, Option<&Link>)> {
+        let typed = self
+            .link_details
+            .iter()
+            .map(|link| (link.url.as_str(), Some(link)));
+        let raw = self
+            .link_details
+            .is_empty()
+            .then_some(&self.links)
+            .into_iter()
+            .flatten()
+            .map(|url| (url.as_str(), None));
+        typed.chain(raw)
+    }
+
+    /// Create a page data instance from the result of parsing the page
+    pub fn from_parse_result(url: &str, result: ParseResult) -> Self {
+        Self {
+            url: url.to_string(),
+            title: result.title,
+            content: result.content,
+            links: result.links,
+            headings: result.headings,
+            link_details: result.link_details,
+            tables: result.tables,
+            code_blocks: result.code_blocks,
+            media: result.media,
+            structured_data: result.structured_data,
+            section_path: result.section_path,
+            language: result.language,
+            metadata: result.metadata,
+            source: None,
         }
     }
 }
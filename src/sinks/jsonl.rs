@@ -0,0 +1,275 @@
+use crate::results::PageData;
+use crate::sinks::{CrawlSummary, PageSink, SinkError};
+use flate2::write::GzEncoder;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// Compression applied to an output stream
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Compression {
+    /// Plain text
+    #[default]
+    None,
+    /// gzip (`.gz`)
+    Gzip,
+    /// Zstandard (`.zst`)
+    Zstd,
+}
+
+impl Compression {
+    /// Picks the compression matching a file extension (`.gz`, `.zst`)
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("gz") => Self::Gzip,
+            Some("zst") => Self::Zstd,
+            _ => Self::None,
+        }
+    }
+}
+
+/// An output stream with its compressor, which must be finished to be complete
+enum Encoder {
+    Plain(Box<dyn Write + Send>),
+    Gzip(GzEncoder<Box<dyn Write + Send>>),
+    Zstd(zstd::Encoder<'static, Box<dyn Write + Send>>),
+}
+
+impl Encoder {
+    fn new(writer: Box<dyn Write + Send>, compression: Compression) -> io::Result<Self> {
+        Ok(match compression {
+            Compression::None => Self::Plain(writer),
+            Compression::Gzip => Self::Gzip(GzEncoder::new(writer, flate2::Compression::default())),
+            Compression::Zstd => Self::Zstd(zstd::Encoder::new(writer, 0)?),
+        })
+    }
+
+    fn writer(&mut self) -> &mut dyn Write {
+        match self {
+            Self::Plain(w) => w,
+            Self::Gzip(w) => w,
+            Self::Zstd(w) => w,
+        }
+    }
+
+    /// Writes the compression trailer and flushes the underlying stream
+    fn finish(self) -> io::Result<()> {
+        let mut writer = match self {
+            Self::Plain(w) => w,
+            Self::Gzip(w) => w.finish()?,
+            Self::Zstd(w) => w.finish()?,
+        };
+        writer.flush()
+    }
+}
+
+/// Writes pages as JSON Lines: one JSON object per page, one page per line
+///
+/// The last line is a `{"summary": {...}}` record with the crawl totals unless
+/// disabled. Fields can be narrowed to the ones a consumer needs.
+pub struct JsonLinesSink {
+    encoder: Encoder,
+    fields: Vec<String>,
+    summary: bool,
+}
+
+impl JsonLinesSink {
+    /// Creates a sink writing to any stream
+    pub fn new(writer: impl Write + Send + 'static, compression: Compression) -> io::Result<Self> {
+        Ok(Self {
+            encoder: Encoder::new(Box::new(writer), compression)?,
+            fields: Vec::new(),
+            summary: true,
+        })
+    }
+
+    /// Creates a sink writing to standard output
+    pub fn stdout(compression: Compression) -> io::Result<Self> {
+        Self::new(BufWriter::new(io::stdout()), compression)
+    }
+
+    /// Creates a sink writing to a file, replacing it if it exists
+    pub fn create(path: &Path, compression: Compression) -> io::Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?), compression)
+    }
+
+    /// Only writes the given top-level fields of each page (e.g. `url`, `title`, `content`)
+    ///
+    /// Returns an error naming the valid fields if one isn't a `PageData` field.
+    pub fn with_fields(mut self, fields: Vec<String>) -> Result<Self, SinkError> {
+        let known = page_fields();
+        if let Some(unknown) = fields.iter().find(|f| !known.contains(f)) {
+            return Err(format!(
+                "Unknown page field '{}' (expected one of: {})",
+                unknown,
+                known.join(", ")
+            )
+            .into());
+        }
+        self.fields = fields;
+        Ok(self)
+    }
+
+    /// Sets whether a summary record is written at the end
+    pub fn with_summary(mut self, summary: bool) -> Self {
+        self.summary = summary;
+        self
+    }
+
+    fn write_line(&mut self, value: &serde_json::Value) -> Result<(), SinkError> {
+        let writer = self.encoder.writer();
+        serde_json::to_writer(&mut *writer, value)?;
+        writer.write_all(b"\n")?;
+        Ok(())
+    }
+}
+
+impl PageSink for JsonLinesSink {
+    fn write_page(&mut self, page: &PageData) -> Result<(), SinkError> {
+        let mut value = serde_json::to_value(page)?;
+        if !self.fields.is_empty()
+            && let Some(object) = value.as_object_mut()
+        {
+            object.retain(|key, _| self.fields.contains(key));
+        }
+        self.write_line(&value)
+    }
+
+    fn finish(mut self: Box<Self>, summary: &CrawlSummary) -> Result<(), SinkError> {
+        if self.summary {
+            self.write_line(&serde_json::json!({ "summary": summary }))?;
+        }
+        self.encoder.finish()?;
+        Ok(())
+    }
+}
+
+/// Returns the names of the top-level fields of a serialized page
+fn page_fields() -> Vec<String> {
+    let page = PageData::new(String::new(), None, String::new(), Vec::new());
+    match serde_json::to_value(page) {
+        Ok(serde_json::Value::Object(object)) => object.keys().cloned().collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::sync::{Arc, Mutex};
+
+    /// A stream whose contents can be read after the sink is done with it
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn page(url: &str) -> PageData {
+        PageData::new(
+            url.to_string(),
+            Some("Title".to_string()),
+            "Content".to_string(),
+            vec!["https://example.org/other".to_string()],
+        )
+    }
+
+    fn write_pages(sink: JsonLinesSink, urls: &[&str]) {
+        let mut sink = Box::new(sink);
+        let mut summary = CrawlSummary::start("https://example.org/");
+        for url in urls {
+            let page = page(url);
+            summary.record(&page);
+            sink.write_page(&page).unwrap();
+        }
+        summary.stop();
+        sink.finish(&summary).unwrap();
+    }
+
+    fn lines(output: &[u8]) -> Vec<serde_json::Value> {
+        String::from_utf8(output.to_vec())
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_pages_and_summary() {
+        let buffer = SharedBuffer::default();
+        let sink = JsonLinesSink::new(buffer.clone(), Compression::None).unwrap();
+        write_pages(sink, &["https://example.org/a", "https://example.org/b"]);
+
+        let lines = lines(&buffer.0.lock().unwrap());
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["url"], "https://example.org/a");
+        assert_eq!(lines[1]["title"], "Title");
+        assert_eq!(lines[2]["summary"]["pages"], 2);
+        assert_eq!(lines[2]["summary"]["links"], 2);
+        assert_eq!(lines[2]["summary"]["uri"], "https://example.org/");
+    }
+
+    #[test]
+    fn test_fields() {
+        let buffer = SharedBuffer::default();
+        let sink = JsonLinesSink::new(buffer.clone(), Compression::None)
+            .unwrap()
+            .with_fields(vec!["url".to_string(), "title".to_string()])
+            .unwrap()
+            .with_summary(false);
+        write_pages(sink, &["https://example.org/a"]);
+
+        let lines = lines(&buffer.0.lock().unwrap());
+        assert_eq!(
+            lines,
+            vec![serde_json::json!({"url": "https://example.org/a", "title": "Title"})]
+        );
+
+        let error = JsonLinesSink::new(SharedBuffer::default(), Compression::None)
+            .unwrap()
+            .with_fields(vec!["body".to_string()])
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("'body'"));
+    }
+
+    #[test]
+    fn test_compression() {
+        assert_eq!(
+            Compression::from_path(Path::new("pages.jsonl.gz")),
+            Compression::Gzip
+        );
+        assert_eq!(
+            Compression::from_path(Path::new("pages.jsonl.zst")),
+            Compression::Zstd
+        );
+        assert_eq!(
+            Compression::from_path(Path::new("pages.jsonl")),
+            Compression::None
+        );
+
+        let buffer = SharedBuffer::default();
+        let sink = JsonLinesSink::new(buffer.clone(), Compression::Gzip).unwrap();
+        write_pages(sink, &["https://example.org/a"]);
+        let mut output = Vec::new();
+        flate2::read::GzDecoder::new(buffer.0.lock().unwrap().as_slice())
+            .read_to_end(&mut output)
+            .unwrap();
+        assert_eq!(lines(&output).len(), 2);
+
+        let buffer = SharedBuffer::default();
+        let sink = JsonLinesSink::new(buffer.clone(), Compression::Zstd).unwrap();
+        write_pages(sink, &["https://example.org/a"]);
+        let output = zstd::decode_all(buffer.0.lock().unwrap().as_slice()).unwrap();
+        assert_eq!(lines(&output).len(), 2);
+    }
+}
@@ -0,0 +1,659 @@
+use crate::results::PageData;
+use crate::sinks::{CrawlSummary, PageSink, SinkError};
+use crate::utils::{sanitize_filename, without_fragment};
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use url::Url;
+
+/// Name of the manifest written at the root of a mirror
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// A quoted `href` or `src` attribute in HTML
+static LINK_ATTRIBUTE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?i)(\s(?:href|src)\s*=\s*)(?:"([^"]*)"|'([^']*)')"#).unwrap());
+
+/// Files written for each page of a mirror
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MirrorFiles {
+    /// The source the page was parsed from; needs a crawl that keeps sources
+    pub raw: bool,
+    /// The extracted text (`.txt`)
+    pub text: bool,
+    /// The extracted text with Markdown headings (`.md`)
+    pub markdown: bool,
+}
+
+impl Default for MirrorFiles {
+    fn default() -> Self {
+        Self {
+            raw: true,
+            text: false,
+            markdown: false,
+        }
+    }
+}
+
+/// A page in the mirror manifest, with the files written for it relative to the mirror root
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MirrorEntry {
+    /// URL of the page
+    pub url: String,
+    /// Title of the page
+    pub title: Option<String>,
+    /// Content-Type of the page, if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    /// Path of the page's source
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw: Option<String>,
+    /// Path of the extracted text
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// Path of the Markdown rendering
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub markdown: Option<String>,
+}
+
+/// Writes pages into a directory tree mirroring their URLs, for offline browsing
+///
+/// `https://example.org/docs/intro` becomes `example.org/docs/intro.html`, and a
+/// URL ending in `/` becomes `index.html` in its directory. Names that would clash
+/// (after sanitizing, or differing only in case) get a suffix derived from the URL,
+/// as do files and directories that would share a name, such as `/feed` and `/feed/atom`.
+/// When the crawl finishes, a manifest of all pages is written and links between
+/// mirrored HTML pages are rewritten to relative paths. Pages an incremental crawl
+/// found unchanged keep the files and manifest entry of the previous mirror.
+pub struct MirrorSink {
+    root: PathBuf,
+    files: MirrorFiles,
+    rewrite_links: bool,
+    entries: Vec<MirrorEntry>,
+    /// Index into `entries` by URL without fragment
+    by_url: HashMap<String, usize>,
+    /// URL each claimed path belongs to, keyed by the lower-cased path
+    claimed: HashMap<String, String>,
+    /// Lower-cased directories holding claimed paths
+    directories: HashSet<String>,
+    /// Entries of the manifest already in the directory, by URL without fragment
+    previous: HashMap<String, MirrorEntry>,
+    /// URLs without fragment of the unchanged pages, whose links were already rewritten
+    unchanged: HashSet<String>,
+}
+
+impl MirrorSink {
+    /// Creates a mirror in a directory, creating it if needed
+    pub fn new(root: impl Into<PathBuf>) -> Result<Self, SinkError> {
+        let root = root.into();
+        fs::create_dir_all(&root)?;
+        let previous = read_manifest(&root)
+            .into_iter()
+            .map(|entry| (without_fragment(&entry.url), entry))
+            .collect();
+        Ok(Self {
+            root,
+            files: MirrorFiles::default(),
+            rewrite_links: true,
+            entries: Vec::new(),
+            by_url: HashMap::new(),
+            claimed: HashMap::new(),
+            directories: HashSet::new(),
+            previous,
+            unchanged: HashSet::new(),
+        })
+    }
+
+    /// Sets which files are written for each page
+    pub fn with_files(mut self, files: MirrorFiles) -> Self {
+        self.files = files;
+        self
+    }
+
+    /// Sets whether links between mirrored HTML pages are rewritten to relative paths
+    pub fn with_link_rewriting(mut self, rewrite_links: bool) -> Self {
+        self.rewrite_links = rewrite_links;
+        self
+    }
+
+    /// Returns the pages written so far
+    pub fn entries(&self) -> &[MirrorEntry] {
+        &self.entries
+    }
+
+    /// Claims a path for a URL, adding a suffix if another URL already has it or it's
+    /// already a directory
+    fn claim(&mut self, path: String, url: &str) -> String {
+        let path = self.outside_files(&path);
+        let mut candidate = path.clone();
+        let mut attempt = 0;
+        while self.is_taken(&candidate, url) {
+            attempt += 1;
+            let suffix = match attempt {
+                1 => url_hash(url),
+                n => format!("{}-{}", url_hash(url), n),
+            };
+            candidate = with_suffix(&path, &suffix);
+        }
+        self.record_claim(&candidate, url);
+        candidate
+    }
+
+    fn is_taken(&self, path: &str, url: &str) -> bool {
+        let key = path.to_lowercase();
+        self.claimed.get(&key).is_some_and(|owner| owner != url) || self.directories.contains(&key)
+    }
+
+    /// Adds a suffix to the directories of a path that are already files
+    fn outside_files(&self, path: &str) -> String {
+        let mut segments: Vec<String> = path.split('/').map(str::to_string).collect();
+        for i in 0..segments.len().saturating_sub(1) {
+            let dir = segments[..=i].join("/");
+            if self.claimed.contains_key(&dir.to_lowercase()) {
+                segments[i] = format!("{}-{}", segments[i], url_hash(&dir));
+            }
+        }
+        segments.join("/")
+    }
+
+    fn record_claim(&mut self, path: &str, url: &str) {
+        let key = path.to_lowercase();
+        let mut dir = key.as_str();
+        while let Some((parent, _)) = dir.rsplit_once('/') {
+            self.directories.insert(parent.to_string());
+            dir = parent;
+        }
+        self.claimed.insert(key, url.to_string());
+    }
+
+    /// Keeps the files of a page found unchanged, as listed in the previous manifest
+    fn keep_unchanged(&mut self, key: String) -> Result<(), SinkError> {
+        let Some(entry) = self.previous.get(&key).cloned() else {
+            ::log::debug!(
+                "Unchanged page {} isn't in the mirror, not mirroring it",
+                key
+            );
+            return Ok(());
+        };
+        for path in [&entry.raw, &entry.text, &entry.markdown]
+            .into_iter()
+            .flatten()
+        {
+            self.record_claim(path, &key);
+        }
+        if !self.by_url.contains_key(&key) {
+            self.by_url.insert(key.clone(), self.entries.len());
+            self.entries.push(entry);
+        }
+        self.unchanged.insert(key);
+        Ok(())
+    }
+
+    fn write_file(&self, path: &str, contents: &[u8]) -> Result<(), SinkError> {
+        let path = self.root.join(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// Rewrites the links of a mirrored HTML page to point at the mirrored copies
+    fn rewrite_page_links(&self, entry: &MirrorEntry) -> Result<(), SinkError> {
+        let Some(raw) = &entry.raw else {
+            return Ok(());
+        };
+        if !is_html(raw) {
+            return Ok(());
+        }
+        let Ok(base) = Url::parse(&entry.url) else {
+            return Ok(());
+        };
+
+        let path = self.root.join(raw);
+        let html = String::from_utf8_lossy(&fs::read(&path)?).into_owned();
+        let rewritten = LINK_ATTRIBUTE.replace_all(&html, |caps: &Captures| {
+            let (value, quote) = match (caps.get(2), caps.get(3)) {
+                (Some(value), _) => (value.as_str(), '"'),
+                (_, Some(value)) => (value.as_str(), '\''),
+                _ => return caps[0].to_string(),
+            };
+            match self.local_link(&base, raw, &value.replace("&amp;", "&")) {
+                Some(local) => format!("{}{}{}{}", &caps[1], quote, local, quote),
+                None => caps[0].to_string(),
+            }
+        });
+
+        if rewritten != html {
+            fs::write(path, rewritten.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Returns the relative path from a mirrored file to the copy of a linked page, if mirrored
+    fn local_link(&self, base: &Url, from: &str, href: &str) -> Option<String> {
+        let mut target = base.join(href).ok()?;
+        let fragment = target.fragment().map(|f| f.to_string());
+        target.set_fragment(None);
+
+        let entry = &self.entries[*self.by_url.get(target.as_str())?];
+        let mut local = relative_path(from, entry.raw.as_deref()?);
+        if let Some(fragment) = fragment {
+            local.push('#');
+            local.push_str(&fragment);
+        }
+        Some(local)
+    }
+}
+
+impl PageSink for MirrorSink {
+    fn write_page(&mut self, page: &PageData) -> Result<(), SinkError> {
+        let key = without_fragment(&page.url);
+        if page.metadata.unchanged {
+            return self.keep_unchanged(key);
+        }
+        let content_type = page.metadata.content_type.clone();
+        let html = content_type.as_deref().is_none_or(|ct| ct.contains("html"));
+        let (stem, extension) = local_path(&page.url, html);
+
+        // A page seen again replaces its files
+        let previous = self.by_url.get(&key).map(|&i| self.entries[i].clone());
+
+        let raw = match &page.source {
+            Some(source) if self.files.raw => {
+                let path = match previous.as_ref().and_then(|e| e.raw.clone()) {
+                    Some(path) => path,
+                    None => self.claim(format!("{}{}", stem, extension), &key),
+                };
+                self.write_file(&path, source)?;
+                Some(path)
+            }
+            None if self.files.raw => {
+                ::log::debug!("No source kept for {}, not mirroring it", page.url);
+                None
+            }
+            _ => None,
+        };
+
+        let text = if self.files.text {
+            let path = match previous.as_ref().and_then(|e| e.text.clone()) {
+                Some(path) => path,
+                None => self.claim(format!("{}.txt", stem), &key),
+            };
+            self.write_file(&path, page.content.as_bytes())?;
+            Some(path)
+        } else {
+            None
+        };
+
+        let markdown = if self.files.markdown {
+            let path = match previous.as_ref().and_then(|e| e.markdown.clone()) {
+                Some(path) => path,
+                None => self.claim(format!("{}.md", stem), &key),
+            };
+            self.write_file(&path, to_markdown(page).as_bytes())?;
+            Some(path)
+        } else {
+            None
+        };
+
+        let entry = MirrorEntry {
+            url: page.url.clone(),
+            title: page.title.clone(),
+            content_type,
+            raw,
+            text,
+            markdown,
+        };
+        match self.by_url.get(&key) {
+            Some(&index) => self.entries[index] = entry,
+            None => {
+                self.by_url.insert(key, self.entries.len());
+                self.entries.push(entry);
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>, summary: &CrawlSummary) -> Result<(), SinkError> {
+        if self.rewrite_links {
+            for entry in &self.entries {
+                if self.unchanged.contains(&without_fragment(&entry.url)) {
+                    continue;
+                }
+                if let Err(e) = self.rewrite_page_links(entry) {
+                    ::log::warn!("Failed to rewrite links in {}: {}", entry.url, e);
+                }
+            }
+        }
+
+        let manifest = serde_json::json!({
+            "summary": summary,
+            "pages": self.entries,
+        });
+        fs::write(
+            self.root.join(MANIFEST_FILE),
+            serde_json::to_vec_pretty(&manifest)?,
+        )?;
+        Ok(())
+    }
+}
+
+/// Reads the pages listed in the manifest of an existing mirror
+fn read_manifest(root: &Path) -> Vec<MirrorEntry> {
+    let Ok(data) = fs::read(root.join(MANIFEST_FILE)) else {
+        return Vec::new();
+    };
+    #[derive(Deserialize)]
+    struct Manifest {
+        pages: Vec<MirrorEntry>,
+    }
+    match serde_json::from_slice::<Manifest>(&data) {
+        Ok(manifest) => manifest.pages,
+        Err(e) => {
+            ::log::warn!("Ignoring unreadable mirror manifest: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Maps a URL to a path relative to the mirror root, split into stem and extension
+///
+/// The raw file of a page is `stem + extension`; extension-less HTML pages get `.html`.
+fn local_path(url: &str, html: bool) -> (String, String) {
+    let Ok(url) = Url::parse(url) else {
+        return (format!("_/{}", safe_segment(url)), String::new());
+    };
+
+    let mut host = url.host_str().unwrap_or("_").to_string();
+    if let Some(port) = url.port() {
+        host = format!("{}_{}", host, port);
+    }
+    let mut segments = vec![safe_segment(&host)];
+
+    let path = url.path();
+    let mut parts: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    let name = if path.ends_with('/') || parts.is_empty() {
+        "index"
+    } else {
+        parts.pop().unwrap_or("index")
+    };
+    segments.extend(parts.into_iter().map(safe_segment));
+
+    let (mut stem, mut extension) = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() && !ext.is_empty() => {
+            (stem.to_string(), format!(".{}", ext))
+        }
+        _ => (name.to_string(), String::new()),
+    };
+    if let Some(query) = url.query() {
+        stem = format!("{}_{}", stem, query);
+    }
+    if extension.is_empty() && html {
+        extension = ".html".to_string();
+    }
+    segments.push(safe_segment(&stem));
+    if !extension.is_empty() {
+        extension = safe_segment(&extension);
+    }
+
+    (segments.join("/"), extension)
+}
+
+/// Sanitizes one path segment, keeping it clear of `.` and `..`
+fn safe_segment(segment: &str) -> String {
+    let name = sanitize_filename(segment);
+    if name.is_empty() || name.chars().all(|c| c == '.') {
+        name.replace('.', "_") + "_"
+    } else {
+        name
+    }
+}
+
+/// Inserts a suffix before the extension of a path
+fn with_suffix(path: &str, suffix: &str) -> String {
+    let (dir, name) = path.rsplit_once('/').unwrap_or(("", path));
+    let name = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => format!("{}-{}.{}", stem, suffix, ext),
+        _ => format!("{}-{}", name, suffix),
+    };
+    if dir.is_empty() {
+        name
+    } else {
+        format!("{}/{}", dir, name)
+    }
+}
+
+/// Short, stable hash of a URL for telling apart paths that clash
+fn url_hash(url: &str) -> String {
+    Sha1::digest(url.as_bytes())[..4]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn is_html(path: &str) -> bool {
+    let path = path.to_ascii_lowercase();
+    path.ends_with(".html") || path.ends_with(".htm") || path.ends_with(".xhtml")
+}
+
+/// Returns the path of `to` relative to the directory of `from`, both relative to the root
+fn relative_path(from: &str, to: &str) -> String {
+    let from_dir: Vec<&str> = from.split('/').rev().skip(1).collect::<Vec<_>>();
+    let from_dir: Vec<&str> = from_dir.into_iter().rev().collect();
+    let to: Vec<&str> = to.split('/').collect();
+
+    let common = from_dir.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let mut parts = vec![".."; from_dir.len() - common];
+    parts.extend(&to[common..]);
+    parts.join("/")
+}
+
+/// Renders the extracted text of a page as Markdown, marking up its headings
+fn to_markdown(page: &PageData) -> String {
+    let mut markdown = String::new();
+    let mut headings = page.headings.iter().peekable();
+
+    if let Some(title) = &page.title
+        && headings.peek().is_none_or(|h| &h.text != title)
+    {
+        markdown.push_str(&format!("# {}\n\n", title));
+    }
+
+    for line in page.content.lines() {
+        let text = line.trim();
+        match headings.peek() {
+            Some(heading) if !text.is_empty() && heading.text == text => {
+                markdown.push_str(&"#".repeat(heading.level.clamp(1, 6) as usize));
+                markdown.push(' ');
+                markdown.push_str(text);
+                headings.next();
+            }
+            _ => markdown.push_str(line),
+        }
+        markdown.push('\n');
+    }
+    markdown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::Heading;
+
+    fn page(url: &str, source: &str) -> PageData {
+        let mut page = PageData::new(
+            url.to_string(),
+            Some("Intro".to_string()),
+            "Intro\nWelcome.\nSetup\nRun it.".to_string(),
+            Vec::new(),
+        );
+        page.headings = vec![
+            Heading {
+                level: 1,
+                text: "Intro".to_string(),
+            },
+            Heading {
+                level: 2,
+                text: "Setup".to_string(),
+            },
+        ];
+        page.metadata.content_type = Some("text/html".to_string());
+        page.source = Some(source.as_bytes().to_vec());
+        page
+    }
+
+    #[test]
+    fn test_local_path() {
+        let path = |url| {
+            let (stem, ext) = local_path(url, true);
+            stem + &ext
+        };
+        assert_eq!(path("https://example.org/"), "example.org/index.html");
+        assert_eq!(
+            path("https://example.org/docs/"),
+            "example.org/docs/index.html"
+        );
+        assert_eq!(
+            path("https://example.org/docs/intro"),
+            "example.org/docs/intro.html"
+        );
+        assert_eq!(path("https://example.org/a/b.pdf"), "example.org/a/b.pdf");
+        assert_eq!(local_path("https://example.org/feed", false).1, "");
+        assert_eq!(
+            path("http://example.org:8080/list?page=2"),
+            "example.org_8080/list_page_2.html"
+        );
+        assert_eq!(path("https://example.org/%2e%2e/x"), "example.org/x.html");
+        assert_eq!(relative_path("a/b/c.html", "a/d.html"), "../d.html");
+        assert_eq!(relative_path("a/c.html", "a/c.html"), "c.html");
+        assert_eq!(relative_path("x.html", "a/b.html"), "a/b.html");
+    }
+
+    #[test]
+    fn test_mirror() {
+        let root = std::env::temp_dir().join(format!("yield-page-mirror-{}", uuid::Uuid::new_v4()));
+        let mut sink = Box::new(MirrorSink::new(&root).unwrap().with_files(MirrorFiles {
+            raw: true,
+            text: true,
+            markdown: true,
+        }));
+
+        sink.write_page(&page(
+            "https://example.org/docs/",
+            r#"<a href="intro#setup">Intro</a> <a href='https://elsewhere.org/'>x</a> <img src="/logo.png">"#,
+        ))
+        .unwrap();
+        sink.write_page(&page(
+            "https://example.org/docs/intro",
+            r#"<a href="../docs/">Back</a>"#,
+        ))
+        .unwrap();
+        // Differs from the previous page only in case
+        sink.write_page(&page("https://example.org/docs/Intro", "upper"))
+            .unwrap();
+
+        let entries = sink.entries().to_vec();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(
+            entries[1].raw.as_deref(),
+            Some("example.org/docs/intro.html")
+        );
+        let upper = entries[2].raw.as_deref().unwrap();
+        assert!(upper.starts_with("example.org/docs/Intro-") && upper.ends_with(".html"));
+        assert_eq!(
+            entries[1].text.as_deref(),
+            Some("example.org/docs/intro.txt")
+        );
+
+        sink.finish(&CrawlSummary::start("https://example.org/docs/"))
+            .unwrap();
+
+        let read = |path: &str| fs::read_to_string(root.join(path)).unwrap();
+        assert_eq!(
+            read("example.org/docs/index.html"),
+            r#"<a href="intro.html#setup">Intro</a> <a href='https://elsewhere.org/'>x</a> <img src="/logo.png">"#
+        );
+        assert_eq!(
+            read("example.org/docs/intro.html"),
+            r#"<a href="index.html">Back</a>"#
+        );
+        assert_eq!(
+            read("example.org/docs/intro.md"),
+            "# Intro\nWelcome.\n## Setup\nRun it.\n"
+        );
+
+        let manifest: serde_json::Value = serde_json::from_str(&read(MANIFEST_FILE)).unwrap();
+        assert_eq!(manifest["pages"].as_array().unwrap().len(), 3);
+        assert_eq!(manifest["pages"][0]["url"], "https://example.org/docs/");
+
+        // An incremental crawl keeps the files of unchanged pages as they were
+        let mut sink = Box::new(MirrorSink::new(&root).unwrap());
+        let mut unchanged = PageData::new(
+            "https://example.org/docs/intro".to_string(),
+            None,
+            String::new(),
+            Vec::new(),
+        );
+        unchanged.metadata.unchanged = true;
+        sink.write_page(&unchanged).unwrap();
+        assert_eq!(sink.entries(), &entries[1..2]);
+        sink.finish(&CrawlSummary::start("https://example.org/docs/"))
+            .unwrap();
+        assert_eq!(
+            read("example.org/docs/intro.html"),
+            r#"<a href="index.html">Back</a>"#
+        );
+        let manifest: serde_json::Value = serde_json::from_str(&read(MANIFEST_FILE)).unwrap();
+        assert_eq!(manifest["pages"].as_array().unwrap().len(), 1);
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_file_and_directory_clash() {
+        let root = std::env::temp_dir().join(format!("yield-page-mirror-{}", uuid::Uuid::new_v4()));
+        let mut sink = MirrorSink::new(&root).unwrap();
+        let feed = |url: &str| {
+            let mut page = page(url, url);
+            page.metadata.content_type = Some("application/atom+xml".to_string());
+            page
+        };
+
+        // A file, then a page that needs it as a directory
+        sink.write_page(&feed("https://example.org/feed")).unwrap();
+        sink.write_page(&page(
+            "https://example.org/feed/atom",
+            "https://example.org/feed/atom",
+        ))
+        .unwrap();
+        // A directory, then a file with its name
+        sink.write_page(&page(
+            "https://example.org/blog/post",
+            "https://example.org/blog/post",
+        ))
+        .unwrap();
+        sink.write_page(&feed("https://example.org/blog")).unwrap();
+
+        let paths: Vec<&str> = sink
+            .entries()
+            .iter()
+            .map(|entry| entry.raw.as_deref().unwrap())
+            .collect();
+        assert_eq!(paths[0], "example.org/feed");
+        assert!(paths[1].starts_with("example.org/feed-") && paths[1].ends_with("/atom.html"));
+        assert_eq!(paths[2], "example.org/blog/post.html");
+        assert!(paths[3].starts_with("example.org/blog-"));
+        for (path, url) in paths.iter().zip(["feed", "feed/atom", "blog/post", "blog"]) {
+            assert_eq!(
+                fs::read_to_string(root.join(path)).unwrap(),
+                format!("https://example.org/{}", url)
+            );
+        }
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
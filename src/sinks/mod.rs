@@ -0,0 +1,89 @@
+pub mod jsonl;
+pub mod mirror;
+pub mod search;
+pub mod sqlite;
+
+pub use jsonl::{Compression, JsonLinesSink};
+pub use mirror::{MirrorFiles, MirrorSink};
+pub use search::{SearchHit, SearchIndex, SearchIndexSink};
+pub use sqlite::SqliteSink;
+
+use crate::results::{CrawlError, PageData};
+use crate::utils::unix_timestamp;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::time::Instant;
+
+/// Error type for page sinks
+pub type SinkError = Box<dyn Error + Send + Sync>;
+
+/// A destination for crawled pages, such as a file or a database
+pub trait PageSink {
+    /// Writes one page
+    fn write_page(&mut self, page: &PageData) -> Result<(), SinkError>;
+
+    /// Records a URL that failed; sinks that only hold pages ignore it
+    fn write_error(&mut self, _error: &CrawlError) -> Result<(), SinkError> {
+        Ok(())
+    }
+
+    /// Completes the output once the crawl is over, recording its summary if supported
+    fn finish(self: Box<Self>, summary: &CrawlSummary) -> Result<(), SinkError>;
+}
+
+/// Totals for a finished crawl
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CrawlSummary {
+    /// URI the crawl started from
+    pub uri: String,
+    /// Number of pages written
+    pub pages: usize,
+    /// Number of links found on those pages
+    pub links: usize,
+    /// Number of URLs that failed
+    #[serde(default)]
+    pub errors: usize,
+    /// When the crawl started, in seconds since the Unix epoch
+    pub started_at: u64,
+    /// How long the crawl took, in seconds
+    pub duration_secs: f64,
+    /// Whether pages unchanged since the previous crawl were left out of the output
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub omits_unchanged: bool,
+    #[serde(skip)]
+    start: Option<Instant>,
+}
+
+impl CrawlSummary {
+    /// Starts the summary of a crawl beginning now
+    pub fn start(uri: &str) -> Self {
+        Self {
+            uri: uri.to_string(),
+            pages: 0,
+            links: 0,
+            errors: 0,
+            started_at: unix_timestamp(),
+            duration_secs: 0.0,
+            omits_unchanged: false,
+            start: Some(Instant::now()),
+        }
+    }
+
+    /// Counts a page
+    pub fn record(&mut self, page: &PageData) {
+        self.pages += 1;
+        self.links += page.links.len();
+    }
+
+    /// Counts a failed URL
+    pub fn record_error(&mut self) {
+        self.errors += 1;
+    }
+
+    /// Stops the clock, setting the duration
+    pub fn stop(&mut self) {
+        if let Some(start) = self.start {
+            self.duration_secs = start.elapsed().as_secs_f64();
+        }
+    }
+}
@@ -0,0 +1,270 @@
+use crate::results::PageData;
+use crate::sinks::{CrawlSummary, PageSink, SinkError};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use tantivy::collector::TopDocs;
+use tantivy::directory::MmapDirectory;
+use tantivy::query::QueryParser;
+use tantivy::schema::{Field, STORED, STRING, Schema, TEXT, Value};
+use tantivy::snippet::SnippetGenerator;
+use tantivy::{Index, IndexWriter, TantivyDocument, Term, doc};
+
+/// Memory the index writer may use before flushing to disk
+const WRITER_MEMORY: usize = 50_000_000;
+
+/// Longest snippet of content returned with a search hit, in characters
+const SNIPPET_CHARS: usize = 200;
+
+/// A page matching a search
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchHit {
+    /// URL of the page
+    pub url: String,
+    /// Title of the page
+    pub title: Option<String>,
+    /// Relevance of the page to the query; higher is better
+    pub score: f32,
+    /// Content around the matched terms, with matches wrapped in `<b>` tags
+    pub snippet: String,
+}
+
+/// A full-text index of crawled pages, stored in a directory
+///
+/// Pages are indexed by title, headings, content and URL, and keyed by URL so
+/// indexing a page again replaces it.
+pub struct SearchIndex {
+    index: Index,
+    url: Field,
+    title: Field,
+    headings: Field,
+    content: Field,
+}
+
+impl SearchIndex {
+    /// Opens the index in a directory, creating the directory and index if needed
+    pub fn open(path: &Path) -> Result<Self, SinkError> {
+        fs::create_dir_all(path)?;
+        let index = Index::open_or_create(MmapDirectory::open(path)?, Self::schema())?;
+        Self::from_index(index)
+    }
+
+    /// Opens an existing index, failing if there is none in the directory
+    pub fn open_existing(path: &Path) -> Result<Self, SinkError> {
+        if !Self::exists(path) {
+            return Err(format!("No search index in {}", path.display()).into());
+        }
+        Self::from_index(Index::open_in_dir(path)?)
+    }
+
+    /// Creates an index held in memory, e.g. for tests
+    pub fn in_memory() -> Result<Self, SinkError> {
+        Self::from_index(Index::create_in_ram(Self::schema()))
+    }
+
+    /// Returns whether a directory holds a search index
+    pub fn exists(path: &Path) -> bool {
+        path.join("meta.json").is_file()
+    }
+
+    fn schema() -> Schema {
+        let mut schema = Schema::builder();
+        schema.add_text_field("url", STRING | STORED);
+        schema.add_text_field("title", TEXT | STORED);
+        schema.add_text_field("headings", TEXT);
+        schema.add_text_field("content", TEXT | STORED);
+        schema.build()
+    }
+
+    fn from_index(index: Index) -> Result<Self, SinkError> {
+        let schema = index.schema();
+        Ok(Self {
+            url: schema.get_field("url")?,
+            title: schema.get_field("title")?,
+            headings: schema.get_field("headings")?,
+            content: schema.get_field("content")?,
+            index,
+        })
+    }
+
+    /// Returns a sink that adds crawled pages to this index
+    pub fn sink(self) -> Result<SearchIndexSink, SinkError> {
+        let writer = self.index.writer(WRITER_MEMORY)?;
+        Ok(SearchIndexSink {
+            index: self,
+            writer,
+        })
+    }
+
+    /// Finds the pages best matching a query
+    ///
+    /// Queries use Tantivy's syntax: terms match any indexed field, `title:rust`
+    /// matches one field, and `"exact phrase"`, `AND`, `OR` and `-excluded` work
+    /// as usual. Title matches rank above content matches.
+    pub fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchHit>, SinkError> {
+        let searcher = self.index.reader()?.searcher();
+        let mut parser = QueryParser::for_index(
+            &self.index,
+            vec![self.title, self.headings, self.content, self.url],
+        );
+        parser.set_field_boost(self.title, 3.0);
+        parser.set_field_boost(self.headings, 2.0);
+        let query = parser.parse_query(query)?;
+
+        let mut snippets = SnippetGenerator::create(&searcher, &*query, self.content)?;
+        snippets.set_max_num_chars(SNIPPET_CHARS);
+
+        let mut hits = Vec::new();
+        for (score, address) in searcher.search(&query, &TopDocs::with_limit(limit))? {
+            let document: TantivyDocument = searcher.doc(address)?;
+            let text = |field| {
+                document
+                    .get_first(field)
+                    .and_then(|value| value.as_str())
+                    .map(|value| value.to_string())
+            };
+            hits.push(SearchHit {
+                url: text(self.url).unwrap_or_default(),
+                title: text(self.title),
+                score,
+                snippet: snippets.snippet_from_doc(&document).to_html(),
+            });
+        }
+        Ok(hits)
+    }
+}
+
+/// Adds crawled pages to a search index, committing them when the crawl finishes
+pub struct SearchIndexSink {
+    index: SearchIndex,
+    writer: IndexWriter,
+}
+
+impl SearchIndexSink {
+    /// Opens or creates the index in a directory for writing
+    pub fn open(path: &Path) -> Result<Self, SinkError> {
+        SearchIndex::open(path)?.sink()
+    }
+
+    /// Returns the index being written
+    pub fn index(&self) -> &SearchIndex {
+        &self.index
+    }
+}
+
+impl PageSink for SearchIndexSink {
+    fn write_page(&mut self, page: &PageData) -> Result<(), SinkError> {
+        // An unchanged page is already indexed from an earlier crawl
+        if page.metadata.unchanged {
+            return Ok(());
+        }
+        let index = &self.index;
+        let headings = page
+            .headings
+            .iter()
+            .map(|heading| heading.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        self.writer
+            .delete_term(Term::from_field_text(index.url, &page.url));
+        self.writer.add_document(doc!(
+            index.url => page.url.as_str(),
+            index.title => page.title.as_deref().unwrap_or_default(),
+            index.headings => headings,
+            index.content => page.content.as_str(),
+        ))?;
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>, _summary: &CrawlSummary) -> Result<(), SinkError> {
+        self.writer.commit()?;
+        self.writer.wait_merging_threads()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::Heading;
+
+    fn page(url: &str, title: &str, heading: &str, content: &str) -> PageData {
+        let mut page = PageData::new(
+            url.to_string(),
+            Some(title.to_string()),
+            content.to_string(),
+            Vec::new(),
+        );
+        page.headings = vec![Heading {
+            level: 2,
+            text: heading.to_string(),
+        }];
+        page
+    }
+
+    #[test]
+    fn test_index_and_search() {
+        let path = std::env::temp_dir().join(format!("yield-page-index-{}", uuid::Uuid::new_v4()));
+        let summary = CrawlSummary::start("https://example.org/");
+
+        let mut sink = Box::new(SearchIndexSink::open(&path).unwrap());
+        sink.write_page(&page(
+            "https://example.org/install",
+            "Installing",
+            "Requirements",
+            "Download the release and unpack it.",
+        ))
+        .unwrap();
+        sink.write_page(&page(
+            "https://example.org/faq",
+            "FAQ",
+            "Does it need a database?",
+            "No, installing it only needs a release download.",
+        ))
+        .unwrap();
+        sink.finish(&summary).unwrap();
+
+        let index = SearchIndex::open_existing(&path).unwrap();
+        let hits = index.search("installing", 10).unwrap();
+        assert_eq!(hits.len(), 2);
+        // The title match ranks first
+        assert_eq!(hits[0].url, "https://example.org/install");
+        assert_eq!(hits[0].title.as_deref(), Some("Installing"));
+        assert!(hits[1].snippet.contains("<b>installing</b>"));
+
+        let hits = index.search("database", 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].url, "https://example.org/faq");
+        assert!(index.search("title:faq", 10).unwrap().len() == 1);
+
+        // Indexing a page again replaces it
+        let mut sink = Box::new(index.sink().unwrap());
+        sink.write_page(&page(
+            "https://example.org/faq",
+            "FAQ",
+            "Questions",
+            "Nothing here.",
+        ))
+        .unwrap();
+        sink.finish(&summary).unwrap();
+        let index = SearchIndex::open_existing(&path).unwrap();
+        assert!(index.search("database", 10).unwrap().is_empty());
+        assert_eq!(index.search("nothing", 10).unwrap().len(), 1);
+
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_open_existing_without_index() {
+        let path = std::env::temp_dir().join(format!("yield-page-index-{}", uuid::Uuid::new_v4()));
+        assert!(SearchIndex::open_existing(&path).is_err());
+        assert!(
+            SearchIndex::in_memory()
+                .unwrap()
+                .search("x", 1)
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,385 @@
+use crate::results::{CrawlError, PageData};
+use crate::sinks::{CrawlSummary, PageSink, SinkError};
+use crate::utils::unix_timestamp;
+use rusqlite::{Connection, params};
+use std::path::Path;
+
+/// Version of the database schema, stored as the database's `user_version`
+pub const SCHEMA_VERSION: i64 = 1;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS crawl_runs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    uri TEXT NOT NULL,
+    started_at INTEGER NOT NULL,
+    finished_at INTEGER,
+    duration_secs REAL,
+    pages INTEGER NOT NULL DEFAULT 0,
+    links INTEGER NOT NULL DEFAULT 0,
+    errors INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE IF NOT EXISTS pages (
+    url TEXT PRIMARY KEY,
+    title TEXT,
+    content TEXT NOT NULL,
+    language TEXT,
+    content_type TEXT,
+    encoding TEXT,
+    archive_url TEXT,
+    section_path TEXT NOT NULL,
+    data TEXT NOT NULL,
+    first_run_id INTEGER NOT NULL REFERENCES crawl_runs(id),
+    last_run_id INTEGER NOT NULL REFERENCES crawl_runs(id),
+    first_seen_at INTEGER NOT NULL,
+    last_seen_at INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS links (
+    source_url TEXT NOT NULL REFERENCES pages(url) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    target_url TEXT NOT NULL,
+    kind TEXT,
+    text TEXT,
+    rel TEXT,
+    nofollow INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (source_url, position)
+);
+
+CREATE INDEX IF NOT EXISTS links_target_url ON links(target_url);
+
+CREATE TABLE IF NOT EXISTS errors (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    run_id INTEGER NOT NULL REFERENCES crawl_runs(id),
+    url TEXT NOT NULL,
+    message TEXT NOT NULL,
+    occurred_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS errors_url ON errors(url);
+";
+
+/// Writes pages, their links and failed URLs to a SQLite database
+///
+/// Each crawl is recorded in `crawl_runs`. Pages are keyed by URL, so crawling
+/// into an existing database updates pages seen before and keeps when each was
+/// first and last seen; a page's links are replaced with the ones found this run.
+/// Errors are kept per run.
+pub struct SqliteSink {
+    connection: Connection,
+    run_id: i64,
+}
+
+impl SqliteSink {
+    /// Returns whether a path names a SQLite database, by its extension
+    /// (`.db`, `.sqlite` or `.sqlite3`)
+    pub fn is_database_path(path: &Path) -> bool {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| matches!(ext, "db" | "sqlite" | "sqlite3"))
+    }
+
+    /// Opens or creates a database and starts recording a crawl of `uri`
+    pub fn open(path: &Path, uri: &str) -> Result<Self, SinkError> {
+        Self::from_connection(Connection::open(path)?, uri)
+    }
+
+    /// Starts recording a crawl of `uri` in an open database, creating the schema if needed
+    pub fn from_connection(connection: Connection, uri: &str) -> Result<Self, SinkError> {
+        let version: i64 = connection.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if version != 0 && version != SCHEMA_VERSION {
+            return Err(format!(
+                "Database schema version {} is not supported (expected {})",
+                version, SCHEMA_VERSION
+            )
+            .into());
+        }
+
+        // WAL lets analysts query the database while a crawl is writing to it
+        connection.pragma_update(None, "journal_mode", "WAL")?;
+        connection.pragma_update(None, "foreign_keys", true)?;
+        connection.execute_batch(SCHEMA)?;
+        connection.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+
+        connection.execute(
+            "INSERT INTO crawl_runs (uri, started_at) VALUES (?1, ?2)",
+            params![uri, unix_timestamp() as i64],
+        )?;
+        let run_id = connection.last_insert_rowid();
+
+        Ok(Self { connection, run_id })
+    }
+
+    /// Returns the ID of the crawl being recorded
+    pub fn run_id(&self) -> i64 {
+        self.run_id
+    }
+
+    /// Returns the database connection, e.g. to query what has been written
+    pub fn connection(&self) -> &Connection {
+        &self.connection
+    }
+}
+
+impl PageSink for SqliteSink {
+    fn write_page(&mut self, page: &PageData) -> Result<(), SinkError> {
+        let now = unix_timestamp() as i64;
+
+        // An unchanged page only carries its URL and links, so keep what's stored
+        if page.metadata.unchanged {
+            self.connection.execute(
+                "UPDATE pages SET last_run_id = ?1, last_seen_at = ?2 WHERE url = ?3",
+                params![self.run_id, now, page.url],
+            )?;
+            return Ok(());
+        }
+        let transaction = self.connection.transaction()?;
+
+        transaction.execute(
+            "INSERT INTO pages (url, title, content, language, content_type, encoding, archive_url,
+                                section_path, data, first_run_id, last_run_id, first_seen_at, last_seen_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?10, ?11, ?11)
+             ON CONFLICT (url) DO UPDATE SET
+                title = excluded.title,
+                content = excluded.content,
+                language = excluded.language,
+                content_type = excluded.content_type,
+                encoding = excluded.encoding,
+                archive_url = excluded.archive_url,
+                section_path = excluded.section_path,
+                data = excluded.data,
+                last_run_id = excluded.last_run_id,
+                last_seen_at = excluded.last_seen_at",
+            params![
+                page.url,
+                page.title,
+                page.content,
+                page.language,
+                page.metadata.content_type,
+                page.metadata.encoding,
+                page.metadata.archive_url,
+                serde_json::to_string(&page.section_path)?,
+                serde_json::to_string(page)?,
+                self.run_id,
+                now,
+            ],
+        )?;
+
+        transaction.execute("DELETE FROM links WHERE source_url = ?1", [&page.url])?;
+        {
+            let mut insert = transaction.prepare(
+                "INSERT INTO links (source_url, position, target_url, kind, text, rel, nofollow)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )?;
+            for (position, (url, link)) in page.outgoing_links().enumerate() {
+                let kind = link
+                    .map(|link| serde_json::to_value(link.kind))
+                    .transpose()?;
+                let rel = link
+                    .filter(|link| !link.rel.is_empty())
+                    .map(|link| link.rel.join(" "));
+                insert.execute(params![
+                    page.url,
+                    position as i64,
+                    url,
+                    kind.as_ref().and_then(|kind| kind.as_str()),
+                    link.and_then(|link| link.text.as_deref()),
+                    rel,
+                    link.is_some_and(|link| link.nofollow)
+                ])?;
+            }
+        }
+
+        transaction.commit()?;
+        Ok(())
+    }
+
+    fn write_error(&mut self, error: &CrawlError) -> Result<(), SinkError> {
+        self.connection.execute(
+            "INSERT INTO errors (run_id, url, message, occurred_at) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                self.run_id,
+                error.url,
+                error.message,
+                error.occurred_at as i64
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>, summary: &CrawlSummary) -> Result<(), SinkError> {
+        self.connection.execute(
+            "UPDATE crawl_runs
+             SET finished_at = ?1, duration_secs = ?2, pages = ?3, links = ?4, errors = ?5
+             WHERE id = ?6",
+            params![
+                unix_timestamp() as i64,
+                summary.duration_secs,
+                summary.pages as i64,
+                summary.links as i64,
+                summary.errors as i64,
+                self.run_id
+            ],
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::{Link, LinkKind};
+
+    fn page(url: &str, title: &str, links: &[&str]) -> PageData {
+        let mut page = PageData::new(
+            url.to_string(),
+            Some(title.to_string()),
+            format!("{} content", title),
+            links.iter().map(|l| l.to_string()).collect(),
+        );
+        page.link_details = links
+            .iter()
+            .map(|l| Link {
+                url: l.to_string(),
+                href: l.to_string(),
+                kind: LinkKind::Anchor,
+                text: Some("more".to_string()),
+                rel: vec!["nofollow".to_string()],
+                nofollow: true,
+                element: "a".to_string(),
+                hreflang: None,
+            })
+            .collect();
+        page
+    }
+
+    fn count(connection: &Connection, sql: &str) -> i64 {
+        connection.query_row(sql, [], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn test_pages_links_and_errors() {
+        let mut sink = SqliteSink::from_connection(
+            Connection::open_in_memory().unwrap(),
+            "https://example.org/",
+        )
+        .unwrap();
+        let mut summary = CrawlSummary::start("https://example.org/");
+
+        let home = page(
+            "https://example.org/",
+            "Home",
+            &["https://example.org/a", "https://example.org/b"],
+        );
+        summary.record(&home);
+        sink.write_page(&home).unwrap();
+        sink.write_error(&CrawlError::new("https://example.org/broken", "Timeout"))
+            .unwrap();
+        summary.record_error();
+
+        let connection = sink.connection();
+        assert_eq!(count(connection, "SELECT COUNT(*) FROM pages"), 1);
+        assert_eq!(count(connection, "SELECT COUNT(*) FROM links"), 2);
+        let (kind, rel, nofollow): (String, String, bool) = connection
+            .query_row(
+                "SELECT kind, rel, nofollow FROM links WHERE target_url = 'https://example.org/b'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(
+            (kind.as_str(), rel.as_str(), nofollow),
+            ("anchor", "nofollow", true)
+        );
+        let message: String = connection
+            .query_row("SELECT message FROM errors", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(message, "Timeout");
+
+        assert_eq!(summary.errors, 1);
+    }
+
+    #[test]
+    fn test_database_path() {
+        assert!(SqliteSink::is_database_path(Path::new("crawl.db")));
+        assert!(SqliteSink::is_database_path(Path::new("out/crawl.sqlite3")));
+        assert!(!SqliteSink::is_database_path(Path::new("crawl.jsonl")));
+        assert!(!SqliteSink::is_database_path(Path::new("sqlite")));
+    }
+
+    #[test]
+    fn test_upsert_across_runs() {
+        let path = std::env::temp_dir().join(format!("yield-page-{}.db", uuid::Uuid::new_v4()));
+
+        let mut first = Box::new(SqliteSink::open(&path, "https://example.org/").unwrap());
+        first
+            .write_page(&page(
+                "https://example.org/",
+                "Old",
+                &["https://example.org/a", "https://example.org/b"],
+            ))
+            .unwrap();
+        first
+            .finish(&CrawlSummary::start("https://example.org/"))
+            .unwrap();
+
+        let mut second = Box::new(SqliteSink::open(&path, "https://example.org/").unwrap());
+        second
+            .write_page(&page(
+                "https://example.org/",
+                "New",
+                &["https://example.org/c"],
+            ))
+            .unwrap();
+        let second_run = second.run_id();
+        let mut summary = CrawlSummary::start("https://example.org/");
+        summary.pages = 1;
+        summary.links = 1;
+        second.finish(&summary).unwrap();
+
+        let connection = Connection::open(&path).unwrap();
+        assert_eq!(count(&connection, "SELECT COUNT(*) FROM crawl_runs"), 2);
+        assert_eq!(count(&connection, "SELECT COUNT(*) FROM pages"), 1);
+        let (title, first_run_id, last_run_id): (String, i64, i64) = connection
+            .query_row(
+                "SELECT title, first_run_id, last_run_id FROM pages",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(title, "New");
+        assert_eq!(first_run_id, second_run - 1);
+        assert_eq!(last_run_id, second_run);
+        assert_eq!(count(&connection, "SELECT COUNT(*) FROM links"), 1);
+        assert_eq!(
+            count(
+                &connection,
+                &format!("SELECT pages FROM crawl_runs WHERE id = {}", second_run)
+            ),
+            1
+        );
+        assert_eq!(count(&connection, "PRAGMA user_version"), SCHEMA_VERSION);
+
+        // An unchanged page is only marked as seen again
+        let mut third = Box::new(SqliteSink::open(&path, "https://example.org/").unwrap());
+        let mut unchanged = page("https://example.org/", "", &[]);
+        unchanged.metadata.unchanged = true;
+        third.write_page(&unchanged).unwrap();
+        let third_run = third.run_id();
+        third
+            .finish(&CrawlSummary::start("https://example.org/"))
+            .unwrap();
+        let (title, last_run_id): (String, i64) = connection
+            .query_row("SELECT title, last_run_id FROM pages", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!((title.as_str(), last_run_id), ("New", third_run));
+        assert_eq!(count(&connection, "SELECT COUNT(*) FROM links"), 1);
+
+        drop(connection);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+    }
+}
@@ -1,4 +1,5 @@
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use url::Url;
 
 /// Utility function to create a reasonable timeout for web requests
 pub fn calculate_timeout(base_ms: u64, url_length: usize) -> Duration {
@@ -11,12 +12,71 @@ pub fn calculate_timeout(base_ms: u64, url_length: usize) -> Duration {
 pub fn sanitize_filename(url: &str) -> String {
     // Remove protocol and replace invalid filename characters
     let mut name = url.replace("http://", "").replace("https://", "");
-    name = name.replace(['/', ':', '?', '&', '=', '#', '%'], "_");
+    name = name.replace(
+        [
+            '/', '\\', ':', '?', '&', '=', '#', '%', '*', '"', '<', '>', '|',
+        ],
+        "_",
+    );
+    name.retain(|c| !c.is_control());
 
-    // Limit filename length
+    // Limit filename length, without splitting a character
     if name.len() > 100 {
-        name[..100].to_string()
-    } else {
-        name
+        let mut end = 100;
+        while !name.is_char_boundary(end) {
+            end -= 1;
+        }
+        name.truncate(end);
+    }
+    name
+}
+
+/// Drops the fragment of a URL, so links to parts of a page point at the page
+pub fn without_fragment(url: &str) -> String {
+    match Url::parse(url) {
+        Ok(mut parsed) => {
+            parsed.set_fragment(None);
+            parsed.to_string()
+        }
+        Err(_) => url.split('#').next().unwrap_or_default().to_string(),
+    }
+}
+
+/// Current time in seconds since the Unix epoch
+pub fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_filename() {
+        assert_eq!(
+            sanitize_filename("https://example.org/a?b=c#d"),
+            "example.org_a_b_c_d"
+        );
+        assert_eq!(sanitize_filename("a<b>|\"c\"\n"), "a_b___c_");
+
+        // Long names are cut on a character boundary
+        let name = sanitize_filename(&"é".repeat(80));
+        assert_eq!(name.len(), 100);
+        assert!(name.chars().all(|c| c == 'é'));
+    }
+
+    #[test]
+    fn test_without_fragment() {
+        assert_eq!(
+            without_fragment("https://example.org/docs#setup"),
+            "https://example.org/docs"
+        );
+        assert_eq!(
+            without_fragment("HTTPS://Example.org"),
+            "https://example.org/"
+        );
+        assert_eq!(without_fragment("/relative#top"), "/relative");
     }
 }
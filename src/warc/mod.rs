@@ -0,0 +1,383 @@
+pub mod reader;
+pub mod writer;
+
+pub use reader::WarcReader;
+pub use writer::WarcWriter;
+
+use crate::crawlers::fetch::FetchedResource;
+use flate2::read::{GzDecoder, ZlibDecoder};
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use std::error::Error;
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+
+/// Version line written at the start of each record
+pub const WARC_VERSION: &str = "WARC/1.1";
+
+/// Error type for reading and writing WARC files
+pub type WarcError = Box<dyn Error + Send + Sync>;
+
+/// Options for writing the raw responses of a crawl to WARC files
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WarcOptions {
+    /// Directory the WARC files are written to, created if missing
+    pub directory: PathBuf,
+
+    /// Start of each file name, followed by a timestamp and a serial number
+    pub prefix: String,
+
+    /// Size in bytes after which a new file is started
+    pub max_file_size: u64,
+
+    /// Whether to gzip each record (`.warc.gz`)
+    pub gzip: bool,
+
+    /// Whether to write a metadata and a conversion record with the extracted text of each page
+    pub extracted_text: bool,
+}
+
+impl Default for WarcOptions {
+    fn default() -> Self {
+        Self {
+            directory: PathBuf::from("warc"),
+            prefix: "yield-page".to_string(),
+            max_file_size: 1024 * 1024 * 1024,
+            gzip: true,
+            extracted_text: true,
+        }
+    }
+}
+
+/// One record of a WARC file: named header fields and a content block
+#[derive(Debug, Clone, PartialEq)]
+pub struct WarcRecord {
+    /// Header fields in order, except `Content-Length`, which is derived from the block
+    pub headers: Vec<(String, String)>,
+    /// The content block
+    pub block: Vec<u8>,
+}
+
+impl WarcRecord {
+    /// Creates a record with a new ID and the current date
+    pub fn new(record_type: &str, content_type: &str, block: Vec<u8>) -> Self {
+        let headers = vec![
+            ("WARC-Type".to_string(), record_type.to_string()),
+            ("WARC-Record-ID".to_string(), new_record_id()),
+            ("WARC-Date".to_string(), warc_date()),
+            ("Content-Type".to_string(), content_type.to_string()),
+        ];
+        Self { headers, block }
+    }
+
+    /// Adds a header field
+    pub fn with_header(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.headers.push((name.to_string(), value.into()));
+        self
+    }
+
+    /// Returns the first value of a header field (case-insensitive)
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Returns the record type (`response`, `request`, `metadata`, ...)
+    pub fn record_type(&self) -> Option<&str> {
+        self.header("WARC-Type")
+    }
+
+    /// Returns the record ID, including its angle brackets
+    pub fn record_id(&self) -> Option<&str> {
+        self.header("WARC-Record-ID")
+    }
+
+    /// Returns the URI the record is about
+    pub fn target_uri(&self) -> Option<&str> {
+        // WARC 1.0 writers may wrap the URI in angle brackets
+        self.header("WARC-Target-URI")
+            .map(|uri| uri.trim_start_matches('<').trim_end_matches('>'))
+    }
+
+    /// Writes the record in WARC format
+    pub fn write_to(&self, mut writer: impl Write) -> io::Result<()> {
+        write!(writer, "{}\r\n", WARC_VERSION)?;
+        for (name, value) in &self.headers {
+            write!(writer, "{}: {}\r\n", name, value)?;
+        }
+        write!(writer, "Content-Length: {}\r\n\r\n", self.block.len())?;
+        writer.write_all(&self.block)?;
+        writer.write_all(b"\r\n\r\n")
+    }
+
+    /// Reads the HTTP response or resource the record archives
+    ///
+    /// Returns `None` for other record types and unparseable responses. Chunked
+    /// and gzip- or deflate-encoded bodies, as written by other archivers, are decoded.
+    pub fn to_resource(&self) -> Option<FetchedResource> {
+        let url = self.target_uri()?.to_string();
+        match self.record_type()? {
+            "response" => parse_http_response(url, &self.block),
+            "resource" => Some(FetchedResource {
+                url,
+                status: 200,
+                headers: self
+                    .header("Content-Type")
+                    .map(|ct| vec![("content-type".to_string(), ct.to_string())])
+                    .unwrap_or_default(),
+                body: self.block.clone(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Parses an archived HTTP response
+fn parse_http_response(url: String, block: &[u8]) -> Option<FetchedResource> {
+    let mut raw_headers = [httparse::EMPTY_HEADER; 128];
+    let mut response = httparse::Response::new(&mut raw_headers);
+    let httparse::Status::Complete(header_len) = response.parse(block).ok()? else {
+        return None;
+    };
+
+    let status = response.code?;
+    let headers: Vec<(String, String)> = response
+        .headers
+        .iter()
+        .map(|h| {
+            (
+                h.name.to_ascii_lowercase(),
+                String::from_utf8_lossy(h.value).into_owned(),
+            )
+        })
+        .collect();
+    let header = |name: &str| {
+        headers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.to_ascii_lowercase())
+    };
+
+    let mut body = block[header_len..].to_vec();
+    if header("transfer-encoding").is_some_and(|te| te.contains("chunked")) {
+        body = dechunk(&body);
+    }
+    let decoded = match header("content-encoding").as_deref() {
+        Some("gzip" | "x-gzip") => decode(GzDecoder::new(body.as_slice())),
+        Some("deflate") => decode(ZlibDecoder::new(body.as_slice())),
+        _ => None,
+    };
+    // Undecodable bodies are kept as they are; the parsers may still make sense of them
+    let body = decoded.unwrap_or(body);
+
+    Some(FetchedResource {
+        url,
+        status,
+        headers,
+        body,
+    })
+}
+
+/// Reads a whole compressed stream, or `None` if it's invalid
+fn decode(mut reader: impl Read) -> Option<Vec<u8>> {
+    let mut decoded = Vec::new();
+    reader.read_to_end(&mut decoded).ok().map(|_| decoded)
+}
+
+/// Removes HTTP/1.1 chunked transfer coding, keeping whatever was received of a truncated body
+fn dechunk(mut bytes: &[u8]) -> Vec<u8> {
+    let mut body = Vec::new();
+    while let Some(line_end) = bytes.windows(2).position(|w| w == b"\r\n") {
+        let size_field = String::from_utf8_lossy(&bytes[..line_end]);
+        let size_field = size_field.split(';').next().unwrap_or_default().trim();
+        let Ok(size) = usize::from_str_radix(size_field, 16) else {
+            break;
+        };
+        if size == 0 {
+            break;
+        }
+        let chunk = &bytes[line_end + 2..];
+        let size = size.min(chunk.len());
+        body.extend_from_slice(&chunk[..size]);
+        bytes = chunk[size..]
+            .strip_prefix(b"\r\n")
+            .unwrap_or(&chunk[size..]);
+    }
+    body
+}
+
+/// Creates a unique record ID
+fn new_record_id() -> String {
+    format!("<urn:uuid:{}>", uuid::Uuid::new_v4())
+}
+
+/// Returns the current time in the W3C format WARC dates use
+fn warc_date() -> String {
+    chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+/// Returns a digest in the `sha1:<base32>` form used by WARC digest fields
+fn sha1_digest(bytes: &[u8]) -> String {
+    format!(
+        "sha1:{}",
+        data_encoding::BASE32.encode(&Sha1::digest(bytes))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::results::PageData;
+    use std::fs;
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("yield-page-warc-{}", uuid::Uuid::new_v4()))
+    }
+
+    fn resource(url: &str, body: &str) -> FetchedResource {
+        FetchedResource {
+            url: url.to_string(),
+            status: 200,
+            headers: vec![
+                ("content-type".to_string(), "text/html".to_string()),
+                ("transfer-encoding".to_string(), "chunked".to_string()),
+            ],
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    fn read_all(files: &[PathBuf]) -> Vec<WarcRecord> {
+        files
+            .iter()
+            .flat_map(|path| WarcReader::open(path).unwrap())
+            .map(Result::unwrap)
+            .collect()
+    }
+
+    #[test]
+    fn test_write_and_read() {
+        let directory = temp_dir();
+        let mut writer = WarcWriter::new(WarcOptions {
+            directory: directory.clone(),
+            ..WarcOptions::default()
+        });
+
+        let fetched = resource("https://example.org/docs?page=2", "<p>Hello</p>");
+        let response_id = writer.write_exchange(&fetched).unwrap();
+        let page = PageData::new(
+            fetched.url.clone(),
+            Some("Docs".to_string()),
+            "Hello".to_string(),
+            vec!["https://example.org/other".to_string()],
+        );
+        writer.write_page(&page, &response_id).unwrap();
+        writer.flush().unwrap();
+
+        assert_eq!(writer.files().len(), 1);
+        assert!(writer.files()[0].to_string_lossy().ends_with(".warc.gz"));
+        let records = read_all(writer.files());
+        let types: Vec<_> = records.iter().map(|r| r.record_type().unwrap()).collect();
+        assert_eq!(
+            types,
+            vec!["warcinfo", "response", "request", "metadata", "conversion"]
+        );
+
+        let response = &records[1];
+        assert_eq!(response.record_id(), Some(response_id.as_str()));
+        let replayed = response.to_resource().unwrap();
+        assert_eq!(replayed.url, fetched.url);
+        assert_eq!(replayed.status, 200);
+        assert_eq!(replayed.content_type(), Some("text/html"));
+        assert_eq!(replayed.header("transfer-encoding"), None);
+        assert_eq!(replayed.body, fetched.body);
+
+        let request = String::from_utf8_lossy(&records[2].block).into_owned();
+        assert!(request.starts_with("GET /docs?page=2 HTTP/1.1\r\nHost: example.org\r\n"));
+        assert_eq!(
+            records[2].header("WARC-Concurrent-To"),
+            Some(response_id.as_str())
+        );
+
+        let metadata = String::from_utf8_lossy(&records[3].block).into_owned();
+        assert!(metadata.contains("title: Docs\r\n"));
+        assert!(metadata.contains("outlink: https://example.org/other\r\n"));
+        assert_eq!(records[4].block, b"Hello");
+        assert_eq!(
+            records[4].header("WARC-Refers-To"),
+            Some(response_id.as_str())
+        );
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_rotation() {
+        let directory = temp_dir();
+        let mut writer = WarcWriter::new(WarcOptions {
+            directory: directory.clone(),
+            max_file_size: 1,
+            gzip: false,
+            ..WarcOptions::default()
+        });
+        for i in 0..3 {
+            let fetched = resource(&format!("https://example.org/{}", i), "body");
+            writer.write_exchange(&fetched).unwrap();
+        }
+        writer.flush().unwrap();
+
+        // Every exchange fills a file, but its two records are never split between files
+        assert_eq!(writer.files().len(), 3);
+        assert!(writer.files()[0].to_string_lossy().ends_with(".warc"));
+        for file in writer.files() {
+            let types: Vec<_> = read_all(std::slice::from_ref(file))
+                .iter()
+                .map(|r| r.record_type().unwrap().to_string())
+                .collect();
+            assert_eq!(types, vec!["warcinfo", "response", "request"]);
+        }
+        let records = read_all(writer.files());
+        let responses: Vec<_> = records
+            .iter()
+            .filter(|r| r.record_type() == Some("response"))
+            .map(|r| r.target_uri().unwrap())
+            .collect();
+        assert_eq!(
+            responses,
+            vec![
+                "https://example.org/0",
+                "https://example.org/1",
+                "https://example.org/2"
+            ]
+        );
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_encoded_response() {
+        // Other archivers keep the transfer and content codings as they were received
+        let mut gzipped = Vec::new();
+        let mut encoder = flate2::write::GzEncoder::new(&mut gzipped, flate2::Compression::fast());
+        encoder.write_all(b"Hello, world").unwrap();
+        encoder.finish().unwrap();
+
+        let mut block = b"HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Encoding: gzip\r\nTransfer-Encoding: chunked\r\n\r\n".to_vec();
+        block.extend_from_slice(format!("{:x}\r\n", gzipped.len()).as_bytes());
+        block.extend_from_slice(&gzipped);
+        block.extend_from_slice(b"\r\n0\r\n\r\n");
+
+        let record = WarcRecord::new("response", "application/http; msgtype=response", block)
+            .with_header("WARC-Target-URI", "<https://example.org/hello.txt>");
+        let resource = record.to_resource().unwrap();
+        assert_eq!(resource.url, "https://example.org/hello.txt");
+        assert_eq!(resource.content_type(), Some("text/plain"));
+        assert_eq!(resource.body, b"Hello, world");
+
+        let record = WarcRecord::new("metadata", "application/warc-fields", Vec::new())
+            .with_header("WARC-Target-URI", "https://example.org/");
+        assert!(record.to_resource().is_none());
+    }
+}
@@ -0,0 +1,109 @@
+use crate::warc::{WarcError, WarcRecord};
+use flate2::read::MultiGzDecoder;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+/// Reads the records of a WARC file in order
+///
+/// Both plain and gzipped files are read; gzipped files may compress each record
+/// separately or the whole file at once.
+pub struct WarcReader {
+    reader: Box<dyn BufRead + Send>,
+}
+
+impl WarcReader {
+    /// Reads records from a stream, detecting gzip compression
+    pub fn new(reader: impl Read + Send + 'static) -> std::io::Result<Self> {
+        let mut reader = BufReader::new(reader);
+        let gzipped = reader.fill_buf()?.starts_with(b"\x1f\x8b");
+        let reader: Box<dyn BufRead + Send> = if gzipped {
+            Box::new(BufReader::new(MultiGzDecoder::new(reader)))
+        } else {
+            Box::new(reader)
+        };
+        Ok(Self { reader })
+    }
+
+    /// Opens a WARC file
+    pub fn open(path: &Path) -> std::io::Result<Self> {
+        Self::new(File::open(path)?)
+    }
+
+    /// Reads the next record, or `None` at the end of the file
+    pub fn read_record(&mut self) -> Result<Option<WarcRecord>, WarcError> {
+        // Skip the blank lines ending the previous record
+        let version = loop {
+            let mut line = String::new();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            let line = line.trim_end();
+            if !line.is_empty() {
+                break line.to_string();
+            }
+        };
+        if !version.starts_with("WARC/") {
+            return Err(format!("Expected a WARC record, found '{}'", version).into());
+        }
+
+        let mut headers = Vec::new();
+        let mut content_length = None;
+        loop {
+            let mut line = String::new();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Err("Truncated WARC record header".into());
+            }
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            let Some((name, value)) = line.split_once(':') else {
+                return Err(format!("Invalid WARC header line '{}'", line).into());
+            };
+            let (name, value) = (name.trim(), value.trim());
+            if name.eq_ignore_ascii_case("Content-Length") {
+                content_length = Some(value.parse::<u64>()?);
+            } else {
+                headers.push((name.to_string(), value.to_string()));
+            }
+        }
+
+        let content_length = content_length.ok_or("WARC record has no Content-Length")?;
+        let mut block = Vec::new();
+        (&mut self.reader)
+            .take(content_length)
+            .read_to_end(&mut block)?;
+        if (block.len() as u64) < content_length {
+            return Err("Truncated WARC record block".into());
+        }
+
+        Ok(Some(WarcRecord { headers, block }))
+    }
+}
+
+impl Iterator for WarcReader {
+    type Item = Result<WarcRecord, WarcError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record().transpose()
+    }
+}
+
+/// Lists the WARC files at a path: the file itself, or the `.warc` and `.warc.gz`
+/// files in a directory, sorted by name
+pub fn warc_files(path: &Path) -> std::io::Result<Vec<PathBuf>> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut files: Vec<PathBuf> = std::fs::read_dir(path)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| {
+            let name = p.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+            p.is_file() && (name.ends_with(".warc") || name.ends_with(".warc.gz"))
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
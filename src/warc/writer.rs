@@ -0,0 +1,198 @@
+use crate::crawlers::fetch::FetchedResource;
+use crate::results::PageData;
+use crate::warc::{WarcError, WarcOptions, WarcRecord, sha1_digest};
+use flate2::write::GzEncoder;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use url::Url;
+
+/// Writes crawled resources to WARC files, starting a new file once one reaches the size limit
+///
+/// Each file starts with a `warcinfo` record. Files are only created once there's
+/// something to write.
+pub struct WarcWriter {
+    options: WarcOptions,
+    file: Option<BufWriter<File>>,
+    file_size: u64,
+    files: Vec<PathBuf>,
+}
+
+impl WarcWriter {
+    /// Creates a writer; the directory is created with the first file
+    pub fn new(options: WarcOptions) -> Self {
+        Self {
+            options,
+            file: None,
+            file_size: 0,
+            files: Vec::new(),
+        }
+    }
+
+    /// Returns the paths of the files written so far, in order
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    /// Writes a response record and the request that fetched it
+    ///
+    /// Returns the ID of the response record, for records about the same resource
+    /// to refer to.
+    pub fn write_exchange(&mut self, resource: &FetchedResource) -> Result<String, WarcError> {
+        let url = Url::parse(&resource.url)?;
+
+        let reason = http::StatusCode::from_u16(resource.status)
+            .ok()
+            .and_then(|s| s.canonical_reason())
+            .unwrap_or_default();
+        let mut block = format!("HTTP/1.1 {} {}\r\n", resource.status, reason).into_bytes();
+        // The body has already been de-chunked, so the transfer coding no longer applies
+        for (name, value) in &resource.headers {
+            if !name.eq_ignore_ascii_case("transfer-encoding") {
+                block.extend_from_slice(format!("{}: {}\r\n", name, value).as_bytes());
+            }
+        }
+        block.extend_from_slice(b"\r\n");
+        block.extend_from_slice(&resource.body);
+
+        let payload_digest = sha1_digest(&resource.body);
+        let block_digest = sha1_digest(&block);
+        let response = WarcRecord::new("response", "application/http; msgtype=response", block)
+            .with_header("WARC-Target-URI", resource.url.as_str())
+            .with_header("WARC-Payload-Digest", payload_digest)
+            .with_header("WARC-Block-Digest", block_digest);
+        let response_id = response.record_id().unwrap_or_default().to_string();
+
+        let mut target = url.path().to_string();
+        if let Some(query) = url.query() {
+            target.push('?');
+            target.push_str(query);
+        }
+        let request = format!(
+            "GET {} HTTP/1.1\r\nHost: {}\r\n\r\n",
+            target,
+            url.host_str().unwrap_or_default()
+        );
+        let request = WarcRecord::new(
+            "request",
+            "application/http; msgtype=request",
+            request.into_bytes(),
+        )
+        .with_header("WARC-Target-URI", resource.url.as_str())
+        .with_header("WARC-Concurrent-To", response_id.as_str());
+
+        // Both records go in the same file, so a reader always finds them together
+        self.write_records(&[&response, &request])?;
+        Ok(response_id)
+    }
+
+    /// Writes a metadata record and a conversion record with the extracted text of a page
+    ///
+    /// `refers_to` is the ID of the response the page was parsed from. Does nothing
+    /// unless extracted text is enabled.
+    pub fn write_page(&mut self, page: &PageData, refers_to: &str) -> Result<(), WarcError> {
+        if !self.options.extracted_text {
+            return Ok(());
+        }
+
+        let mut fields = String::new();
+        if let Some(title) = &page.title {
+            fields.push_str(&format!("title: {}\r\n", single_line(title)));
+        }
+        if let Some(language) = &page.language {
+            fields.push_str(&format!("language: {}\r\n", language));
+        }
+        if let Some(archive_url) = &page.metadata.archive_url {
+            fields.push_str(&format!("archive-url: {}\r\n", archive_url));
+        }
+        for (link, _) in page.outgoing_links() {
+            fields.push_str(&format!("outlink: {}\r\n", single_line(link)));
+        }
+
+        let metadata = WarcRecord::new("metadata", "application/warc-fields", fields.into_bytes())
+            .with_header("WARC-Target-URI", page.url.as_str())
+            .with_header("WARC-Refers-To", refers_to);
+        let conversion = WarcRecord::new(
+            "conversion",
+            "text/plain; charset=utf-8",
+            page.content.clone().into_bytes(),
+        )
+        .with_header("WARC-Target-URI", page.url.as_str())
+        .with_header("WARC-Refers-To", refers_to);
+
+        self.write_records(&[&metadata, &conversion])
+    }
+
+    /// Writes a record, starting a new file first if the current one is full
+    pub fn write_record(&mut self, record: &WarcRecord) -> Result<(), WarcError> {
+        self.write_records(&[record])
+    }
+
+    /// Writes records to the same file, starting a new file first if the current one is full
+    fn write_records(&mut self, records: &[&WarcRecord]) -> Result<(), WarcError> {
+        if self.file.is_none() || self.file_size >= self.options.max_file_size {
+            self.open_next_file()?;
+        }
+        for record in records {
+            self.append(record)?;
+        }
+        Ok(())
+    }
+
+    /// Flushes the current file
+    pub fn flush(&mut self) -> io::Result<()> {
+        match &mut self.file {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+
+    fn open_next_file(&mut self) -> Result<(), WarcError> {
+        self.flush()?;
+        fs::create_dir_all(&self.options.directory)?;
+
+        let name = format!(
+            "{}-{}-{:05}.warc{}",
+            self.options.prefix,
+            chrono::Utc::now().format("%Y%m%d%H%M%S"),
+            self.files.len(),
+            if self.options.gzip { ".gz" } else { "" }
+        );
+        let path = self.options.directory.join(&name);
+        ::log::info!("Writing WARC file {}", path.display());
+
+        self.file = Some(BufWriter::new(File::create(&path)?));
+        self.file_size = 0;
+        self.files.push(path);
+
+        let info = format!(
+            "software: yield-page/{}\r\nformat: WARC File Format 1.1\r\n",
+            env!("CARGO_PKG_VERSION")
+        );
+        let warcinfo = WarcRecord::new("warcinfo", "application/warc-fields", info.into_bytes())
+            .with_header("WARC-Filename", name);
+        self.append(&warcinfo)
+    }
+
+    /// Writes a record to the current file, as its own gzip member if compressing
+    fn append(&mut self, record: &WarcRecord) -> Result<(), WarcError> {
+        let mut bytes = Vec::new();
+        if self.options.gzip {
+            let mut encoder = GzEncoder::new(&mut bytes, flate2::Compression::default());
+            record.write_to(&mut encoder)?;
+            encoder.finish()?;
+        } else {
+            record.write_to(&mut bytes)?;
+        }
+
+        let file = self.file.as_mut().ok_or("No WARC file open")?;
+        file.write_all(&bytes)?;
+        self.file_size += bytes.len() as u64;
+        Ok(())
+    }
+}
+
+/// Folds a value onto one line so it can't break a `warc-fields` block
+fn single_line(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}